@@ -5,8 +5,54 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct AuthenticationConfiguration {
     pub jwt_secret: String,
+    #[serde(default)]
+    pub jwt_algorithm: JwtAlgorithm,
+    /// PEM private key (PKCS#8, or PKCS#1 for RSA), required for every algorithm except HS256.
+    /// Together with `jwt_algorithm` this only seeds the keyring on first start.
+    #[serde(default)]
+    pub jwt_private_key_path: Option<String>,
     pub jwt_expiration_seconds: u64,
     pub refresh_token_expiration_days: u64,
+    #[serde(default)]
+    pub jwt: JwtConfiguration,
+    #[serde(default)]
+    pub session_cache_seconds: u64,
+    #[serde(default)]
+    pub admin_accounts: Vec<String>,
+    #[serde(default)]
+    pub lockout: LockoutConfiguration,
+    #[serde(default)]
+    pub mfa: MfaConfiguration,
+    #[serde(default)]
+    pub webauthn: WebAuthnConfiguration,
+    #[serde(default)]
+    pub mail: MailConfiguration,
+    #[serde(default)]
+    pub password_policy: PasswordPolicyConfiguration,
+    #[serde(default)]
+    pub password_hashing: PasswordHashingConfiguration,
+    #[serde(default)]
+    pub password_reset: PasswordResetConfiguration,
+    #[serde(default)]
+    pub magic_link: MagicLinkConfiguration,
+    #[serde(default)]
+    pub email_verification: EmailVerificationConfiguration,
+    #[serde(default)]
+    pub oauth: OAuthConfiguration,
+    #[serde(default)]
+    pub oidc: OidcConfiguration,
+    #[serde(default)]
+    pub external_login: ExternalLoginConfiguration,
+    #[serde(default)]
+    pub api_keys: ApiKeyConfiguration,
+    #[serde(default)]
+    pub signing_keys: SigningKeyConfiguration,
+    #[serde(default)]
+    pub cookies: CookieConfiguration,
+    #[serde(default)]
+    pub impersonation: ImpersonationConfiguration,
+    #[serde(default)]
+    pub session_activity: SessionActivityConfiguration,
 }
 
 impl ConfigurationKey for AuthenticationConfiguration {
@@ -14,3 +60,530 @@ impl ConfigurationKey for AuthenticationConfiguration {
         "authentication"
     }
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum JwtAlgorithm {
+    #[default]
+    HS256,
+    RS256,
+    ES256,
+    EdDSA,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct JwtConfiguration {
+    /// `iss` of issued tokens, falls back to the OIDC issuer so access and id tokens match.
+    pub issuer: Option<String>,
+    /// `aud` of first party tokens, tokens for OAuth clients carry the client id instead.
+    pub audience: String,
+    /// Clock skew tolerated when checking `exp` and `nbf`.
+    pub leeway_seconds: u64,
+}
+
+impl Default for JwtConfiguration {
+    fn default() -> Self {
+        JwtConfiguration {
+            issuer: None,
+            audience: "core-auth".to_string(),
+            leeway_seconds: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LockoutConfiguration {
+    pub max_failed_attempts: u32,
+    pub lockout_seconds: u64,
+    pub max_lockout_seconds: u64,
+    pub max_failed_attempts_per_ip: u32,
+    pub ip_window_seconds: u64,
+}
+
+impl LockoutConfiguration {
+    pub fn lockout_duration(&self, failed_attempts: u32) -> chrono::Duration {
+        let exponent = failed_attempts
+            .saturating_sub(self.max_failed_attempts)
+            .min(16);
+        // The bound keeps misconfigured caps from overflowing the lock timestamp
+        let seconds = self
+            .lockout_seconds
+            .saturating_mul(1 << exponent)
+            .min(self.max_lockout_seconds)
+            .min(u32::MAX as u64);
+
+        chrono::Duration::seconds(seconds as i64)
+    }
+}
+
+impl Default for LockoutConfiguration {
+    fn default() -> Self {
+        LockoutConfiguration {
+            max_failed_attempts: 5,
+            lockout_seconds: 60,
+            max_lockout_seconds: 3600,
+            max_failed_attempts_per_ip: 20,
+            ip_window_seconds: 900,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MfaConfiguration {
+    pub issuer: String,
+    pub encryption_key: String,
+    pub challenge_expiration_seconds: u64,
+    pub recovery_code_count: usize,
+    pub allowed_skew_steps: u64,
+}
+
+impl Default for MfaConfiguration {
+    fn default() -> Self {
+        MfaConfiguration {
+            issuer: "core".to_string(),
+            encryption_key: String::new(),
+            challenge_expiration_seconds: 300,
+            recovery_code_count: 10,
+            allowed_skew_steps: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WebAuthnConfiguration {
+    pub rp_id: String,
+    pub rp_name: String,
+    pub origins: Vec<String>,
+    pub challenge_expiration_seconds: u64,
+    pub require_user_verification: bool,
+}
+
+impl Default for WebAuthnConfiguration {
+    fn default() -> Self {
+        WebAuthnConfiguration {
+            rp_id: "localhost".to_string(),
+            rp_name: "core".to_string(),
+            origins: vec!["http://localhost:3000".to_string()],
+            challenge_expiration_seconds: 300,
+            require_user_verification: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MailTransport {
+    Smtp,
+    File,
+    Stdout,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MailConfiguration {
+    /// `file` and `stdout` are meant for development, they expose every mailed token.
+    pub transport: MailTransport,
+    pub from: String,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_starttls: bool,
+    pub file_directory: String,
+}
+
+impl Default for MailConfiguration {
+    fn default() -> Self {
+        MailConfiguration {
+            transport: MailTransport::Smtp,
+            from: "core <no-reply@localhost>".to_string(),
+            smtp_host: "localhost".to_string(),
+            smtp_port: 587,
+            smtp_username: None,
+            smtp_password: None,
+            smtp_starttls: true,
+            file_directory: "mail".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PasswordPolicyConfiguration {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    pub disallow_username: bool,
+    /// Minimum estimated strength from 0 (trivially guessable) to 4 (very unguessable).
+    pub min_strength_score: u8,
+    /// Pwned Passwords SHA-1 list in the "ordered by hash" `HASH:COUNT` format.
+    pub breached_passwords_path: Option<String>,
+    /// Passwords seen at least this often in the breached list are rejected.
+    pub breached_min_count: u64,
+    /// Most recent passwords, including the current one, that cannot be set again.
+    pub history_size: usize,
+    /// Password sign-ins require a password change once the password is older than this.
+    pub max_age_days: Option<u64>,
+    pub change_token_expiration_seconds: u64,
+}
+
+impl Default for PasswordPolicyConfiguration {
+    fn default() -> Self {
+        PasswordPolicyConfiguration {
+            min_length: 8,
+            max_length: 128,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            disallow_username: true,
+            min_strength_score: 2,
+            breached_passwords_path: None,
+            breached_min_count: 1,
+            history_size: 0,
+            max_age_days: None,
+            change_token_expiration_seconds: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PasswordHashingConfiguration {
+    /// Argon2id memory cost in KiB.
+    pub memory_cost_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Server side secret mixed into every hash, it cannot be changed once hashes use it.
+    pub pepper: Option<String>,
+}
+
+impl Default for PasswordHashingConfiguration {
+    fn default() -> Self {
+        PasswordHashingConfiguration {
+            memory_cost_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+            pepper: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PasswordResetConfiguration {
+    pub token_expiration_seconds: u64,
+    pub reset_url: String,
+}
+
+impl Default for PasswordResetConfiguration {
+    fn default() -> Self {
+        PasswordResetConfiguration {
+            token_expiration_seconds: 3600,
+            reset_url: "http://localhost:3000/reset-password?token={token}".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MagicLinkConfiguration {
+    pub enabled: bool,
+    pub token_expiration_seconds: u64,
+    pub link_url: String,
+    /// Wrong codes tolerated before the code is invalidated.
+    pub max_code_attempts: u32,
+    pub request_window_seconds: u64,
+    /// Further requests within the window are dropped without sending a mail.
+    pub max_requests_per_account: usize,
+    pub max_requests_per_ip: usize,
+}
+
+impl Default for MagicLinkConfiguration {
+    fn default() -> Self {
+        MagicLinkConfiguration {
+            enabled: false,
+            token_expiration_seconds: 600,
+            link_url: "http://localhost:3000/magic-link?token={token}".to_string(),
+            max_code_attempts: 5,
+            request_window_seconds: 900,
+            max_requests_per_account: 3,
+            max_requests_per_ip: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EmailVerificationConfiguration {
+    pub require_verified_email: bool,
+    pub token_expiration_seconds: u64,
+    pub resend_interval_seconds: u64,
+    pub verify_url: String,
+}
+
+impl Default for EmailVerificationConfiguration {
+    fn default() -> Self {
+        EmailVerificationConfiguration {
+            require_verified_email: false,
+            token_expiration_seconds: 86400,
+            resend_interval_seconds: 60,
+            verify_url: "http://localhost:3000/verify-email?token={token}".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OAuthConfiguration {
+    pub authorization_code_expiration_seconds: u64,
+    pub require_pkce: bool,
+}
+
+impl Default for OAuthConfiguration {
+    fn default() -> Self {
+        OAuthConfiguration {
+            authorization_code_expiration_seconds: 60,
+            require_pkce: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OidcConfiguration {
+    pub issuer: String,
+    pub id_token_expiration_seconds: u64,
+}
+
+impl Default for OidcConfiguration {
+    fn default() -> Self {
+        OidcConfiguration {
+            issuer: "http://localhost:3000".to_string(),
+            id_token_expiration_seconds: 3600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExternalLoginConfiguration {
+    pub state_expiration_seconds: u64,
+    pub allow_signup: bool,
+    pub metadata_cache_seconds: u64,
+    pub providers: Vec<ExternalProviderConfiguration>,
+}
+
+impl Default for ExternalLoginConfiguration {
+    fn default() -> Self {
+        ExternalLoginConfiguration {
+            state_expiration_seconds: 600,
+            allow_signup: true,
+            metadata_cache_seconds: 3600,
+            providers: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalProviderConfiguration {
+    pub id: String,
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    #[serde(default = "default_external_provider_scopes")]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub authorization_endpoint: Option<String>,
+    #[serde(default)]
+    pub token_endpoint: Option<String>,
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+}
+
+fn default_external_provider_scopes() -> Vec<String> {
+    vec![
+        "openid".to_string(),
+        "email".to_string(),
+        "profile".to_string(),
+    ]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiKeyConfiguration {
+    pub max_keys_per_account: usize,
+    pub max_expiration_days: Option<u64>,
+    pub last_used_update_interval_seconds: u64,
+}
+
+impl Default for ApiKeyConfiguration {
+    fn default() -> Self {
+        ApiKeyConfiguration {
+            max_keys_per_account: 25,
+            max_expiration_days: None,
+            last_used_update_interval_seconds: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CookieSameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CookieConfiguration {
+    /// Lets sign-ins opt into cookies, clients sending `Authorization` headers are unaffected.
+    pub enabled: bool,
+    pub refresh_cookie_name: String,
+    /// Scoped to the refresh endpoint so the refresh token is not sent with every request.
+    pub refresh_cookie_path: String,
+    /// Also sets the access token as a cookie, otherwise it is only returned in the body.
+    pub access_cookie: bool,
+    pub access_cookie_name: String,
+    /// Readable by scripts, state-changing requests echo it in `csrf_header_name`.
+    pub csrf_cookie_name: String,
+    pub csrf_header_name: String,
+    pub domain: Option<String>,
+    pub path: String,
+    pub secure: bool,
+    pub same_site: CookieSameSite,
+}
+
+impl Default for CookieConfiguration {
+    fn default() -> Self {
+        CookieConfiguration {
+            enabled: false,
+            refresh_cookie_name: "refresh_token".to_string(),
+            refresh_cookie_path: "/session/refresh".to_string(),
+            access_cookie: false,
+            access_cookie_name: "access_token".to_string(),
+            csrf_cookie_name: "csrf_token".to_string(),
+            csrf_header_name: "X-CSRF-Token".to_string(),
+            domain: None,
+            path: "/".to_string(),
+            secure: true,
+            same_site: CookieSameSite::Strict,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImpersonationConfiguration {
+    /// Lifetime of impersonation sessions, refreshing does not extend it.
+    pub session_expiration_seconds: u64,
+}
+
+impl Default for ImpersonationConfiguration {
+    fn default() -> Self {
+        ImpersonationConfiguration {
+            session_expiration_seconds: 3600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionActivityConfiguration {
+    /// Authenticated requests record session activity at most this often, refreshes always do.
+    pub update_interval_seconds: u64,
+    pub max_name_length: usize,
+}
+
+impl Default for SessionActivityConfiguration {
+    fn default() -> Self {
+        SessionActivityConfiguration {
+            update_interval_seconds: 300,
+            max_name_length: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SigningKeyConfiguration {
+    /// How often each instance reloads the keyring from the database.
+    pub refresh_seconds: u64,
+    /// How long a replaced key keeps verifying tokens after a new key was activated.
+    pub rotation_overlap_seconds: u64,
+    /// Base64 encoded 32 byte key, private keys are stored unencrypted when empty.
+    pub encryption_key: String,
+}
+
+impl Default for SigningKeyConfiguration {
+    fn default() -> Self {
+        SigningKeyConfiguration {
+            refresh_seconds: 30,
+            rotation_overlap_seconds: 86400,
+            encryption_key: String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubles_lockout_per_attempt_past_the_threshold() {
+        let lockout = LockoutConfiguration::default();
+        let cases = [(5, 60), (6, 120), (7, 240), (8, 480), (9, 960), (10, 1920)];
+
+        for (failed_attempts, seconds) in cases {
+            assert_eq!(
+                lockout.lockout_duration(failed_attempts),
+                chrono::Duration::seconds(seconds),
+                "after {} failed attempts",
+                failed_attempts
+            );
+        }
+    }
+
+    #[test]
+    fn caps_lockout_at_the_maximum() {
+        let lockout = LockoutConfiguration::default();
+
+        for failed_attempts in [11, 12, 21, 100] {
+            assert_eq!(
+                lockout.lockout_duration(failed_attempts),
+                chrono::Duration::seconds(3600)
+            );
+        }
+    }
+
+    #[test]
+    fn does_not_overflow_at_high_attempt_counts() {
+        let lockout = LockoutConfiguration {
+            max_failed_attempts: 1,
+            lockout_seconds: u64::MAX / 2,
+            max_lockout_seconds: u64::MAX,
+            ..LockoutConfiguration::default()
+        };
+
+        let longest = chrono::Duration::seconds(u32::MAX as i64);
+        assert_eq!(lockout.lockout_duration(u32::MAX), longest);
+        assert_eq!(lockout.lockout_duration(1), longest);
+        assert!(chrono::Utc::now().checked_add_signed(longest).is_some());
+
+        assert_eq!(
+            LockoutConfiguration::default().lockout_duration(u32::MAX),
+            chrono::Duration::seconds(3600)
+        );
+    }
+}
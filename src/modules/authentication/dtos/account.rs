@@ -5,6 +5,14 @@ use crate::{common::model::DatabaseModel, modules::authentication::account_model
 pub struct AccountDTO {
     pub id: String,
     pub username: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub is_locked: bool,
+    pub locked_until: Option<BaseDateTime>,
+    pub failed_sign_in_attempts: u32,
+    pub totp_enabled: bool,
+    pub must_change_password: bool,
+    pub password_changed_at: BaseDateTime,
     pub created_at: BaseDateTime,
     pub updated_at: BaseDateTime,
 }
@@ -13,12 +21,21 @@ pub struct AccountDTO {
 pub struct CreateAccountRequestDTO {
     pub username: String,
     pub password: String,
+    #[serde(default)]
+    pub email: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateAccountRequestDTO {
     pub username: Option<String>,
     pub password: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreatePasswordHistoryOptions {
+    pub account_id: BaseId,
+    pub password_hash: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -30,7 +47,15 @@ impl From<AccountModel> for AccountDTO {
     fn from(account: AccountModel) -> Self {
         AccountDTO {
             id: AccountModel::to_named_format(&account.id),
+            is_locked: account.is_locked(),
+            password_changed_at: account.password_changed_at().clone(),
             username: account.username,
+            email: account.email,
+            email_verified: account.email_verified,
+            locked_until: account.locked_until,
+            failed_sign_in_attempts: account.failed_sign_in_attempts,
+            totp_enabled: account.totp_enabled,
+            must_change_password: account.must_change_password,
             created_at: account.created_at,
             updated_at: account.updated_at,
         }
@@ -42,6 +67,14 @@ impl From<&AccountModel> for AccountDTO {
         AccountDTO {
             id: AccountModel::to_named_format(&account.id),
             username: account.username.clone(),
+            email: account.email.clone(),
+            email_verified: account.email_verified,
+            is_locked: account.is_locked(),
+            locked_until: account.locked_until.clone(),
+            failed_sign_in_attempts: account.failed_sign_in_attempts,
+            totp_enabled: account.totp_enabled,
+            must_change_password: account.must_change_password,
+            password_changed_at: account.password_changed_at().clone(),
             created_at: account.created_at.clone(),
             updated_at: account.updated_at.clone(),
         }
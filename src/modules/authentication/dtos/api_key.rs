@@ -0,0 +1,52 @@
+use super::prelude::*;
+use crate::{common::model::DatabaseModel, modules::authentication::models::api_key::ApiKeyModel};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateApiKeyOptions {
+    pub account_id: BaseId,
+    pub name: String,
+    pub token_hash: String,
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<BaseDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateApiKeyRequestDto {
+    pub name: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub expires_in_days: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKeyDTO {
+    pub id: String,
+    pub name: String,
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<BaseDateTime>,
+    pub last_used_at: Option<BaseDateTime>,
+    pub created_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKeySecretDto {
+    pub api_key: ApiKeyDTO,
+    pub token: String,
+}
+
+impl From<ApiKeyModel> for ApiKeyDTO {
+    fn from(api_key: ApiKeyModel) -> Self {
+        ApiKeyDTO {
+            id: ApiKeyModel::to_named_format(&api_key.id),
+            name: api_key.name,
+            token_prefix: api_key.token_prefix,
+            scopes: api_key.scopes,
+            expires_at: api_key.expires_at,
+            last_used_at: api_key.last_used_at,
+            created_at: api_key.created_at,
+        }
+    }
+}
@@ -1,10 +1,21 @@
-use super::prelude::*;
+use super::{mfa::MfaChallengeResponseDto, prelude::*};
 use chrono::{DateTime, Utc};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SignInRequestDto {
     pub username: String,
     pub password: String,
+    /// Sets the session as cookies, requires cookie sessions to be enabled.
+    #[serde(default)]
+    pub use_cookies: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SignUpRequestDto {
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub email: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -22,3 +33,51 @@ pub struct AuthenticationResponseDto {
     pub access_token_expires_at: DateTime<Utc>,
     pub refresh_token_expires_at: DateTime<Utc>,
 }
+
+/// Returned in place of `AuthenticationResponseDto` when the tokens were set as cookies.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CookieAuthenticationResponseDto {
+    pub session_id: String,
+    pub account_id: String,
+    /// Left out when the access token is set as a cookie as well.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    pub access_token_expires_at: DateTime<Utc>,
+    pub refresh_token_expires_at: DateTime<Utc>,
+    pub csrf_token: String,
+}
+
+/// Returned by sign-up in place of a session while verified email addresses are required.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VerificationPendingDto {
+    pub account_id: String,
+    pub email_verification_required: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum SignUpResultDto {
+    Authenticated(AuthenticationResponseDto),
+    VerificationPending(VerificationPendingDto),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasswordChangeRequiredDto {
+    pub password_change_required: bool,
+    pub change_token: String,
+    pub change_token_expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangePasswordRequestDto {
+    pub change_token: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum SignInResponseDto {
+    Authenticated(AuthenticationResponseDto),
+    MfaRequired(MfaChallengeResponseDto),
+    PasswordChangeRequired(PasswordChangeRequiredDto),
+}
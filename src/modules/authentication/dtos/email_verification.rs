@@ -0,0 +1,14 @@
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateEmailVerificationTokenOptions {
+    pub account_id: BaseId,
+    pub email: String,
+    pub token_hash: String,
+    pub expires_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfirmEmailRequestDto {
+    pub token: String,
+}
@@ -0,0 +1,69 @@
+use super::prelude::*;
+use crate::modules::authentication::{
+    config::authentication::ExternalProviderConfiguration, models::identity::IdentityModel,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateIdentityOptions {
+    pub account_id: BaseId,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub last_used_at: Option<BaseDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateExternalLoginStateOptions {
+    pub state_hash: String,
+    pub provider: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub expires_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExternalProviderDto {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExternalAuthorizationResponseDto {
+    pub redirect_to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExternalCallbackRequestDto {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IdentityDTO {
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub last_used_at: Option<BaseDateTime>,
+    pub created_at: BaseDateTime,
+}
+
+impl From<&ExternalProviderConfiguration> for ExternalProviderDto {
+    fn from(provider: &ExternalProviderConfiguration) -> Self {
+        ExternalProviderDto {
+            id: provider.id.clone(),
+            name: provider.name.clone(),
+        }
+    }
+}
+
+impl From<IdentityModel> for IdentityDTO {
+    fn from(identity: IdentityModel) -> Self {
+        IdentityDTO {
+            provider: identity.provider,
+            subject: identity.subject,
+            email: identity.email,
+            last_used_at: identity.last_used_at,
+            created_at: identity.created_at,
+        }
+    }
+}
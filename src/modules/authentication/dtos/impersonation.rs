@@ -0,0 +1,42 @@
+use super::prelude::*;
+use crate::{
+    common::model::DatabaseModel,
+    modules::authentication::{
+        account_model::AccountModel, models::impersonation_event::ImpersonationEventModel,
+        session_model::SessionModel,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateImpersonationEventOptions {
+    pub actor_id: BaseId,
+    pub account_id: BaseId,
+    pub session_id: BaseId,
+    pub action: String,
+    pub ip_address: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImpersonationEventDTO {
+    pub id: String,
+    pub actor_id: String,
+    pub account_id: String,
+    pub session_id: String,
+    pub action: String,
+    pub ip_address: String,
+    pub created_at: BaseDateTime,
+}
+
+impl From<ImpersonationEventModel> for ImpersonationEventDTO {
+    fn from(event: ImpersonationEventModel) -> Self {
+        ImpersonationEventDTO {
+            id: ImpersonationEventModel::to_named_format(&event.id),
+            actor_id: AccountModel::to_named_format(&event.actor_id),
+            account_id: AccountModel::to_named_format(&event.account_id),
+            session_id: SessionModel::to_named_format(&event.session_id),
+            action: event.action,
+            ip_address: event.ip_address,
+            created_at: event.created_at,
+        }
+    }
+}
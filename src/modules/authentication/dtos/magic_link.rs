@@ -0,0 +1,25 @@
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateMagicLinkTokenOptions {
+    pub account_id: BaseId,
+    pub token_hash: String,
+    pub code_hash: String,
+    pub ip_address: String,
+    pub expires_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MagicLinkRequestDto {
+    pub email: String,
+}
+
+/// Redeems either the token from the link, or the mailed code together with the email address.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MagicLinkVerifyRequestDto {
+    pub token: Option<String>,
+    pub email: Option<String>,
+    pub code: Option<String>,
+    #[serde(default)]
+    pub use_cookies: bool,
+}
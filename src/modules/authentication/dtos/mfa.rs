@@ -0,0 +1,35 @@
+use super::prelude::*;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TotpEnrollmentResponseDto {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TotpCodeRequestDto {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecoveryCodesResponseDto {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MfaChallengeResponseDto {
+    pub mfa_required: bool,
+    pub challenge_token: String,
+    pub challenge_expires_at: DateTime<Utc>,
+    pub methods: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MfaVerifyRequestDto {
+    pub challenge_token: String,
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+    #[serde(default)]
+    pub use_cookies: bool,
+}
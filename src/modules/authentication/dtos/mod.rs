@@ -1,6 +1,19 @@
 pub mod account;
+pub mod api_key;
 pub mod authentication;
+pub mod email_verification;
+pub mod external_login;
+pub mod impersonation;
+pub mod magic_link;
+pub mod mfa;
+pub mod oauth;
+pub mod oidc;
+pub mod password_reset;
+pub mod role;
+pub mod service_account;
 pub mod session;
+pub mod signing_key;
+pub mod webauthn;
 
 pub(super) mod prelude {
     pub use crate::modules::base::exports::{BaseDateTime, BaseId};
@@ -0,0 +1,185 @@
+use super::prelude::*;
+use crate::{
+    common::model::DatabaseModel, modules::authentication::models::oauth_client::OAuthClientModel,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateOAuthClientOptions {
+    pub name: String,
+    pub client_secret_hash: Option<String>,
+    pub redirect_uris: Vec<String>,
+    pub allowed_scopes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateRevokedTokenOptions {
+    pub token_id: String,
+    pub expires_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateAuthorizationCodeOptions {
+    pub code_hash: String,
+    pub client_id: BaseId,
+    pub account_id: BaseId,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    pub code_challenge: Option<String>,
+    pub nonce: Option<String>,
+    pub expires_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateOAuthClientRequestDto {
+    pub name: String,
+    #[serde(default = "default_confidential")]
+    pub confidential: bool,
+    pub redirect_uris: Vec<String>,
+    #[serde(default)]
+    pub allowed_scopes: Vec<String>,
+}
+
+fn default_confidential() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateOAuthClientRequestDto {
+    pub name: Option<String>,
+    pub redirect_uris: Option<Vec<String>>,
+    pub allowed_scopes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OAuthClientDTO {
+    pub client_id: String,
+    pub name: String,
+    pub confidential: bool,
+    pub redirect_uris: Vec<String>,
+    pub allowed_scopes: Vec<String>,
+    pub created_at: BaseDateTime,
+    pub updated_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OAuthClientSecretDto {
+    pub client: OAuthClientDTO,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthorizeRequestDto {
+    pub response_type: String,
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+    pub nonce: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthorizationDetailsDto {
+    pub client_id: String,
+    pub client_name: String,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthorizationResponseDto {
+    pub redirect_to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TokenRequestDto {
+    pub grant_type: String,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TokenResponseDto {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    pub scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IntrospectionRequestDto {
+    pub token: String,
+    pub token_type_hint: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RevocationRequestDto {
+    pub token: String,
+    pub token_type_hint: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+/// Introspection response as defined in RFC 7662 section 2.2.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct IntrospectionResponseDto {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+impl IntrospectionResponseDto {
+    pub fn inactive() -> Self {
+        Self::default()
+    }
+}
+
+impl From<OAuthClientModel> for OAuthClientDTO {
+    fn from(client: OAuthClientModel) -> Self {
+        OAuthClientDTO {
+            client_id: OAuthClientModel::to_named_format(&client.id),
+            confidential: client.is_confidential(),
+            name: client.name,
+            redirect_uris: client.redirect_uris,
+            allowed_scopes: client.allowed_scopes,
+            created_at: client.created_at,
+            updated_at: client.updated_at,
+        }
+    }
+}
+
+impl From<&OAuthClientModel> for OAuthClientDTO {
+    fn from(client: &OAuthClientModel) -> Self {
+        OAuthClientDTO {
+            client_id: OAuthClientModel::to_named_format(&client.id),
+            name: client.name.clone(),
+            confidential: client.is_confidential(),
+            redirect_uris: client.redirect_uris.clone(),
+            allowed_scopes: client.allowed_scopes.clone(),
+            created_at: client.created_at.clone(),
+            updated_at: client.updated_at.clone(),
+        }
+    }
+}
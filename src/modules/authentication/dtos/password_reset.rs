@@ -0,0 +1,19 @@
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreatePasswordResetTokenOptions {
+    pub account_id: BaseId,
+    pub token_hash: String,
+    pub expires_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ForgotPasswordRequestDto {
+    pub username: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResetPasswordRequestDto {
+    pub token: String,
+    pub new_password: String,
+}
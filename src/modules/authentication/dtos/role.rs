@@ -0,0 +1,67 @@
+use super::prelude::*;
+use crate::{common::model::DatabaseModel, modules::authentication::models::role::RoleModel};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoleDTO {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+    pub created_at: BaseDateTime,
+    pub updated_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateRoleRequestDTO {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateRoleRequestDTO {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub permissions: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateAccountRoleOptions {
+    pub account_id: BaseId,
+    pub role_id: BaseId,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountRolesDTO {
+    pub account_id: String,
+    pub roles: Vec<RoleDTO>,
+    pub permissions: Vec<String>,
+}
+
+impl From<RoleModel> for RoleDTO {
+    fn from(role: RoleModel) -> Self {
+        RoleDTO {
+            id: RoleModel::to_named_format(&role.id),
+            name: role.name,
+            description: role.description,
+            permissions: role.permissions,
+            created_at: role.created_at,
+            updated_at: role.updated_at,
+        }
+    }
+}
+
+impl From<&RoleModel> for RoleDTO {
+    fn from(role: &RoleModel) -> Self {
+        RoleDTO {
+            id: RoleModel::to_named_format(&role.id),
+            name: role.name.clone(),
+            description: role.description.clone(),
+            permissions: role.permissions.clone(),
+            created_at: role.created_at.clone(),
+            updated_at: role.updated_at.clone(),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use super::prelude::*;
+use crate::{
+    common::model::DatabaseModel,
+    modules::authentication::models::service_account::ServiceAccountModel,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateServiceAccountOptions {
+    pub name: String,
+    pub description: String,
+    pub client_secret_hash: String,
+    pub scopes: Vec<String>,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateServiceAccountRequestDto {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateServiceAccountRequestDto {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub scopes: Option<Vec<String>>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceAccountDTO {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub scopes: Vec<String>,
+    pub is_active: bool,
+    pub created_at: BaseDateTime,
+    pub updated_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceAccountSecretDto {
+    pub service_account: ServiceAccountDTO,
+    pub client_secret: String,
+}
+
+impl From<ServiceAccountModel> for ServiceAccountDTO {
+    fn from(service_account: ServiceAccountModel) -> Self {
+        ServiceAccountDTO {
+            id: ServiceAccountModel::to_named_format(&service_account.id),
+            name: service_account.name,
+            description: service_account.description,
+            scopes: service_account.scopes,
+            is_active: service_account.is_active,
+            created_at: service_account.created_at,
+            updated_at: service_account.updated_at,
+        }
+    }
+}
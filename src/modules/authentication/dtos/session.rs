@@ -10,8 +10,13 @@ pub struct CreateSessionOptions {
     pub refresh_hash: String,
     pub expires_at: BaseDateTime,
     pub user_agent: String,
+    pub device_label: String,
     pub ip_address: String,
     pub is_active: bool,
+    pub audience: String,
+    pub scopes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impersonator_id: Option<BaseId>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -21,24 +26,30 @@ pub struct RefreshSessionOptions {
     pub refresh_token: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RenameSessionRequestDto {
+    /// Clears the name when empty or missing.
+    pub name: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SessionDTO {
     pub id: String,
     pub account_id: String,
+    pub name: Option<String>,
+    pub device_label: String,
     pub ip_address: String,
     pub user_agent: String,
+    pub last_ip_address: String,
+    pub last_used_at: BaseDateTime,
+    pub created_at: BaseDateTime,
     pub expires_at: BaseDateTime,
+    pub is_active: bool,
 }
 
 impl From<SessionModel> for SessionDTO {
     fn from(session: SessionModel) -> Self {
-        SessionDTO {
-            id: SessionModel::to_named_format(&session.id),
-            account_id: AccountModel::to_named_format(&session.account_id),
-            ip_address: session.ip_address,
-            user_agent: session.user_agent,
-            expires_at: session.expires_at,
-        }
+        SessionDTO::from(&session)
     }
 }
 
@@ -47,9 +58,15 @@ impl From<&SessionModel> for SessionDTO {
         SessionDTO {
             id: SessionModel::to_named_format(&session.id),
             account_id: AccountModel::to_named_format(&session.account_id),
+            name: session.name.clone(),
+            device_label: session.device_label.clone(),
             ip_address: session.ip_address.clone(),
             user_agent: session.user_agent.clone(),
+            last_ip_address: session.last_ip_address().to_string(),
+            last_used_at: session.last_used_at().clone(),
+            created_at: session.created_at.clone(),
             expires_at: session.expires_at.clone(),
+            is_active: session.is_active,
         }
     }
 }
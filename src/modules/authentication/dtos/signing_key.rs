@@ -0,0 +1,45 @@
+use super::prelude::*;
+use crate::modules::authentication::{
+    config::authentication::JwtAlgorithm,
+    models::signing_key::{SigningKeyModel, SigningKeyStatus},
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateSigningKeyOptions {
+    pub kid: String,
+    pub algorithm: JwtAlgorithm,
+    pub key_material: String,
+    pub is_encrypted: bool,
+    pub status: SigningKeyStatus,
+    pub activated_at: Option<BaseDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateSigningKeyRequestDto {
+    pub algorithm: JwtAlgorithm,
+    /// PEM private key to import, a key is generated when omitted (not supported for RS256).
+    pub private_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SigningKeyDTO {
+    pub kid: String,
+    pub algorithm: JwtAlgorithm,
+    pub status: SigningKeyStatus,
+    pub activated_at: Option<BaseDateTime>,
+    pub retire_at: Option<BaseDateTime>,
+    pub created_at: BaseDateTime,
+}
+
+impl From<SigningKeyModel> for SigningKeyDTO {
+    fn from(signing_key: SigningKeyModel) -> Self {
+        SigningKeyDTO {
+            kid: signing_key.kid,
+            algorithm: signing_key.algorithm,
+            status: signing_key.status,
+            activated_at: signing_key.activated_at,
+            retire_at: signing_key.retire_at,
+            created_at: signing_key.created_at,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use super::prelude::*;
+use crate::{
+    common::model::DatabaseModel, modules::authentication::models::credential::CredentialModel,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateCredentialOptions {
+    pub account_id: BaseId,
+    pub credential_id: String,
+    pub public_key: String,
+    pub algorithm: i64,
+    pub sign_count: u32,
+    pub name: String,
+    pub transports: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateWebAuthnChallengeOptions {
+    pub challenge: String,
+    pub ceremony: String,
+    pub account_id: Option<BaseId>,
+    pub expires_at: BaseDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialDTO {
+    pub id: String,
+    pub name: String,
+    pub transports: Vec<String>,
+    pub created_at: BaseDateTime,
+    pub last_used_at: Option<BaseDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelyingPartyDto {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntityDto {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialParameterDto {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub alg: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialDescriptorDto {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub id: String,
+    pub transports: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelectionDto {
+    pub resident_key: String,
+    pub user_verification: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCredentialCreationOptionsDto {
+    pub rp: RelyingPartyDto,
+    pub user: UserEntityDto,
+    pub challenge: String,
+    pub pub_key_cred_params: Vec<CredentialParameterDto>,
+    pub timeout: u64,
+    pub exclude_credentials: Vec<CredentialDescriptorDto>,
+    pub authenticator_selection: AuthenticatorSelectionDto,
+    pub attestation: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCredentialRequestOptionsDto {
+    pub challenge: String,
+    pub rp_id: String,
+    pub timeout: u64,
+    pub allow_credentials: Vec<CredentialDescriptorDto>,
+    pub user_verification: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebAuthnRegistrationOptionsDto {
+    pub challenge_id: String,
+    pub public_key: PublicKeyCredentialCreationOptionsDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebAuthnAuthenticationOptionsDto {
+    pub challenge_id: String,
+    pub public_key: PublicKeyCredentialRequestOptionsDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponseDto {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub attestation_object: String,
+    #[serde(default)]
+    pub transports: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationCredentialDto {
+    pub id: String,
+    pub raw_id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub response: AttestationResponseDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponseDto {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    pub user_handle: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticationCredentialDto {
+    pub id: String,
+    pub raw_id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub response: AssertionResponseDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebAuthnRegistrationRequestDto {
+    pub challenge_id: String,
+    pub name: Option<String>,
+    pub credential: RegistrationCredentialDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebAuthnAuthenticationStartRequestDto {
+    pub username: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebAuthnAuthenticationRequestDto {
+    pub challenge_id: String,
+    pub credential: AuthenticationCredentialDto,
+}
+
+impl From<CredentialModel> for CredentialDTO {
+    fn from(credential: CredentialModel) -> Self {
+        CredentialDTO {
+            id: CredentialModel::to_named_format(&credential.id),
+            name: credential.name,
+            transports: credential.transports,
+            created_at: credential.created_at,
+            last_used_at: credential.last_used_at,
+        }
+    }
+}
+
+impl From<&CredentialModel> for CredentialDTO {
+    fn from(credential: &CredentialModel) -> Self {
+        CredentialDTO {
+            id: CredentialModel::to_named_format(&credential.id),
+            name: credential.name.clone(),
+            transports: credential.transports.clone(),
+            created_at: credential.created_at.clone(),
+            last_used_at: credential.last_used_at.clone(),
+        }
+    }
+}
@@ -19,11 +19,15 @@ pub enum AuthenticationClientError {
     AccountNotFound,
     #[error("User account already exists.")]
     AccountAlreadyExists,
+    #[error("Too many failed sign-in attempts, try again later.")]
+    TooManySignInAttempts,
 
     #[error("Insufficient refresh token")]
     InvalidRefreshToken,
     #[error("Expired refresh token")]
     ExpiredRefreshToken,
+    #[error("Refresh token has already been used")]
+    ReusedRefreshToken,
     #[error("Invalid access token")]
     InvalidAccessToken,
     #[error("Expired access token")]
@@ -31,14 +35,163 @@ pub enum AuthenticationClientError {
 
     #[error("Invalid session Id")]
     InvalidSessionId,
+    #[error("Session name must be at most {0} characters")]
+    SessionNameTooLong(usize),
     #[error("Invalid account Id")]
     InvalidAccountId,
 
     #[error("Authentication required.")]
     AuthenticationRequired,
 
+    #[error("Invalid two-factor authentication code.")]
+    InvalidMfaCode,
+    #[error("Invalid or expired two-factor authentication challenge.")]
+    InvalidMfaChallenge,
+    #[error("Two-factor authentication is already enabled.")]
+    MfaAlreadyEnabled,
+    #[error("TOTP is not available, no MFA encryption key is configured.")]
+    TotpNotConfigured,
+    #[error("Two-factor authentication is not enabled.")]
+    MfaNotEnabled,
+    #[error("Two-factor authentication enrollment has not been started.")]
+    MfaEnrollmentNotStarted,
+
     #[error("Session not found.")]
     SessionNotFound,
+    #[error("Cookie sessions are not enabled.")]
+    CookieSessionsDisabled,
+
+    #[error("Invalid or expired WebAuthn challenge.")]
+    InvalidWebAuthnChallenge,
+    #[error("Invalid WebAuthn response: {0}")]
+    InvalidWebAuthnResponse(String),
+    #[error("Credential not found.")]
+    CredentialNotFound,
+    #[error("Credential is already registered.")]
+    CredentialAlreadyRegistered,
+    #[error("Invalid credential Id")]
+    InvalidCredentialId,
+
+    #[error("Invalid or expired password reset token.")]
+    InvalidPasswordResetToken,
+    #[error("Invalid or expired password change token.")]
+    InvalidPasswordChangeToken,
+
+    #[error("Passwordless sign-in is not enabled.")]
+    MagicLinkDisabled,
+    #[error("Invalid or expired sign-in link or code.")]
+    InvalidMagicLink,
+    #[error("Too many sign-in links requested, try again later.")]
+    TooManyMagicLinkRequests,
+
+    #[error("Password must be at least {0} characters long.")]
+    PasswordTooShort(usize),
+    #[error("Password must be at most {0} characters long.")]
+    PasswordTooLong(usize),
+    #[error("Password must contain a lowercase letter.")]
+    PasswordMissingLowercase,
+    #[error("Password must contain an uppercase letter.")]
+    PasswordMissingUppercase,
+    #[error("Password must contain a digit.")]
+    PasswordMissingDigit,
+    #[error("Password must contain a symbol.")]
+    PasswordMissingSymbol,
+    #[error("Password must not contain the username.")]
+    PasswordContainsUsername,
+    #[error("Password is too easy to guess.")]
+    PasswordTooWeak,
+    #[error("Password has appeared in a data breach, please choose a different one.")]
+    PasswordBreached,
+    #[error("Password must not match any of the last {0} passwords.")]
+    PasswordReused(usize),
+
+    #[error("Invalid email address.")]
+    InvalidEmail,
+    #[error("Email address is already in use.")]
+    EmailAlreadyInUse,
+    #[error("Email address has not been verified.")]
+    EmailNotVerified,
+    #[error("Email address is already verified.")]
+    EmailAlreadyVerified,
+    #[error("Account has no email address.")]
+    EmailMissing,
+    #[error("Invalid or expired email verification token.")]
+    InvalidEmailVerificationToken,
+    #[error("A verification mail was sent recently, please try again later.")]
+    EmailVerificationThrottled,
+
+    #[error("OAuth client not found.")]
+    OAuthClientNotFound,
+    #[error("Invalid OAuth client Id")]
+    InvalidOAuthClientId,
+    #[error("Client authentication failed.")]
+    InvalidClient,
+    #[error("Invalid redirect URI.")]
+    InvalidRedirectUri,
+    #[error("Invalid OAuth request: {0}")]
+    InvalidOAuthRequest(String),
+    #[error("The requested scope is invalid or not allowed for this client.")]
+    InvalidScope,
+    #[error("The authorization grant is invalid, expired or revoked.")]
+    InvalidGrant,
+    #[error("Unsupported grant type.")]
+    UnsupportedGrantType,
+    #[error("Unsupported response type.")]
+    UnsupportedResponseType,
+
+    #[error("External identity provider not found.")]
+    ExternalProviderNotFound,
+    #[error("Invalid or expired external sign-in state.")]
+    InvalidExternalLoginState,
+    #[error("External sign-in failed: {0}")]
+    ExternalLoginFailed(String),
+    #[error("No account is linked to this external identity.")]
+    ExternalIdentityNotLinked,
+
+    #[error("API key not found.")]
+    ApiKeyNotFound,
+    #[error("Invalid API key Id")]
+    InvalidApiKeyId,
+    #[error("Invalid or expired API key.")]
+    InvalidApiKey,
+    #[error("The maximum number of API keys has been reached.")]
+    ApiKeyLimitReached,
+    #[error("API keys cannot be granted scopes the account does not have.")]
+    InvalidApiKeyScope,
+    #[error("This action requires an interactive session.")]
+    InteractiveSessionRequired,
+
+    #[error("Service account not found.")]
+    ServiceAccountNotFound,
+    #[error("Service account already exists.")]
+    ServiceAccountAlreadyExists,
+    #[error("Invalid service account Id")]
+    InvalidServiceAccountId,
+
+    #[error("Signing key not found.")]
+    SigningKeyNotFound,
+    #[error("The active signing key cannot be retired.")]
+    SigningKeyActive,
+    #[error("Retired signing keys cannot be activated.")]
+    SigningKeyRetired,
+    #[error("Invalid signing key: {0}")]
+    InvalidSigningKey(String),
+
+    #[error("This account cannot be impersonated.")]
+    CannotImpersonate,
+    #[error("This action is not allowed while impersonating.")]
+    ImpersonationNotAllowed,
+    #[error("This session is not impersonating an account.")]
+    NotImpersonating,
+
+    #[error("Permission denied.")]
+    PermissionDenied,
+    #[error("Role not found.")]
+    RoleNotFound,
+    #[error("Role already exists.")]
+    RoleAlreadyExists,
+    #[error("Invalid role Id")]
+    InvalidRoleId,
 }
 
 impl AuthenticationClientError {
@@ -57,18 +210,190 @@ impl AuthenticationClientError {
             self,
             AuthenticationClientError::InvalidRefreshToken
                 | AuthenticationClientError::ExpiredRefreshToken
+                | AuthenticationClientError::ReusedRefreshToken
         )
     }
 
     pub fn is_account_error(&self) -> bool {
         matches!(
             self,
-            AuthenticationClientError::AccountLocked | AuthenticationClientError::AccountNotFound
+            AuthenticationClientError::AccountLocked
+                | AuthenticationClientError::AccountNotFound
+                | AuthenticationClientError::TooManySignInAttempts
+        )
+    }
+
+    pub fn is_mfa_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::InvalidMfaCode
+                | AuthenticationClientError::InvalidMfaChallenge
+                | AuthenticationClientError::MfaAlreadyEnabled
+                | AuthenticationClientError::TotpNotConfigured
+                | AuthenticationClientError::MfaNotEnabled
+                | AuthenticationClientError::MfaEnrollmentNotStarted
+        )
+    }
+
+    pub fn is_webauthn_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::InvalidWebAuthnChallenge
+                | AuthenticationClientError::InvalidWebAuthnResponse(_)
+                | AuthenticationClientError::CredentialNotFound
+                | AuthenticationClientError::CredentialAlreadyRegistered
+                | AuthenticationClientError::InvalidCredentialId
+        )
+    }
+
+    pub fn is_password_reset_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::InvalidPasswordResetToken
+                | AuthenticationClientError::InvalidPasswordChangeToken
+        )
+    }
+
+    pub fn is_magic_link_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::MagicLinkDisabled
+                | AuthenticationClientError::InvalidMagicLink
+                | AuthenticationClientError::TooManyMagicLinkRequests
+        )
+    }
+
+    pub fn is_password_policy_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::PasswordTooShort(_)
+                | AuthenticationClientError::PasswordTooLong(_)
+                | AuthenticationClientError::PasswordMissingLowercase
+                | AuthenticationClientError::PasswordMissingUppercase
+                | AuthenticationClientError::PasswordMissingDigit
+                | AuthenticationClientError::PasswordMissingSymbol
+                | AuthenticationClientError::PasswordContainsUsername
+                | AuthenticationClientError::PasswordTooWeak
+                | AuthenticationClientError::PasswordBreached
+                | AuthenticationClientError::PasswordReused(_)
+        )
+    }
+
+    pub fn is_email_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::InvalidEmail
+                | AuthenticationClientError::EmailAlreadyInUse
+                | AuthenticationClientError::EmailNotVerified
+                | AuthenticationClientError::EmailAlreadyVerified
+                | AuthenticationClientError::EmailMissing
+                | AuthenticationClientError::InvalidEmailVerificationToken
+                | AuthenticationClientError::EmailVerificationThrottled
         )
     }
 
+    pub fn is_oauth_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::OAuthClientNotFound
+                | AuthenticationClientError::InvalidOAuthClientId
+                | AuthenticationClientError::InvalidClient
+                | AuthenticationClientError::InvalidRedirectUri
+                | AuthenticationClientError::InvalidOAuthRequest(_)
+                | AuthenticationClientError::InvalidScope
+                | AuthenticationClientError::InvalidGrant
+                | AuthenticationClientError::UnsupportedGrantType
+                | AuthenticationClientError::UnsupportedResponseType
+        )
+    }
+
+    pub fn is_external_login_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::ExternalProviderNotFound
+                | AuthenticationClientError::InvalidExternalLoginState
+                | AuthenticationClientError::ExternalLoginFailed(_)
+                | AuthenticationClientError::ExternalIdentityNotLinked
+        )
+    }
+
+    pub fn is_api_key_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::ApiKeyNotFound
+                | AuthenticationClientError::InvalidApiKeyId
+                | AuthenticationClientError::InvalidApiKey
+                | AuthenticationClientError::ApiKeyLimitReached
+                | AuthenticationClientError::InvalidApiKeyScope
+                | AuthenticationClientError::InteractiveSessionRequired
+        )
+    }
+
+    pub fn is_service_account_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::ServiceAccountNotFound
+                | AuthenticationClientError::ServiceAccountAlreadyExists
+                | AuthenticationClientError::InvalidServiceAccountId
+        )
+    }
+
+    pub fn is_signing_key_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::SigningKeyNotFound
+                | AuthenticationClientError::SigningKeyActive
+                | AuthenticationClientError::SigningKeyRetired
+                | AuthenticationClientError::InvalidSigningKey(_)
+        )
+    }
+
+    /// Error code as defined in RFC 6749 section 5.2.
+    pub fn oauth_error_code(&self) -> &'static str {
+        match self {
+            AuthenticationClientError::InvalidClient
+            | AuthenticationClientError::OAuthClientNotFound
+            | AuthenticationClientError::InvalidOAuthClientId => "invalid_client",
+            AuthenticationClientError::InvalidScope => "invalid_scope",
+            AuthenticationClientError::InvalidGrant
+            | AuthenticationClientError::SessionNotFound
+            | AuthenticationClientError::ExpiredRefreshToken
+            | AuthenticationClientError::ReusedRefreshToken => "invalid_grant",
+            AuthenticationClientError::UnsupportedGrantType => "unsupported_grant_type",
+            AuthenticationClientError::UnsupportedResponseType => "unsupported_response_type",
+            _ => "invalid_request",
+        }
+    }
+
     pub fn is_session_error(&self) -> bool {
-        matches!(self, AuthenticationClientError::SessionNotFound)
+        matches!(
+            self,
+            AuthenticationClientError::SessionNotFound
+                | AuthenticationClientError::CookieSessionsDisabled
+                | AuthenticationClientError::SessionNameTooLong(_)
+        )
+    }
+
+    pub fn is_impersonation_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::CannotImpersonate
+                | AuthenticationClientError::ImpersonationNotAllowed
+                | AuthenticationClientError::NotImpersonating
+        )
+    }
+
+    pub fn is_authorization_error(&self) -> bool {
+        matches!(self, AuthenticationClientError::PermissionDenied)
+    }
+
+    pub fn is_role_error(&self) -> bool {
+        matches!(
+            self,
+            AuthenticationClientError::RoleNotFound
+                | AuthenticationClientError::RoleAlreadyExists
+                | AuthenticationClientError::InvalidRoleId
+        )
     }
 }
 
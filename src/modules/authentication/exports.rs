@@ -1,6 +1,12 @@
 pub use super::dtos::{
-    account as account_dto, authentication as authentication_dto, session as session_dto,
+    account as account_dto, authentication as authentication_dto, role as role_dto,
+    session as session_dto,
 };
+pub use super::guards::permission::{Permission, RequirePermission};
 pub use super::guards::*;
-pub use super::models::{account as account_model, session as session_model};
+pub use super::models::{
+    account as account_model, account_role as account_role_model, role as role_model,
+    session as session_model,
+};
 pub use super::module::AuthenticationModule;
+pub use super::services::claims::{ClaimProvider, Claims, register_claim_provider};
@@ -6,8 +6,27 @@ use crate::{
             config::authentication::AuthenticationConfiguration,
             errors::service::AuthenticationServiceError,
             services::{
-                account::AccountService, authentication::AuthenticationService,
-                password::PasswordService, session::SessionService, token::TokenService,
+                account::AccountService,
+                api_key::ApiKeyService,
+                authentication::AuthenticationService,
+                cookie::CookieService,
+                email_verification::EmailVerificationService,
+                external_login::ExternalLoginService,
+                impersonation::ImpersonationService,
+                magic_link::MagicLinkService,
+                mailer::{Mailer, mailer_from_config},
+                mfa::MfaService,
+                oauth::OAuthService,
+                oidc::OidcService,
+                password::PasswordService,
+                password_reset::PasswordResetService,
+                revocation::RevocationService,
+                role::RoleService,
+                service_account::ServiceAccountService,
+                session::SessionService,
+                signing_key::SigningKeyService,
+                token::TokenService,
+                webauthn::WebAuthnService,
             },
         },
         base::exports::DatabaseConnection,
@@ -42,22 +61,148 @@ impl AuthenticationServiceGuard {
     }
 
     pub fn password_service(&self) -> Result<PasswordService, AuthenticationServiceError> {
-        Ok(PasswordService)
+        self.auth_config().map(|auth_config| {
+            PasswordService::new(auth_config.password_policy, auth_config.password_hashing)
+        })
     }
 
     pub fn account_service(&self) -> Result<AccountService, AuthenticationServiceError> {
-        Ok(AccountService::new(self.database_connection.clone()))
+        let auth_config = self.auth_config()?;
+
+        Ok(AccountService::new(
+            auth_config,
+            self.database_connection.clone(),
+        ))
     }
 
     pub fn account_service_with_deps(
         &self,
     ) -> Result<(AccountService, PasswordService), AuthenticationServiceError> {
-        let account_service = AccountService::new(self.database_connection.clone());
-        let password_service = PasswordService;
+        let account_service = self.account_service()?;
+        let password_service = self.password_service()?;
 
         Ok((account_service, password_service))
     }
 
+    pub fn api_key_service(&self) -> Result<ApiKeyService, AuthenticationServiceError> {
+        let auth_config = self.auth_config()?;
+
+        Ok(ApiKeyService::new(
+            auth_config.api_keys,
+            self.database_connection.clone(),
+        ))
+    }
+
+    pub fn service_account_service(
+        &self,
+    ) -> Result<ServiceAccountService, AuthenticationServiceError> {
+        Ok(ServiceAccountService::new(self.database_connection.clone()))
+    }
+
+    pub fn signing_key_service(&self) -> Result<SigningKeyService, AuthenticationServiceError> {
+        let auth_config = self.auth_config()?;
+
+        Ok(SigningKeyService::new(
+            auth_config.signing_keys,
+            self.database_connection.clone(),
+        ))
+    }
+
+    pub fn mailer(&self) -> Result<Box<dyn Mailer>, AuthenticationServiceError> {
+        mailer_from_config(&self.auth_config()?.mail)
+    }
+
+    pub fn email_verification_service(
+        &self,
+    ) -> Result<EmailVerificationService, AuthenticationServiceError> {
+        let auth_config = self.auth_config()?;
+
+        Ok(EmailVerificationService::new(
+            auth_config.email_verification,
+            self.database_connection.clone(),
+        ))
+    }
+
+    pub fn password_reset_service(
+        &self,
+    ) -> Result<PasswordResetService, AuthenticationServiceError> {
+        let auth_config = self.auth_config()?;
+
+        Ok(PasswordResetService::new(
+            auth_config.password_reset,
+            self.database_connection.clone(),
+        ))
+    }
+
+    pub fn external_login_service(
+        &self,
+    ) -> Result<ExternalLoginService, AuthenticationServiceError> {
+        let auth_config = self.auth_config()?;
+
+        ExternalLoginService::new(auth_config.external_login, self.database_connection.clone())
+    }
+
+    pub fn cookie_service(&self) -> Result<CookieService, AuthenticationServiceError> {
+        self.auth_config()
+            .map(|auth_config| CookieService::new(auth_config.cookies))
+    }
+
+    pub fn impersonation_service(
+        &self,
+    ) -> Result<ImpersonationService, AuthenticationServiceError> {
+        Ok(ImpersonationService::new(self.database_connection.clone()))
+    }
+
+    pub fn magic_link_service(&self) -> Result<MagicLinkService, AuthenticationServiceError> {
+        let auth_config = self.auth_config()?;
+
+        Ok(MagicLinkService::new(
+            auth_config.magic_link,
+            self.database_connection.clone(),
+        ))
+    }
+
+    pub fn mfa_service(&self) -> Result<MfaService, AuthenticationServiceError> {
+        self.auth_config()
+            .map(|auth_config| MfaService::new(auth_config.mfa))
+    }
+
+    pub fn webauthn_service(&self) -> Result<WebAuthnService, AuthenticationServiceError> {
+        let auth_config = self.auth_config()?;
+
+        Ok(WebAuthnService::new(
+            auth_config.webauthn,
+            self.database_connection.clone(),
+        ))
+    }
+
+    pub fn oauth_service(&self) -> Result<OAuthService, AuthenticationServiceError> {
+        let auth_config = self.auth_config()?;
+
+        Ok(OAuthService::new(
+            auth_config.oauth,
+            self.database_connection.clone(),
+        ))
+    }
+
+    pub fn oidc_service(&self) -> Result<OidcService, AuthenticationServiceError> {
+        self.auth_config()
+            .map(|auth_config| OidcService::new(auth_config.oidc))
+    }
+
+    pub fn revocation_service(&self) -> Result<RevocationService, AuthenticationServiceError> {
+        let auth_config = self.auth_config()?;
+
+        Ok(RevocationService::new(
+            auth_config.jwt,
+            self.database_connection.clone(),
+        ))
+    }
+
+    pub fn role_service(&self) -> Result<RoleService, AuthenticationServiceError> {
+        Ok(RoleService::new(self.database_connection.clone()))
+    }
+
     pub fn session_service(&self) -> Result<SessionService, AuthenticationServiceError> {
         let auth_config = self.auth_config()?;
 
@@ -126,10 +271,24 @@ impl FromRequestParts<()> for AuthenticationServiceGuard {
 
         let app_state = app_state_opt.unwrap();
 
-        Ok(AuthenticationServiceGuard {
+        let guard = AuthenticationServiceGuard {
             database_connection: app_state.database.clone(),
             env_config: app_state.env_config.clone(),
             file_config: app_state.file_config.clone(),
-        })
+        };
+
+        // Keys rotated on other instances are picked up here, a failed reload keeps the old keyring
+        if let Err(e) = match guard.signing_key_service() {
+            Ok(signing_key_service) => signing_key_service.refresh_if_stale().await,
+            Err(e) => Err(e),
+        } {
+            tracing::error!(
+                "{} Failed to refresh the signing keyring: {:?}",
+                GUARD_NAME,
+                e
+            );
+        }
+
+        Ok(guard)
     }
 }
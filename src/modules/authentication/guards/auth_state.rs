@@ -1,8 +1,13 @@
 use crate::modules::{
     authentication::{
-        guards::auth_services::AuthenticationServiceGuard, models::account::AccountModel,
+        errors::service::{AuthenticationClientError, AuthenticationServiceError},
+        guards::auth_services::AuthenticationServiceGuard,
+        models::{
+            account::AccountModel, api_key::ApiKeyModel, service_account::ServiceAccountModel,
+        },
+        services::role::RoleService,
     },
-    base::exports::BaseId,
+    base::exports::{BaseId, request_info::RequestInfoExtractor},
 };
 use axum::{
     Json,
@@ -16,9 +21,18 @@ enum AuthenticationKind {
     Authenticated {
         account_id: BaseId,
         session_id: BaseId,
+        impersonator_id: Option<BaseId>,
     },
     RefreshToken {
         refresh_token_hash: String,
+        from_cookie: bool,
+    },
+    ApiKey {
+        token: String,
+    },
+    ServiceAccount {
+        service_account_id: BaseId,
+        scopes: Vec<String>,
     },
     NotAuthenticated,
 }
@@ -39,12 +53,23 @@ impl FromRequestParts<()> for AuthenticationKind {
             return Ok(AuthenticationKind::NotAuthenticated);
         }
 
-        let auth_header = { parts.headers.get(header::AUTHORIZATION) };
-        if auth_header.is_none() || auth_header.unwrap().to_str().is_err() {
+        let auth_svc_guard = auth_svc_guard_res.unwrap();
+        let credentials = match parts.headers.get(header::AUTHORIZATION) {
+            Some(auth_header) => auth_header.to_str().ok().map(|value| {
+                let mut header_parts = value.split_whitespace();
+                (
+                    header_parts.next().unwrap_or("").trim().to_string(),
+                    header_parts.next().unwrap_or("").trim().to_string(),
+                    false,
+                )
+            }),
+            None => cookie_credentials(&auth_svc_guard, parts),
+        };
+        let Some((header_kind, header_value, from_cookie)) = credentials else {
             return Ok(AuthenticationKind::NotAuthenticated);
-        }
+        };
+        let header_value = header_value.as_str();
 
-        let auth_svc_guard = auth_svc_guard_res.unwrap();
         let token_service_res = auth_svc_guard.token_service();
         if token_service_res.is_err() {
             tracing::error!(
@@ -56,26 +81,50 @@ impl FromRequestParts<()> for AuthenticationKind {
 
         let token_service = token_service_res.unwrap();
 
-        let header_parts = auth_header.unwrap().to_str().unwrap().split_whitespace();
-        let header_kind = header_parts.clone().next().unwrap_or("").trim();
-        let header_value = header_parts.clone().nth(1).unwrap_or("").trim();
-
-        match header_kind {
+        match header_kind.as_str() {
             "Bearer" => {
                 if header_value.is_empty() {
                     return Ok(AuthenticationKind::NotAuthenticated);
                 }
 
-                let verify_res = token_service.verify_jwt(header_value);
-                if verify_res.is_err() {
-                    return Ok(AuthenticationKind::NotAuthenticated);
-                }
+                let (token_id, auth_kind) =
+                    if let Ok(claims) = token_service.verify_jwt(header_value) {
+                        (
+                            claims.token_id,
+                            AuthenticationKind::Authenticated {
+                                account_id: claims.account_id,
+                                session_id: claims.session_id,
+                                impersonator_id: claims.actor_id,
+                            },
+                        )
+                    } else if let Ok(claims) = token_service.verify_service_token(header_value) {
+                        (
+                            claims.token_id,
+                            AuthenticationKind::ServiceAccount {
+                                service_account_id: claims.service_account_id,
+                                scopes: claims.scopes,
+                            },
+                        )
+                    } else {
+                        return Ok(AuthenticationKind::NotAuthenticated);
+                    };
 
-                let (account_id, session_id) = verify_res.unwrap();
-                Ok(AuthenticationKind::Authenticated {
-                    account_id,
-                    session_id,
-                })
+                // Tokens revoked through the revocation endpoint stay rejected until they expire
+                match auth_svc_guard.revocation_service() {
+                    Ok(revocation_service) => {
+                        match revocation_service.ensure_not_revoked(&token_id).await {
+                            Ok(()) => Ok(auth_kind),
+                            Err(e) => {
+                                tracing::debug!("Access token rejected: {:?}", e);
+                                Ok(AuthenticationKind::NotAuthenticated)
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Revocation service retrieval error: {:?}", e);
+                        Ok(AuthenticationKind::NotAuthenticated)
+                    }
+                }
             }
             "Refresh" => {
                 if header_value.is_empty() {
@@ -85,6 +134,16 @@ impl FromRequestParts<()> for AuthenticationKind {
                 let hash = token_service.hash_refresh_token(header_value);
                 Ok(AuthenticationKind::RefreshToken {
                     refresh_token_hash: hash,
+                    from_cookie,
+                })
+            }
+            "ApiKey" => {
+                if header_value.is_empty() {
+                    return Ok(AuthenticationKind::NotAuthenticated);
+                }
+
+                Ok(AuthenticationKind::ApiKey {
+                    token: header_value.to_string(),
                 })
             }
             _ => Ok(AuthenticationKind::NotAuthenticated),
@@ -92,21 +151,135 @@ impl FromRequestParts<()> for AuthenticationKind {
     }
 }
 
+/// Browser sessions send their tokens as cookies, which only count together with a matching
+/// CSRF token. The refresh cookie is scoped to the refresh endpoint, so it wins where it is sent.
+fn cookie_credentials(
+    auth_svc_guard: &AuthenticationServiceGuard,
+    parts: &axum::http::request::Parts,
+) -> Option<(String, String, bool)> {
+    let cookie_service = match auth_svc_guard.cookie_service() {
+        Ok(cookie_service) => cookie_service,
+        Err(e) => {
+            tracing::error!("Cookie service retrieval error: {:?}", e);
+            return None;
+        }
+    };
+
+    let (kind, token) = match cookie_service.refresh_token(&parts.headers) {
+        Some(refresh_token) => ("Refresh", refresh_token),
+        None => ("Bearer", cookie_service.access_token(&parts.headers)?),
+    };
+
+    if !cookie_service.verify_csrf(&parts.method, &parts.headers) {
+        tracing::debug!("Cookie credentials rejected without a matching CSRF token");
+        return None;
+    }
+
+    Some((kind.to_string(), token.to_string(), true))
+}
+
+async fn validate_access_session(
+    auth_svc_guard: &AuthenticationServiceGuard,
+    account_id: &BaseId,
+    session_id: &BaseId,
+    impersonator_id: &Option<BaseId>,
+    request_info: Option<RequestInfoExtractor>,
+) -> Result<(), AuthenticationServiceError> {
+    let session_service = auth_svc_guard.session_service()?;
+    let session = session_service.get_live_session(session_id).await?;
+    if &session.account_id != account_id || &session.impersonator_id != impersonator_id {
+        return Err(AuthenticationServiceError::client(
+            AuthenticationClientError::SessionNotFound,
+        ));
+    }
+
+    // Activity is informational, failing to record it must not reject the request
+    if let Some(request_info) = request_info
+        && let Err(e) = session_service.touch_session(&session, &request_info).await
+    {
+        tracing::warn!("Failed to record session activity: {:?}", e);
+    }
+
+    Ok(())
+}
+
+async fn validate_refresh_session(
+    auth_svc_guard: &AuthenticationServiceGuard,
+    refresh_token_hash: &str,
+) -> Result<(), AuthenticationServiceError> {
+    let session_service = auth_svc_guard.session_service()?;
+    match session_service
+        .get_session_by_refresh_token_hash(refresh_token_hash.to_string())
+        .await
+    {
+        Ok(session) => session_service.validate_session(&session),
+        // Unknown hashes are passed on so the refresh can run reuse detection.
+        Err(AuthenticationServiceError::ClientError(
+            AuthenticationClientError::SessionNotFound,
+        )) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// An account signed in through an interactive session. API keys are rejected here, routes that
+/// accept them opt in through `SessionOrApiKeyGuard` or `RequirePermission`.
 #[derive(Debug)]
 pub struct AuthenticatedGuard {
+    /// The effective account, while impersonating this is the impersonated one.
     pub account_id: BaseId,
-    #[allow(dead_code)]
-    pub session_id: BaseId,
+    /// Only set for interactive sessions, requests made with an API key carry `api_key` instead.
+    pub session_id: Option<BaseId>,
+    pub api_key: Option<ApiKeyModel>,
     pub account: AccountModel,
+    /// The admin behind an impersonation session.
+    pub impersonator: Option<AccountModel>,
 }
 
-impl FromRequestParts<()> for AuthenticatedGuard {
-    type Rejection = (StatusCode, Json<Value>);
+impl AuthenticatedGuard {
+    pub fn is_impersonating(&self) -> bool {
+        self.impersonator.is_some()
+    }
 
-    async fn from_request_parts(
+    /// The account of the person making the request.
+    pub fn real_account(&self) -> &AccountModel {
+        self.impersonator.as_ref().unwrap_or(&self.account)
+    }
+
+    /// For credential and account lifecycle changes only the owner may make.
+    pub fn forbid_impersonation(&self) -> Result<(), AuthenticationServiceError> {
+        if self.is_impersonating() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::ImpersonationNotAllowed,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn require_session(&self) -> Result<&BaseId, AuthenticationServiceError> {
+        self.session_id
+            .as_ref()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::InteractiveSessionRequired,
+            ))
+    }
+
+    /// API keys are limited to their scopes on top of the account's permissions.
+    pub fn allows_permission(&self, permission: &str) -> bool {
+        self.api_key.as_ref().is_none_or(|api_key| {
+            api_key
+                .scopes
+                .iter()
+                .any(|scope| RoleService::permission_matches(scope, permission))
+        })
+    }
+}
+
+impl AuthenticatedGuard {
+    async fn authenticate(
         parts: &mut axum::http::request::Parts,
-        _: &(),
-    ) -> Result<Self, Self::Rejection> {
+        allow_api_key: bool,
+    ) -> Result<Self, (StatusCode, Json<Value>)> {
         let auth_kind = AuthenticationKind::from_request_parts(parts, &()).await;
         let auth_svc_guard_res = AuthenticationServiceGuard::from_request_parts(parts, &()).await;
         if auth_svc_guard_res.is_err() {
@@ -140,7 +313,27 @@ impl FromRequestParts<()> for AuthenticatedGuard {
             Ok(AuthenticationKind::Authenticated {
                 account_id,
                 session_id,
+                impersonator_id,
             }) => {
+                let request_info = RequestInfoExtractor::from_request_parts(parts, &())
+                    .await
+                    .ok();
+                let session_res = validate_access_session(
+                    &auth_svc_guard,
+                    &account_id,
+                    &session_id,
+                    &impersonator_id,
+                    request_info,
+                )
+                .await;
+                if session_res.is_err() {
+                    tracing::debug!("Session validation failed: {:?}", session_res.err());
+                    return Err((
+                        StatusCode::UNAUTHORIZED,
+                        Json(serde_json::json!({"error": "Unauthorized"})),
+                    ));
+                }
+
                 let account_res = account_service.get_account_by_id(&account_id).await;
                 if account_res.is_err() {
                     tracing::debug!("Failed to fetch account: {:?}", account_res.err());
@@ -152,10 +345,63 @@ impl FromRequestParts<()> for AuthenticatedGuard {
 
                 let account = account_res.unwrap();
 
+                let impersonator = match impersonator_id {
+                    Some(impersonator_id) => {
+                        match account_service.get_account_by_id(&impersonator_id).await {
+                            Ok(impersonator) => Some(impersonator),
+                            Err(e) => {
+                                tracing::debug!("Failed to fetch impersonator: {:?}", e);
+                                return Err((
+                                    StatusCode::UNAUTHORIZED,
+                                    Json(serde_json::json!({"error": "Unauthorized"})),
+                                ));
+                            }
+                        }
+                    }
+                    None => None,
+                };
+
                 Ok(AuthenticatedGuard {
                     account_id,
-                    session_id,
+                    session_id: Some(session_id),
+                    api_key: None,
                     account,
+                    impersonator,
+                })
+            }
+            Ok(AuthenticationKind::ApiKey { token }) if allow_api_key => {
+                let api_key_res = match auth_svc_guard.api_key_service() {
+                    Ok(api_key_service) => api_key_service.authenticate(&token).await,
+                    Err(e) => Err(e),
+                };
+                let api_key = match api_key_res {
+                    Ok(api_key) => api_key,
+                    Err(e) => {
+                        tracing::debug!("API key validation failed: {:?}", e);
+                        return Err((
+                            StatusCode::UNAUTHORIZED,
+                            Json(serde_json::json!({"error": "Unauthorized"})),
+                        ));
+                    }
+                };
+
+                let account_res = account_service.get_account_by_id(&api_key.account_id).await;
+                if account_res.is_err() {
+                    tracing::debug!("Failed to fetch account: {:?}", account_res.err());
+                    return Err((
+                        StatusCode::UNAUTHORIZED,
+                        Json(serde_json::json!({"error": "Unauthorized"})),
+                    ));
+                }
+
+                let account = account_res.unwrap();
+
+                Ok(AuthenticatedGuard {
+                    account_id: api_key.account_id.clone(),
+                    session_id: None,
+                    api_key: Some(api_key),
+                    account,
+                    impersonator: None,
                 })
             }
             _ => Err((
@@ -166,9 +412,152 @@ impl FromRequestParts<()> for AuthenticatedGuard {
     }
 }
 
+impl FromRequestParts<()> for AuthenticatedGuard {
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _: &(),
+    ) -> Result<Self, Self::Rejection> {
+        AuthenticatedGuard::authenticate(parts, false).await
+    }
+}
+
+/// An account signed in through a session or an API key. API key scopes are not checked here,
+/// so only routes that merely read the account may opt in.
+#[derive(Debug)]
+pub struct SessionOrApiKeyGuard(pub AuthenticatedGuard);
+
+impl FromRequestParts<()> for SessionOrApiKeyGuard {
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _: &(),
+    ) -> Result<Self, Self::Rejection> {
+        AuthenticatedGuard::authenticate(parts, true)
+            .await
+            .map(SessionOrApiKeyGuard)
+    }
+}
+
+/// A service account authenticated through a client credentials token.
+#[derive(Debug)]
+pub struct ServiceAuthenticatedGuard {
+    pub service_account_id: BaseId,
+    pub service_account: ServiceAccountModel,
+    /// Scopes granted to the token, a subset of the service account's scopes.
+    pub scopes: Vec<String>,
+}
+
+impl ServiceAuthenticatedGuard {
+    pub fn allows_permission(&self, permission: &str) -> bool {
+        self.scopes
+            .iter()
+            .any(|scope| RoleService::permission_matches(scope, permission))
+    }
+}
+
+impl FromRequestParts<()> for ServiceAuthenticatedGuard {
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _: &(),
+    ) -> Result<Self, Self::Rejection> {
+        let auth_kind = AuthenticationKind::from_request_parts(parts, &()).await;
+        let Ok(AuthenticationKind::ServiceAccount {
+            service_account_id,
+            scopes,
+        }) = auth_kind
+        else {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({"error": "Unauthorized"})),
+            ));
+        };
+
+        let service_account_res =
+            match AuthenticationServiceGuard::from_request_parts(parts, &()).await {
+                Ok(auth_svc_guard) => match auth_svc_guard.service_account_service() {
+                    Ok(service_account_service) => service_account_service
+                        .get_service_account_by_id(&service_account_id)
+                        .await
+                        .map_err(|e| format!("{:?}", e)),
+                    Err(e) => Err(format!("{:?}", e)),
+                },
+                Err(e) => Err(format!("{:?}", e)),
+            };
+
+        // Disabling a service account cuts off its outstanding tokens immediately
+        match service_account_res {
+            Ok(service_account) if service_account.is_active => Ok(ServiceAuthenticatedGuard {
+                service_account_id,
+                service_account,
+                scopes,
+            }),
+            res => {
+                tracing::debug!("Service account validation failed: {:?}", res.err());
+                Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(serde_json::json!({"error": "Unauthorized"})),
+                ))
+            }
+        }
+    }
+}
+
+/// Either a human account or a service account, for routes open to both.
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum PrincipalGuard {
+    Human(AuthenticatedGuard),
+    Service(ServiceAuthenticatedGuard),
+}
+
+impl PrincipalGuard {
+    pub fn is_human(&self) -> bool {
+        matches!(self, PrincipalGuard::Human(_))
+    }
+
+    pub fn is_service(&self) -> bool {
+        matches!(self, PrincipalGuard::Service(_))
+    }
+
+    pub fn allows_permission(&self, permission: &str) -> bool {
+        match self {
+            PrincipalGuard::Human(auth) => auth.allows_permission(permission),
+            PrincipalGuard::Service(auth) => auth.allows_permission(permission),
+        }
+    }
+}
+
+impl FromRequestParts<()> for PrincipalGuard {
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _: &(),
+    ) -> Result<Self, Self::Rejection> {
+        match AuthenticationKind::from_request_parts(parts, &()).await {
+            Ok(AuthenticationKind::ServiceAccount { .. }) => {
+                ServiceAuthenticatedGuard::from_request_parts(parts, &())
+                    .await
+                    .map(PrincipalGuard::Service)
+            }
+            // Permission checks limit API keys to their scopes
+            _ => AuthenticatedGuard::authenticate(parts, true)
+                .await
+                .map(PrincipalGuard::Human),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct RefreshTokenGuard {
     pub refresh_token_hash: String,
+    /// Set when the refresh token came from the session cookie, the rotated one goes back there.
+    pub from_cookie: bool,
 }
 
 impl FromRequestParts<()> for RefreshTokenGuard {
@@ -180,8 +569,33 @@ impl FromRequestParts<()> for RefreshTokenGuard {
     ) -> Result<Self, Self::Rejection> {
         let auth_kind = AuthenticationKind::from_request_parts(parts, &()).await;
         match auth_kind {
-            Ok(AuthenticationKind::RefreshToken { refresh_token_hash }) => {
-                Ok(RefreshTokenGuard { refresh_token_hash })
+            Ok(AuthenticationKind::RefreshToken {
+                refresh_token_hash,
+                from_cookie,
+            }) => {
+                let auth_svc_guard = AuthenticationServiceGuard::from_request_parts(parts, &())
+                    .await
+                    .map_err(|_| {
+                        (
+                            StatusCode::UNAUTHORIZED,
+                            Json(serde_json::json!({"error": "Unauthorized"})),
+                        )
+                    })?;
+
+                let session_res =
+                    validate_refresh_session(&auth_svc_guard, &refresh_token_hash).await;
+                if let Err(e) = session_res {
+                    tracing::debug!("Refresh session validation failed: {:?}", e);
+                    return Err((
+                        StatusCode::UNAUTHORIZED,
+                        Json(serde_json::json!({"error": format!("{}", e)})),
+                    ));
+                }
+
+                Ok(RefreshTokenGuard {
+                    refresh_token_hash,
+                    from_cookie,
+                })
             }
             _ => Err((
                 StatusCode::UNAUTHORIZED,
@@ -229,6 +643,7 @@ impl FromRequestParts<()> for OptionalAuthenticatedGuard {
             Ok(AuthenticationKind::Authenticated {
                 account_id,
                 session_id,
+                ..
             }) => Ok(OptionalAuthenticatedGuard {
                 account_id: Some(account_id),
                 session_id: Some(session_id),
@@ -0,0 +1,126 @@
+use crate::{
+    modules::authentication::{
+        errors::service::AuthenticationClientError,
+        guards::{auth_services::AuthenticationServiceGuard, auth_state::PrincipalGuard},
+    },
+    permission,
+};
+use axum::{Json, extract::FromRequestParts, http::StatusCode};
+use serde_json::Value;
+use std::marker::PhantomData;
+
+pub trait Permission {
+    const NAME: &'static str;
+}
+
+permission!(AccountsRead, "accounts:read");
+permission!(AccountsWrite, "accounts:write");
+permission!(AccountsDelete, "accounts:delete");
+permission!(AccountsImpersonate, "accounts:impersonate");
+permission!(SessionsRead, "sessions:read");
+permission!(SessionsWrite, "sessions:write");
+permission!(RolesRead, "roles:read");
+permission!(RolesWrite, "roles:write");
+permission!(OAuthClientsRead, "oauth_clients:read");
+permission!(OAuthClientsWrite, "oauth_clients:write");
+permission!(ServiceAccountsRead, "service_accounts:read");
+permission!(ServiceAccountsWrite, "service_accounts:write");
+permission!(SigningKeysRead, "signing_keys:read");
+permission!(SigningKeysWrite, "signing_keys:write");
+permission!(TokensIntrospect, "tokens:introspect");
+
+#[derive(Debug)]
+pub struct RequirePermission<P: Permission> {
+    pub auth: PrincipalGuard,
+    _permission: PhantomData<P>,
+}
+
+impl<P: Permission> FromRequestParts<()> for RequirePermission<P> {
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _: &(),
+    ) -> Result<Self, Self::Rejection> {
+        let auth = PrincipalGuard::from_request_parts(parts, &()).await?;
+        if !auth.allows_permission(P::NAME) {
+            tracing::debug!("Token is missing scope '{}'", P::NAME);
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "error": format!("{}", AuthenticationClientError::PermissionDenied)
+                })),
+            ));
+        }
+
+        // Service accounts hold no roles, their scopes are the permissions granted by an admin
+        let (account_id, impersonator_id) = match &auth {
+            PrincipalGuard::Human(human) => (
+                human.account_id.clone(),
+                human
+                    .impersonator
+                    .as_ref()
+                    .map(|impersonator| impersonator.id.clone()),
+            ),
+            PrincipalGuard::Service(_) => {
+                return Ok(RequirePermission {
+                    auth,
+                    _permission: PhantomData,
+                });
+            }
+        };
+
+        let auth_svc_guard = AuthenticationServiceGuard::from_request_parts(parts, &())
+            .await
+            .map_err(|_| {
+                (
+                    StatusCode::UNAUTHORIZED,
+                    Json(serde_json::json!({"error": "Unauthorized"})),
+                )
+            })?;
+
+        let role_service_res = auth_svc_guard.role_service();
+        if role_service_res.is_err() {
+            tracing::error!("Role service retrieval error: {:?}", role_service_res.err());
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Internal server error"})),
+            ));
+        }
+
+        let role_service = role_service_res.unwrap();
+        let mut has_permission = role_service
+            .account_has_permission(&account_id, P::NAME)
+            .await;
+
+        // Impersonating never grants more than the impersonator holds themselves
+        if let (Ok(true), Some(impersonator_id)) = (&has_permission, &impersonator_id) {
+            has_permission = role_service
+                .account_has_permission(impersonator_id, P::NAME)
+                .await;
+        }
+
+        match has_permission {
+            Ok(true) => Ok(RequirePermission {
+                auth,
+                _permission: PhantomData,
+            }),
+            Ok(false) => {
+                tracing::debug!("Account is missing permission '{}'", P::NAME);
+                Err((
+                    StatusCode::FORBIDDEN,
+                    Json(serde_json::json!({
+                        "error": format!("{}", AuthenticationClientError::PermissionDenied)
+                    })),
+                ))
+            }
+            Err(e) => {
+                tracing::error!("Permission check error: {:?}", e);
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({"error": "Internal server error"})),
+                ))
+            }
+        }
+    }
+}
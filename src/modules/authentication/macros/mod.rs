@@ -1 +1,2 @@
 pub mod error_return;
+pub mod permission;
@@ -0,0 +1,11 @@
+#[macro_export]
+macro_rules! permission {
+    ($name:ident, $value:literal) => {
+        #[derive(Debug)]
+        pub struct $name;
+
+        impl $crate::modules::authentication::exports::Permission for $name {
+            const NAME: &'static str = $value;
+        }
+    };
+}
@@ -6,10 +6,29 @@ pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
         DEFINE TABLE IF NOT EXISTS accounts SCHEMAFULL;
         DEFINE FIELD IF NOT EXISTS username      ON TABLE accounts TYPE string;
         DEFINE FIELD IF NOT EXISTS password      ON TABLE accounts TYPE string;
+        DEFINE FIELD IF NOT EXISTS email         ON TABLE accounts TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS email_verified ON TABLE accounts TYPE bool DEFAULT false;
+        DEFINE FIELD IF NOT EXISTS email_verified_at ON TABLE accounts TYPE option<datetime>;
+        DEFINE FIELD IF NOT EXISTS failed_sign_in_attempts ON TABLE accounts TYPE int DEFAULT 0;
+        DEFINE FIELD IF NOT EXISTS locked_until  ON TABLE accounts TYPE option<datetime>;
+        DEFINE FIELD IF NOT EXISTS totp_enabled  ON TABLE accounts TYPE bool DEFAULT false;
+        DEFINE FIELD IF NOT EXISTS totp_secret   ON TABLE accounts TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS totp_pending_secret ON TABLE accounts TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS totp_recovery_codes ON TABLE accounts TYPE array<string> DEFAULT [];
+        DEFINE FIELD IF NOT EXISTS totp_last_used_step ON TABLE accounts TYPE option<int>;
+        DEFINE FIELD IF NOT EXISTS must_change_password ON TABLE accounts TYPE bool DEFAULT false;
+        DEFINE FIELD IF NOT EXISTS password_changed_at ON TABLE accounts TYPE option<datetime>;
         DEFINE FIELD IF NOT EXISTS created_at    ON TABLE accounts TYPE datetime DEFAULT time::now();
         DEFINE FIELD IF NOT EXISTS updated_at    ON TABLE accounts TYPE datetime VALUE time::now();
 
         DEFINE INDEX IF NOT EXISTS account_username_unique ON TABLE accounts COLUMNS username UNIQUE;
+        REMOVE INDEX IF EXISTS account_email_idx ON TABLE accounts;
+        DEFINE INDEX IF NOT EXISTS account_email_unique ON TABLE accounts COLUMNS email UNIQUE;
+
+        UPDATE accounts SET failed_sign_in_attempts = 0 WHERE failed_sign_in_attempts = NONE;
+        UPDATE accounts SET totp_enabled = false, totp_recovery_codes = [] WHERE totp_enabled = NONE;
+        UPDATE accounts SET email_verified = false WHERE email_verified = NONE;
+        UPDATE accounts SET must_change_password = false WHERE must_change_password = NONE;
         "#,
     ).await?;
 
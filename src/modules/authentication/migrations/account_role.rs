@@ -0,0 +1,17 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS account_roles SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS account_id   ON TABLE account_roles TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS role_id      ON TABLE account_roles TYPE record<roles>;
+        DEFINE FIELD IF NOT EXISTS created_at   ON TABLE account_roles TYPE datetime DEFAULT time::now();
+
+        DEFINE INDEX IF NOT EXISTS account_role_unique   ON TABLE account_roles COLUMNS account_id, role_id UNIQUE;
+        DEFINE INDEX IF NOT EXISTS account_role_role_idx ON TABLE account_roles COLUMNS role_id;
+        "#,
+    ).await?;
+
+    Ok(())
+}
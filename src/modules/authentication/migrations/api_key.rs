@@ -0,0 +1,23 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS api_keys SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS account_id   ON TABLE api_keys TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS name         ON TABLE api_keys TYPE string;
+        DEFINE FIELD IF NOT EXISTS token_hash   ON TABLE api_keys TYPE string;
+        DEFINE FIELD IF NOT EXISTS token_prefix ON TABLE api_keys TYPE string;
+        DEFINE FIELD IF NOT EXISTS scopes       ON TABLE api_keys TYPE array<string> DEFAULT [];
+        DEFINE FIELD IF NOT EXISTS expires_at   ON TABLE api_keys TYPE option<datetime>;
+        DEFINE FIELD IF NOT EXISTS last_used_at ON TABLE api_keys TYPE option<datetime>;
+        DEFINE FIELD IF NOT EXISTS created_at   ON TABLE api_keys TYPE datetime DEFAULT time::now();
+        DEFINE FIELD IF NOT EXISTS updated_at   ON TABLE api_keys TYPE datetime VALUE time::now();
+
+        DEFINE INDEX IF NOT EXISTS api_key_token_hash_idx ON TABLE api_keys COLUMNS token_hash UNIQUE;
+        DEFINE INDEX IF NOT EXISTS api_key_account_idx ON TABLE api_keys COLUMNS account_id;
+        "#,
+    ).await?;
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS credentials SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS account_id    ON TABLE credentials TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS credential_id ON TABLE credentials TYPE string;
+        DEFINE FIELD IF NOT EXISTS public_key    ON TABLE credentials TYPE string;
+        DEFINE FIELD IF NOT EXISTS algorithm     ON TABLE credentials TYPE int;
+        DEFINE FIELD IF NOT EXISTS sign_count    ON TABLE credentials TYPE int DEFAULT 0;
+        DEFINE FIELD IF NOT EXISTS name          ON TABLE credentials TYPE string DEFAULT "";
+        DEFINE FIELD IF NOT EXISTS transports    ON TABLE credentials TYPE array<string> DEFAULT [];
+        DEFINE FIELD IF NOT EXISTS created_at    ON TABLE credentials TYPE datetime DEFAULT time::now();
+        DEFINE FIELD IF NOT EXISTS last_used_at  ON TABLE credentials TYPE option<datetime>;
+
+        DEFINE INDEX IF NOT EXISTS credential_id_unique  ON TABLE credentials COLUMNS credential_id UNIQUE;
+        DEFINE INDEX IF NOT EXISTS credential_account_idx ON TABLE credentials COLUMNS account_id;
+
+        DEFINE TABLE IF NOT EXISTS webauthn_challenges SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS challenge     ON TABLE webauthn_challenges TYPE string;
+        DEFINE FIELD IF NOT EXISTS ceremony      ON TABLE webauthn_challenges TYPE string;
+        DEFINE FIELD IF NOT EXISTS account_id    ON TABLE webauthn_challenges TYPE option<record<accounts>>;
+        DEFINE FIELD IF NOT EXISTS expires_at    ON TABLE webauthn_challenges TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS created_at    ON TABLE webauthn_challenges TYPE datetime DEFAULT time::now();
+        "#,
+    ).await?;
+
+    Ok(())
+}
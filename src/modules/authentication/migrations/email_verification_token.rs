@@ -0,0 +1,19 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS email_verification_tokens SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS account_id   ON TABLE email_verification_tokens TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS email        ON TABLE email_verification_tokens TYPE string;
+        DEFINE FIELD IF NOT EXISTS token_hash   ON TABLE email_verification_tokens TYPE string;
+        DEFINE FIELD IF NOT EXISTS expires_at   ON TABLE email_verification_tokens TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS created_at   ON TABLE email_verification_tokens TYPE datetime DEFAULT time::now();
+
+        DEFINE INDEX IF NOT EXISTS email_verification_token_hash_idx ON TABLE email_verification_tokens COLUMNS token_hash UNIQUE;
+        DEFINE INDEX IF NOT EXISTS email_verification_token_account_idx ON TABLE email_verification_tokens COLUMNS account_id;
+        "#,
+    ).await?;
+
+    Ok(())
+}
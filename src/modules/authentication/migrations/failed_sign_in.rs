@@ -0,0 +1,16 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS failed_sign_ins SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS ip_address   ON TABLE failed_sign_ins TYPE string;
+        DEFINE FIELD IF NOT EXISTS username     ON TABLE failed_sign_ins TYPE string;
+        DEFINE FIELD IF NOT EXISTS created_at   ON TABLE failed_sign_ins TYPE datetime DEFAULT time::now();
+
+        DEFINE INDEX IF NOT EXISTS failed_sign_in_ip_idx ON TABLE failed_sign_ins COLUMNS ip_address, created_at;
+        "#,
+    ).await?;
+
+    Ok(())
+}
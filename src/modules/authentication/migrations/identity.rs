@@ -0,0 +1,31 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS identities SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS account_id   ON TABLE identities TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS provider     ON TABLE identities TYPE string;
+        DEFINE FIELD IF NOT EXISTS subject      ON TABLE identities TYPE string;
+        DEFINE FIELD IF NOT EXISTS email        ON TABLE identities TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS last_used_at ON TABLE identities TYPE option<datetime>;
+        DEFINE FIELD IF NOT EXISTS created_at   ON TABLE identities TYPE datetime DEFAULT time::now();
+        DEFINE FIELD IF NOT EXISTS updated_at   ON TABLE identities TYPE datetime VALUE time::now();
+
+        DEFINE INDEX IF NOT EXISTS identity_provider_subject_idx ON TABLE identities COLUMNS provider, subject UNIQUE;
+        DEFINE INDEX IF NOT EXISTS identity_account_idx ON TABLE identities COLUMNS account_id;
+
+        DEFINE TABLE IF NOT EXISTS external_login_states SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS state_hash    ON TABLE external_login_states TYPE string;
+        DEFINE FIELD IF NOT EXISTS provider      ON TABLE external_login_states TYPE string;
+        DEFINE FIELD IF NOT EXISTS nonce         ON TABLE external_login_states TYPE string;
+        DEFINE FIELD IF NOT EXISTS code_verifier ON TABLE external_login_states TYPE string;
+        DEFINE FIELD IF NOT EXISTS expires_at    ON TABLE external_login_states TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS created_at    ON TABLE external_login_states TYPE datetime DEFAULT time::now();
+
+        DEFINE INDEX IF NOT EXISTS external_login_state_hash_idx ON TABLE external_login_states COLUMNS state_hash UNIQUE;
+        "#,
+    ).await?;
+
+    Ok(())
+}
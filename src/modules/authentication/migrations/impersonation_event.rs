@@ -0,0 +1,20 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS impersonation_events SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS actor_id   ON TABLE impersonation_events TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS account_id ON TABLE impersonation_events TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS session_id ON TABLE impersonation_events TYPE record<sessions>;
+        DEFINE FIELD IF NOT EXISTS action     ON TABLE impersonation_events TYPE string;
+        DEFINE FIELD IF NOT EXISTS ip_address ON TABLE impersonation_events TYPE string DEFAULT "";
+        DEFINE FIELD IF NOT EXISTS created_at ON TABLE impersonation_events TYPE datetime DEFAULT time::now();
+
+        DEFINE INDEX IF NOT EXISTS impersonation_event_actor_idx ON TABLE impersonation_events COLUMNS actor_id;
+        DEFINE INDEX IF NOT EXISTS impersonation_event_account_idx ON TABLE impersonation_events COLUMNS account_id;
+        "#,
+    ).await?;
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS magic_link_tokens SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS account_id    ON TABLE magic_link_tokens TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS token_hash    ON TABLE magic_link_tokens TYPE string;
+        DEFINE FIELD IF NOT EXISTS code_hash     ON TABLE magic_link_tokens TYPE string;
+        DEFINE FIELD IF NOT EXISTS ip_address    ON TABLE magic_link_tokens TYPE string;
+        DEFINE FIELD IF NOT EXISTS code_attempts ON TABLE magic_link_tokens TYPE int DEFAULT 0;
+        DEFINE FIELD IF NOT EXISTS expires_at    ON TABLE magic_link_tokens TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS created_at    ON TABLE magic_link_tokens TYPE datetime DEFAULT time::now();
+
+        DEFINE INDEX IF NOT EXISTS magic_link_token_hash_idx ON TABLE magic_link_tokens COLUMNS token_hash UNIQUE;
+        DEFINE INDEX IF NOT EXISTS magic_link_token_account_idx ON TABLE magic_link_tokens COLUMNS account_id;
+        DEFINE INDEX IF NOT EXISTS magic_link_token_ip_idx ON TABLE magic_link_tokens COLUMNS ip_address;
+        "#,
+    ).await?;
+
+    Ok(())
+}
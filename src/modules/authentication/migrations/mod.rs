@@ -1,10 +1,40 @@
 use crate::modules::base::exports::DatabaseConnection;
 
 mod account;
+mod account_role;
+mod api_key;
+mod credential;
+mod email_verification_token;
+mod failed_sign_in;
+mod identity;
+mod impersonation_event;
+mod magic_link_token;
+mod oauth;
+mod password_history;
+mod password_reset_token;
+mod revoked_token;
+mod role;
+mod service_account;
 mod session;
+mod signing_key;
 
 pub async fn run_migrations(db: &DatabaseConnection) -> anyhow::Result<()> {
     account::run_migration(db).await?;
     session::run_migration(db).await?;
+    role::run_migration(db).await?;
+    account_role::run_migration(db).await?;
+    failed_sign_in::run_migration(db).await?;
+    credential::run_migration(db).await?;
+    password_reset_token::run_migration(db).await?;
+    email_verification_token::run_migration(db).await?;
+    oauth::run_migration(db).await?;
+    identity::run_migration(db).await?;
+    api_key::run_migration(db).await?;
+    service_account::run_migration(db).await?;
+    signing_key::run_migration(db).await?;
+    revoked_token::run_migration(db).await?;
+    password_history::run_migration(db).await?;
+    magic_link_token::run_migration(db).await?;
+    impersonation_event::run_migration(db).await?;
     Ok(())
 }
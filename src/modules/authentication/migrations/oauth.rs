@@ -0,0 +1,30 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS oauth_clients SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS name               ON TABLE oauth_clients TYPE string;
+        DEFINE FIELD IF NOT EXISTS client_secret_hash ON TABLE oauth_clients TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS redirect_uris      ON TABLE oauth_clients TYPE array<string> DEFAULT [];
+        DEFINE FIELD IF NOT EXISTS allowed_scopes     ON TABLE oauth_clients TYPE array<string> DEFAULT [];
+        DEFINE FIELD IF NOT EXISTS created_at         ON TABLE oauth_clients TYPE datetime DEFAULT time::now();
+        DEFINE FIELD IF NOT EXISTS updated_at         ON TABLE oauth_clients TYPE datetime VALUE time::now();
+
+        DEFINE TABLE IF NOT EXISTS oauth_authorization_codes SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS code_hash      ON TABLE oauth_authorization_codes TYPE string;
+        DEFINE FIELD IF NOT EXISTS client_id      ON TABLE oauth_authorization_codes TYPE record<oauth_clients>;
+        DEFINE FIELD IF NOT EXISTS account_id     ON TABLE oauth_authorization_codes TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS redirect_uri   ON TABLE oauth_authorization_codes TYPE string;
+        DEFINE FIELD IF NOT EXISTS scopes         ON TABLE oauth_authorization_codes TYPE array<string> DEFAULT [];
+        DEFINE FIELD IF NOT EXISTS code_challenge ON TABLE oauth_authorization_codes TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS nonce          ON TABLE oauth_authorization_codes TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS expires_at     ON TABLE oauth_authorization_codes TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS created_at     ON TABLE oauth_authorization_codes TYPE datetime DEFAULT time::now();
+
+        DEFINE INDEX IF NOT EXISTS oauth_authorization_code_hash_idx ON TABLE oauth_authorization_codes COLUMNS code_hash UNIQUE;
+        "#,
+    ).await?;
+
+    Ok(())
+}
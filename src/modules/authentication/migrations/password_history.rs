@@ -0,0 +1,16 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS password_history SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS account_id    ON TABLE password_history TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS password_hash ON TABLE password_history TYPE string;
+        DEFINE FIELD IF NOT EXISTS created_at    ON TABLE password_history TYPE datetime DEFAULT time::now();
+
+        DEFINE INDEX IF NOT EXISTS password_history_account_idx ON TABLE password_history COLUMNS account_id;
+        "#,
+    ).await?;
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS password_reset_tokens SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS account_id   ON TABLE password_reset_tokens TYPE record<accounts>;
+        DEFINE FIELD IF NOT EXISTS token_hash   ON TABLE password_reset_tokens TYPE string;
+        DEFINE FIELD IF NOT EXISTS expires_at   ON TABLE password_reset_tokens TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS created_at   ON TABLE password_reset_tokens TYPE datetime DEFAULT time::now();
+
+        DEFINE INDEX IF NOT EXISTS password_reset_token_hash_idx ON TABLE password_reset_tokens COLUMNS token_hash UNIQUE;
+        DEFINE INDEX IF NOT EXISTS password_reset_token_account_idx ON TABLE password_reset_tokens COLUMNS account_id;
+        "#,
+    ).await?;
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS revoked_tokens SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS token_id   ON TABLE revoked_tokens TYPE string;
+        DEFINE FIELD IF NOT EXISTS expires_at ON TABLE revoked_tokens TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS created_at ON TABLE revoked_tokens TYPE datetime DEFAULT time::now();
+
+        DEFINE INDEX IF NOT EXISTS revoked_token_token_id_idx ON TABLE revoked_tokens COLUMNS token_id UNIQUE;
+        DEFINE INDEX IF NOT EXISTS revoked_token_expires_at_idx ON TABLE revoked_tokens COLUMNS expires_at;
+        "#,
+    ).await?;
+
+    Ok(())
+}
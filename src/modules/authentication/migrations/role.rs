@@ -0,0 +1,20 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS roles SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS name         ON TABLE roles TYPE string;
+        DEFINE FIELD IF NOT EXISTS description  ON TABLE roles TYPE string DEFAULT "";
+        DEFINE FIELD IF NOT EXISTS permissions  ON TABLE roles TYPE array<string> DEFAULT [];
+        DEFINE FIELD IF NOT EXISTS created_at   ON TABLE roles TYPE datetime DEFAULT time::now();
+        DEFINE FIELD IF NOT EXISTS updated_at   ON TABLE roles TYPE datetime VALUE time::now();
+
+        DEFINE INDEX IF NOT EXISTS role_name_unique ON TABLE roles COLUMNS name UNIQUE;
+
+        INSERT IGNORE INTO roles { id: roles:admin, name: "admin", description: "Full access", permissions: ["*"] };
+        "#,
+    ).await?;
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS service_accounts SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS name               ON TABLE service_accounts TYPE string;
+        DEFINE FIELD IF NOT EXISTS description        ON TABLE service_accounts TYPE string DEFAULT "";
+        DEFINE FIELD IF NOT EXISTS client_secret_hash ON TABLE service_accounts TYPE string;
+        DEFINE FIELD IF NOT EXISTS scopes             ON TABLE service_accounts TYPE array<string> DEFAULT [];
+        DEFINE FIELD IF NOT EXISTS is_active          ON TABLE service_accounts TYPE bool DEFAULT true;
+        DEFINE FIELD IF NOT EXISTS created_at         ON TABLE service_accounts TYPE datetime DEFAULT time::now();
+        DEFINE FIELD IF NOT EXISTS updated_at         ON TABLE service_accounts TYPE datetime VALUE time::now();
+
+        DEFINE INDEX IF NOT EXISTS service_account_name_idx ON TABLE service_accounts COLUMNS name UNIQUE;
+        "#,
+    ).await?;
+
+    Ok(())
+}
@@ -6,6 +6,7 @@ pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
         DEFINE TABLE IF NOT EXISTS sessions SCHEMAFULL;
         DEFINE FIELD IF NOT EXISTS account_id   ON TABLE sessions TYPE record<accounts>;
         DEFINE FIELD IF NOT EXISTS refresh_hash ON TABLE sessions TYPE string;
+        DEFINE FIELD IF NOT EXISTS previous_refresh_hashes ON TABLE sessions TYPE array<string> DEFAULT [];
         DEFINE FIELD IF NOT EXISTS user_agent   ON TABLE sessions TYPE string DEFAULT "";
         DEFINE FIELD IF NOT EXISTS ip_address   ON TABLE sessions TYPE string DEFAULT "";
         DEFINE FIELD IF NOT EXISTS created_at   ON TABLE sessions TYPE datetime DEFAULT time::now();
@@ -13,9 +14,21 @@ pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
         DEFINE FIELD IF NOT EXISTS is_active    ON TABLE sessions TYPE bool DEFAULT false;
         DEFINE FIELD IF NOT EXISTS user_agent   ON TABLE sessions TYPE string DEFAULT "";
         DEFINE FIELD IF NOT EXISTS ip_address   ON TABLE sessions TYPE string DEFAULT "";
+        DEFINE FIELD IF NOT EXISTS audience     ON TABLE sessions TYPE string DEFAULT "core-auth";
+        DEFINE FIELD IF NOT EXISTS scopes       ON TABLE sessions TYPE array<string> DEFAULT [];
+        DEFINE FIELD IF NOT EXISTS impersonator_id ON TABLE sessions TYPE option<record<accounts>>;
+        DEFINE FIELD IF NOT EXISTS device_label ON TABLE sessions TYPE string DEFAULT "";
+        DEFINE FIELD IF NOT EXISTS name         ON TABLE sessions TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS last_used_at ON TABLE sessions TYPE option<datetime>;
+        DEFINE FIELD IF NOT EXISTS last_ip_address ON TABLE sessions TYPE option<string>;
 
         DEFINE INDEX IF NOT EXISTS session_refresh_unique ON TABLE sessions COLUMNS refresh_hash UNIQUE;
         DEFINE INDEX IF NOT EXISTS session_account_idx    ON TABLE sessions COLUMNS account_id;
+        DEFINE INDEX IF NOT EXISTS session_previous_refresh_idx ON TABLE sessions COLUMNS previous_refresh_hashes;
+
+        UPDATE sessions SET previous_refresh_hashes = [] WHERE previous_refresh_hashes = NONE;
+        UPDATE sessions SET audience = "core-auth", scopes = [] WHERE audience = NONE;
+        UPDATE sessions SET device_label = "" WHERE device_label = NONE;
         "#,
     ).await?;
 
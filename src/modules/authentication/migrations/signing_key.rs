@@ -0,0 +1,23 @@
+use crate::modules::base::exports::DatabaseConnection;
+
+pub async fn run_migration(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.query(
+        r#"
+        DEFINE TABLE IF NOT EXISTS signing_keys SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS kid          ON TABLE signing_keys TYPE string;
+        DEFINE FIELD IF NOT EXISTS algorithm    ON TABLE signing_keys TYPE string;
+        DEFINE FIELD IF NOT EXISTS key_material ON TABLE signing_keys TYPE string;
+        DEFINE FIELD IF NOT EXISTS is_encrypted ON TABLE signing_keys TYPE bool DEFAULT false;
+        DEFINE FIELD IF NOT EXISTS status       ON TABLE signing_keys TYPE string ASSERT $value IN ["pending", "active", "inactive", "retired"];
+        DEFINE FIELD IF NOT EXISTS activated_at ON TABLE signing_keys TYPE option<datetime>;
+        DEFINE FIELD IF NOT EXISTS retire_at    ON TABLE signing_keys TYPE option<datetime>;
+        DEFINE FIELD IF NOT EXISTS created_at   ON TABLE signing_keys TYPE datetime DEFAULT time::now();
+        DEFINE FIELD IF NOT EXISTS updated_at   ON TABLE signing_keys TYPE datetime VALUE time::now();
+
+        DEFINE INDEX IF NOT EXISTS signing_key_kid_idx ON TABLE signing_keys COLUMNS kid UNIQUE;
+        DEFINE INDEX IF NOT EXISTS signing_key_status_idx ON TABLE signing_keys COLUMNS status;
+        "#,
+    ).await?;
+
+    Ok(())
+}
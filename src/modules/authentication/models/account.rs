@@ -7,6 +7,31 @@ pub struct AccountModel {
     pub id: BaseId,
     pub username: String,
     pub password: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    #[serde(default)]
+    pub email_verified_at: Option<BaseDateTime>,
+    #[serde(default)]
+    pub failed_sign_in_attempts: u32,
+    #[serde(default)]
+    pub locked_until: Option<BaseDateTime>,
+    #[serde(default)]
+    pub totp_enabled: bool,
+    #[serde(default)]
+    pub totp_secret: Option<String>,
+    #[serde(default)]
+    pub totp_pending_secret: Option<String>,
+    #[serde(default)]
+    pub totp_recovery_codes: Vec<String>,
+    #[serde(default)]
+    pub totp_last_used_step: Option<u64>,
+    #[serde(default)]
+    pub must_change_password: bool,
+    /// Unset for passwords that have not been changed since the account was created.
+    #[serde(default)]
+    pub password_changed_at: Option<BaseDateTime>,
     pub created_at: BaseDateTime,
     pub updated_at: BaseDateTime,
 }
@@ -20,3 +45,17 @@ impl DatabaseModel for AccountModel {
         "acc_".to_string()
     }
 }
+
+impl AccountModel {
+    pub fn is_locked(&self) -> bool {
+        self.locked_until
+            .as_ref()
+            .is_some_and(|locked_until| locked_until > &BaseDateTime::from(chrono::Utc::now()))
+    }
+
+    pub fn password_changed_at(&self) -> &BaseDateTime {
+        self.password_changed_at
+            .as_ref()
+            .unwrap_or(&self.created_at)
+    }
+}
@@ -0,0 +1,21 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountRoleModel {
+    pub id: BaseId,
+    pub account_id: BaseId,
+    pub role_id: BaseId,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for AccountRoleModel {
+    fn table_name() -> &'static str {
+        "account_roles"
+    }
+
+    fn key_prefix() -> String {
+        "acr_".to_string()
+    }
+}
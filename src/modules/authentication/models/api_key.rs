@@ -0,0 +1,35 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKeyModel {
+    pub id: BaseId,
+    pub account_id: BaseId,
+    pub name: String,
+    pub token_hash: String,
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<BaseDateTime>,
+    pub last_used_at: Option<BaseDateTime>,
+    pub created_at: BaseDateTime,
+    pub updated_at: BaseDateTime,
+}
+
+impl DatabaseModel for ApiKeyModel {
+    fn table_name() -> &'static str {
+        "api_keys"
+    }
+
+    fn key_prefix() -> String {
+        "apk_".to_string()
+    }
+}
+
+impl ApiKeyModel {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .as_ref()
+            .is_some_and(|expires_at| expires_at <= &BaseDateTime::from(chrono::Utc::now()))
+    }
+}
@@ -0,0 +1,27 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthorizationCodeModel {
+    pub id: BaseId,
+    pub code_hash: String,
+    pub client_id: BaseId,
+    pub account_id: BaseId,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    pub code_challenge: Option<String>,
+    pub nonce: Option<String>,
+    pub expires_at: BaseDateTime,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for AuthorizationCodeModel {
+    fn table_name() -> &'static str {
+        "oauth_authorization_codes"
+    }
+
+    fn key_prefix() -> String {
+        "oac_".to_string()
+    }
+}
@@ -0,0 +1,27 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialModel {
+    pub id: BaseId,
+    pub account_id: BaseId,
+    pub credential_id: String,
+    pub public_key: String,
+    pub algorithm: i64,
+    pub sign_count: u32,
+    pub name: String,
+    pub transports: Vec<String>,
+    pub created_at: BaseDateTime,
+    pub last_used_at: Option<BaseDateTime>,
+}
+
+impl DatabaseModel for CredentialModel {
+    fn table_name() -> &'static str {
+        "credentials"
+    }
+
+    fn key_prefix() -> String {
+        "crd_".to_string()
+    }
+}
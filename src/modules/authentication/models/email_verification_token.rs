@@ -0,0 +1,23 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EmailVerificationTokenModel {
+    pub id: BaseId,
+    pub account_id: BaseId,
+    pub email: String,
+    pub token_hash: String,
+    pub expires_at: BaseDateTime,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for EmailVerificationTokenModel {
+    fn table_name() -> &'static str {
+        "email_verification_tokens"
+    }
+
+    fn key_prefix() -> String {
+        "evt_".to_string()
+    }
+}
@@ -0,0 +1,24 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExternalLoginStateModel {
+    pub id: BaseId,
+    pub state_hash: String,
+    pub provider: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub expires_at: BaseDateTime,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for ExternalLoginStateModel {
+    fn table_name() -> &'static str {
+        "external_login_states"
+    }
+
+    fn key_prefix() -> String {
+        "els_".to_string()
+    }
+}
@@ -0,0 +1,21 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FailedSignInModel {
+    pub id: BaseId,
+    pub ip_address: String,
+    pub username: String,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for FailedSignInModel {
+    fn table_name() -> &'static str {
+        "failed_sign_ins"
+    }
+
+    fn key_prefix() -> String {
+        "fsi_".to_string()
+    }
+}
@@ -0,0 +1,25 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IdentityModel {
+    pub id: BaseId,
+    pub account_id: BaseId,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub last_used_at: Option<BaseDateTime>,
+    pub created_at: BaseDateTime,
+    pub updated_at: BaseDateTime,
+}
+
+impl DatabaseModel for IdentityModel {
+    fn table_name() -> &'static str {
+        "identities"
+    }
+
+    fn key_prefix() -> String {
+        "idn_".to_string()
+    }
+}
@@ -0,0 +1,25 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImpersonationEventModel {
+    pub id: BaseId,
+    /// The admin acting as `account_id`.
+    pub actor_id: BaseId,
+    pub account_id: BaseId,
+    pub session_id: BaseId,
+    pub action: String,
+    pub ip_address: String,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for ImpersonationEventModel {
+    fn table_name() -> &'static str {
+        "impersonation_events"
+    }
+
+    fn key_prefix() -> String {
+        "ime_".to_string()
+    }
+}
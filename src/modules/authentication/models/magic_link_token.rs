@@ -0,0 +1,26 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MagicLinkTokenModel {
+    pub id: BaseId,
+    pub account_id: BaseId,
+    pub token_hash: String,
+    pub code_hash: String,
+    pub ip_address: String,
+    #[serde(default)]
+    pub code_attempts: u32,
+    pub expires_at: BaseDateTime,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for MagicLinkTokenModel {
+    fn table_name() -> &'static str {
+        "magic_link_tokens"
+    }
+
+    fn key_prefix() -> String {
+        "mlt_".to_string()
+    }
+}
@@ -1,5 +1,23 @@
 pub mod account;
+pub mod account_role;
+pub mod api_key;
+pub mod authorization_code;
+pub mod credential;
+pub mod email_verification_token;
+pub mod external_login_state;
+pub mod failed_sign_in;
+pub mod identity;
+pub mod impersonation_event;
+pub mod magic_link_token;
+pub mod oauth_client;
+pub mod password_history;
+pub mod password_reset_token;
+pub mod revoked_token;
+pub mod role;
+pub mod service_account;
 pub mod session;
+pub mod signing_key;
+pub mod webauthn_challenge;
 
 pub(super) mod prelude {
     pub use crate::modules::base::exports::{BaseDateTime, BaseId};
@@ -0,0 +1,30 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OAuthClientModel {
+    pub id: BaseId,
+    pub name: String,
+    pub client_secret_hash: Option<String>,
+    pub redirect_uris: Vec<String>,
+    pub allowed_scopes: Vec<String>,
+    pub created_at: BaseDateTime,
+    pub updated_at: BaseDateTime,
+}
+
+impl DatabaseModel for OAuthClientModel {
+    fn table_name() -> &'static str {
+        "oauth_clients"
+    }
+
+    fn key_prefix() -> String {
+        "cli_".to_string()
+    }
+}
+
+impl OAuthClientModel {
+    pub fn is_confidential(&self) -> bool {
+        self.client_secret_hash.is_some()
+    }
+}
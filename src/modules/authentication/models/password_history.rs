@@ -0,0 +1,21 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasswordHistoryModel {
+    pub id: BaseId,
+    pub account_id: BaseId,
+    pub password_hash: String,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for PasswordHistoryModel {
+    fn table_name() -> &'static str {
+        "password_history"
+    }
+
+    fn key_prefix() -> String {
+        "pwh_".to_string()
+    }
+}
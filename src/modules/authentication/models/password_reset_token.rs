@@ -0,0 +1,22 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasswordResetTokenModel {
+    pub id: BaseId,
+    pub account_id: BaseId,
+    pub token_hash: String,
+    pub expires_at: BaseDateTime,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for PasswordResetTokenModel {
+    fn table_name() -> &'static str {
+        "password_reset_tokens"
+    }
+
+    fn key_prefix() -> String {
+        "prt_".to_string()
+    }
+}
@@ -0,0 +1,21 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RevokedTokenModel {
+    pub id: BaseId,
+    pub token_id: String,
+    pub expires_at: BaseDateTime,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for RevokedTokenModel {
+    fn table_name() -> &'static str {
+        "revoked_tokens"
+    }
+
+    fn key_prefix() -> String {
+        "rvk_".to_string()
+    }
+}
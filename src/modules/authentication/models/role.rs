@@ -0,0 +1,23 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoleModel {
+    pub id: BaseId,
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+    pub created_at: BaseDateTime,
+    pub updated_at: BaseDateTime,
+}
+
+impl DatabaseModel for RoleModel {
+    fn table_name() -> &'static str {
+        "roles"
+    }
+
+    fn key_prefix() -> String {
+        "rol_".to_string()
+    }
+}
@@ -0,0 +1,25 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceAccountModel {
+    pub id: BaseId,
+    pub name: String,
+    pub description: String,
+    pub client_secret_hash: String,
+    pub scopes: Vec<String>,
+    pub is_active: bool,
+    pub created_at: BaseDateTime,
+    pub updated_at: BaseDateTime,
+}
+
+impl DatabaseModel for ServiceAccountModel {
+    fn table_name() -> &'static str {
+        "service_accounts"
+    }
+
+    fn key_prefix() -> String {
+        "svc_".to_string()
+    }
+}
@@ -7,11 +7,45 @@ pub struct SessionModel {
     pub id: BaseId,
     pub account_id: BaseId,
     pub refresh_hash: String,
+    #[serde(default)]
+    pub previous_refresh_hashes: Vec<String>,
     pub ip_address: String,
     pub user_agent: String,
+    /// Browser and operating system of the last request, parsed from the user agent.
+    #[serde(default)]
+    pub device_label: String,
+    /// Name given by the account holder, shown instead of the device label.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub last_used_at: Option<BaseDateTime>,
+    #[serde(default)]
+    pub last_ip_address: Option<String>,
     pub created_at: BaseDateTime,
     pub expires_at: BaseDateTime,
     pub is_active: bool,
+    #[serde(default = "default_audience")]
+    pub audience: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Set for sessions an admin opened to act as the account.
+    #[serde(default)]
+    pub impersonator_id: Option<BaseId>,
+}
+
+fn default_audience() -> String {
+    "core-auth".to_string()
+}
+
+impl SessionModel {
+    /// Sessions from before activity was tracked count as last used when they were created.
+    pub fn last_used_at(&self) -> &BaseDateTime {
+        self.last_used_at.as_ref().unwrap_or(&self.created_at)
+    }
+
+    pub fn last_ip_address(&self) -> &str {
+        self.last_ip_address.as_deref().unwrap_or(&self.ip_address)
+    }
 }
 
 impl DatabaseModel for SessionModel {
@@ -0,0 +1,41 @@
+use crate::{
+    common::model::DatabaseModel, modules::authentication::config::authentication::JwtAlgorithm,
+};
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningKeyStatus {
+    /// Published for verification but not used for signing yet.
+    Pending,
+    Active,
+    /// Replaced by a newer key, still verifies until `retire_at`.
+    Inactive,
+    Retired,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SigningKeyModel {
+    pub id: BaseId,
+    pub kid: String,
+    pub algorithm: JwtAlgorithm,
+    /// PEM private key or HMAC secret, encrypted when `is_encrypted` is set.
+    pub key_material: String,
+    pub is_encrypted: bool,
+    pub status: SigningKeyStatus,
+    pub activated_at: Option<BaseDateTime>,
+    pub retire_at: Option<BaseDateTime>,
+    pub created_at: BaseDateTime,
+    pub updated_at: BaseDateTime,
+}
+
+impl DatabaseModel for SigningKeyModel {
+    fn table_name() -> &'static str {
+        "signing_keys"
+    }
+
+    fn key_prefix() -> String {
+        "sgk_".to_string()
+    }
+}
@@ -0,0 +1,23 @@
+use crate::common::model::DatabaseModel;
+
+use super::prelude::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebAuthnChallengeModel {
+    pub id: BaseId,
+    pub challenge: String,
+    pub ceremony: String,
+    pub account_id: Option<BaseId>,
+    pub expires_at: BaseDateTime,
+    pub created_at: BaseDateTime,
+}
+
+impl DatabaseModel for WebAuthnChallengeModel {
+    fn table_name() -> &'static str {
+        "webauthn_challenges"
+    }
+
+    fn key_prefix() -> String {
+        "wac_".to_string()
+    }
+}
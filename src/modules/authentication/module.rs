@@ -1,9 +1,18 @@
 use crate::{
     common::{module::Module, server::ServerSettings},
     config::{enviroment::EnviromentConfiguration, file::FileConfiguration},
-    modules::{authentication::routes::routes, base::exports::DatabaseConnection},
+    modules::{
+        authentication::{
+            config::authentication::{AuthenticationConfiguration, MailTransport},
+            routes::routes,
+            services::{
+                account::AccountService, encryption::EncryptionService, password::PasswordService,
+                role::RoleService, signing_key::SigningKeyService,
+            },
+        },
+        base::exports::DatabaseConnection,
+    },
 };
-use axum::Router;
 use std::sync::Mutex;
 
 pub struct AuthenticationModule;
@@ -26,9 +35,46 @@ impl Module for AuthenticationModule {
         &self,
         db_connection: DatabaseConnection,
         _env_config: &EnviromentConfiguration,
-        _file_config: &FileConfiguration,
+        file_config: &FileConfiguration,
     ) -> anyhow::Result<()> {
         super::migrations::run_migrations(&db_connection).await?;
+
+        if let Some(auth_config) = file_config.get_as::<AuthenticationConfiguration>() {
+            // A malformed key would otherwise only surface as a server error on TOTP enrollment
+            if auth_config.mfa.encryption_key.is_empty() {
+                tracing::warn!("No MFA encryption key is configured, TOTP is unavailable");
+            } else {
+                EncryptionService::new(&auth_config.mfa.encryption_key).map_err(|e| {
+                    anyhow::anyhow!("Invalid authentication.mfa.encryptionKey: {}", e)
+                })?;
+            }
+
+            if auth_config.mail.transport != MailTransport::Smtp {
+                tracing::warn!(
+                    "Mail transport {:?} writes password reset links, sign-in codes and verification tokens in plain text, use smtp in production",
+                    auth_config.mail.transport
+                );
+            }
+
+            // Fail on startup rather than on the first sign-in when no key can be loaded
+            SigningKeyService::new(auth_config.signing_keys.clone(), db_connection.clone())
+                .ensure_keyring(&auth_config)
+                .await?;
+
+            let account_service = AccountService::new(auth_config.clone(), db_connection.clone());
+            let password_service = PasswordService::new(
+                auth_config.password_policy.clone(),
+                auth_config.password_hashing.clone(),
+            );
+            RoleService::new(db_connection)
+                .ensure_admin_accounts(
+                    &account_service,
+                    &password_service,
+                    &auth_config.admin_accounts,
+                )
+                .await?;
+        }
+
         Ok(())
     }
 }
@@ -2,19 +2,37 @@ use crate::{
     common::model::DatabaseModel,
     error_return,
     modules::authentication::{
-        auth_services::AuthenticationServiceGuard, auth_state::AuthenticatedGuard,
-        dtos::account::*, errors::service::*, models::account::AccountModel,
+        auth_services::AuthenticationServiceGuard,
+        auth_state::{AuthenticatedGuard, PrincipalGuard, SessionOrApiKeyGuard},
+        dtos::{
+            account::*,
+            api_key::{ApiKeyDTO, ApiKeySecretDto, CreateApiKeyRequestDto},
+            impersonation::ImpersonationEventDTO,
+        },
+        errors::service::*,
+        models::{account::AccountModel, api_key::ApiKeyModel},
+        permission::{
+            AccountsDelete, AccountsImpersonate, AccountsRead, AccountsWrite, Permission,
+            RequirePermission,
+        },
     },
+    modules::base::exports::request_info::RequestInfoExtractor,
 };
 use axum::{Json, extract::Path, http::StatusCode};
 use serde_json::{Value, json};
 
 #[axum::debug_handler()]
-async fn self_get_account(account_session: AuthenticatedGuard) -> (StatusCode, Json<Value>) {
+async fn self_get_account(
+    SessionOrApiKeyGuard(account_session): SessionOrApiKeyGuard,
+) -> (StatusCode, Json<Value>) {
     let account = account_session.account;
     let dto = AccountDTO::from(&account);
+    let impersonator = account_session.impersonator.as_ref().map(AccountDTO::from);
 
-    (StatusCode::OK, Json(json!({"account": dto})))
+    (
+        StatusCode::OK,
+        Json(json!({"account": dto, "impersonator": impersonator})),
+    )
 }
 
 #[axum::debug_handler()]
@@ -22,6 +40,8 @@ async fn self_delete_account(
     auth_services: AuthenticationServiceGuard,
     account_session: AuthenticatedGuard,
 ) -> (StatusCode, Json<Value>) {
+    error_return!(account_session.require_session());
+    error_return!(account_session.forbid_impersonation());
     error_return!(let (
         authentication_service,
         account_service,
@@ -29,12 +49,20 @@ async fn self_delete_account(
         session_service,
         _token_service,
     ) = auth_services.authentication_service_with_deps());
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(let webauthn_service = auth_services.webauthn_service());
+    error_return!(let external_login_service = auth_services.external_login_service());
+    error_return!(let api_key_service = auth_services.api_key_service());
 
     error_return!(
         authentication_service
             .delete_account(
                 &account_service,
                 &session_service,
+                &role_service,
+                &webauthn_service,
+                &external_login_service,
+                &api_key_service,
                 &account_session.account.id,
             )
             .await
@@ -52,6 +80,8 @@ async fn self_update_account(
     account_session: AuthenticatedGuard,
     Json(dto): Json<UpdateAccountRequestDTO>,
 ) -> (StatusCode, Json<Value>) {
+    error_return!(account_session.require_session());
+    error_return!(account_session.forbid_impersonation());
     let account_id = account_session.account_id;
     error_return!(let account_service = auth_services.account_service());
 
@@ -72,6 +102,21 @@ async fn self_update_account(
         );
     }
 
+    if let Some(email) = dto.email {
+        error_return!(let email_changed = account_service.update_account_email(&account_id, &email).await);
+
+        if email_changed {
+            error_return!(let account = account_service.get_account_by_id(&account_id).await);
+            error_return!(let email_verification_service = auth_services.email_verification_service());
+            error_return!(let mailer = auth_services.mailer());
+            error_return!(
+                email_verification_service
+                    .send_verification(mailer.as_ref(), &account)
+                    .await
+            );
+        }
+    }
+
     (
         StatusCode::OK,
         Json(json!({"message": "Account updated successfully"})),
@@ -81,7 +126,7 @@ async fn self_update_account(
 #[axum::debug_handler()]
 async fn list_all_accounts(
     auth_services: AuthenticationServiceGuard,
-    _: AuthenticatedGuard,
+    _: RequirePermission<AccountsRead>,
 ) -> (StatusCode, Json<Value>) {
     error_return!(let account_service = auth_services.account_service());
     error_return!(let accounts = account_service.get_all_accounts().await);
@@ -94,7 +139,7 @@ async fn list_all_accounts(
 #[axum::debug_handler()]
 async fn get_account_by_id(
     auth_services: AuthenticationServiceGuard,
-    _: AuthenticatedGuard,
+    _: RequirePermission<AccountsRead>,
     Path(id): Path<String>,
 ) -> (StatusCode, Json<Value>) {
     error_return!(let account_id = AccountModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidAccountId)));
@@ -109,7 +154,7 @@ async fn get_account_by_id(
 #[axum::debug_handler()]
 async fn delete_account_by_id(
     auth_services: AuthenticationServiceGuard,
-    _: AuthenticatedGuard,
+    _: RequirePermission<AccountsDelete>,
     Path(id): Path<String>,
 ) -> (StatusCode, Json<Value>) {
     error_return!(let account_id = AccountModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidAccountId)));
@@ -120,10 +165,22 @@ async fn delete_account_by_id(
         session_service,
         _token_service,
     ) = auth_services.authentication_service_with_deps());
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(let webauthn_service = auth_services.webauthn_service());
+    error_return!(let external_login_service = auth_services.external_login_service());
+    error_return!(let api_key_service = auth_services.api_key_service());
 
     error_return!(
         authentication_service
-            .delete_account(&account_service, &session_service, &account_id)
+            .delete_account(
+                &account_service,
+                &session_service,
+                &role_service,
+                &webauthn_service,
+                &external_login_service,
+                &api_key_service,
+                &account_id,
+            )
             .await
     );
 
@@ -136,7 +193,7 @@ async fn delete_account_by_id(
 #[axum::debug_handler()]
 async fn update_account_by_id(
     auth_services: AuthenticationServiceGuard,
-    _: AuthenticatedGuard,
+    _: RequirePermission<AccountsWrite>,
     Path(id): Path<String>,
     Json(dto): Json<UpdateAccountRequestDTO>,
 ) -> (StatusCode, Json<Value>) {
@@ -159,19 +216,271 @@ async fn update_account_by_id(
         );
     }
 
+    if let Some(email) = dto.email {
+        error_return!(
+            account_service
+                .update_account_email(&account_id, &email)
+                .await
+        );
+    }
+
     (
         StatusCode::OK,
         Json(json!({"message": "Account updated successfully"})),
     )
 }
 
+#[axum::debug_handler()]
+async fn unlock_account_by_id(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<AccountsWrite>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_id = AccountModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidAccountId)));
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(account_service.get_account_by_id(&account_id).await);
+    error_return!(account_service.unlock_account(&account_id).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Account unlocked successfully"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn require_password_change_by_id(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<AccountsWrite>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_id = AccountModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidAccountId)));
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(account_service.get_account_by_id(&account_id).await);
+    error_return!(account_service.require_password_change(&account_id).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Password change required on next sign-in"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn impersonate_account_by_id(
+    request_info: RequestInfoExtractor,
+    auth_services: AuthenticationServiceGuard,
+    permission: RequirePermission<AccountsImpersonate>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    // Service accounts have no session a human could be held accountable for
+    error_return!(let actor = match permission.auth {
+        PrincipalGuard::Human(actor) => Ok(actor),
+        PrincipalGuard::Service(_) => Err(AuthenticationServiceError::client(
+            AuthenticationClientError::InteractiveSessionRequired,
+        )),
+    });
+    error_return!(actor.require_session());
+    error_return!(actor.forbid_impersonation());
+
+    error_return!(let account_id = AccountModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidAccountId)));
+    error_return!(let (
+        _authentication_service,
+        account_service,
+        _password_service,
+        session_service,
+        token_service,
+    ) = auth_services.authentication_service_with_deps());
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(let impersonation_service = auth_services.impersonation_service());
+
+    // Permissions are limited to what the actor holds, but impersonation must not be chained
+    error_return!(let is_protected = role_service
+        .account_has_permission(&account_id, AccountsImpersonate::NAME)
+        .await);
+    if is_protected {
+        error_return!(Err::<(), _>(AuthenticationServiceError::client(
+            AuthenticationClientError::CannotImpersonate
+        )));
+    }
+
+    error_return!(let auth_response = impersonation_service
+        .start(
+            &account_service,
+            &session_service,
+            &token_service,
+            &actor.account_id,
+            &account_id,
+            request_info,
+        )
+        .await);
+
+    (StatusCode::CREATED, Json(json!(auth_response)))
+}
+
+#[axum::debug_handler()]
+async fn stop_impersonation(
+    request_info: RequestInfoExtractor,
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let session_id = account_session.require_session());
+    error_return!(let impersonator = account_session.impersonator.as_ref().ok_or(AuthenticationServiceError::client(AuthenticationClientError::NotImpersonating)));
+    error_return!(let session_service = auth_services.session_service());
+    error_return!(let impersonation_service = auth_services.impersonation_service());
+
+    error_return!(
+        impersonation_service
+            .stop(
+                &session_service,
+                &impersonator.id,
+                &account_session.account_id,
+                session_id,
+                &request_info.ip_address,
+            )
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Impersonation stopped successfully"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn list_impersonation_events_by_id(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<AccountsRead>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_id = AccountModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidAccountId)));
+    error_return!(let impersonation_service = auth_services.impersonation_service());
+    error_return!(let events = impersonation_service
+        .get_events_for_account(&account_id)
+        .await);
+
+    let event_dtos: Vec<ImpersonationEventDTO> = events
+        .into_iter()
+        .map(ImpersonationEventDTO::from)
+        .collect();
+
+    (StatusCode::OK, Json(json!({"events": event_dtos})))
+}
+
+#[axum::debug_handler()]
+async fn clear_lockout_for_ip(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<AccountsWrite>,
+    Path(ip_address): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(
+        account_service
+            .clear_failed_sign_ins_for_ip(&ip_address)
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Failed sign-in attempts cleared successfully"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn self_list_tokens(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+) -> (StatusCode, Json<Value>) {
+    // API keys must not be able to list or revoke keys
+    error_return!(account_session.require_session());
+    error_return!(let api_key_service = auth_services.api_key_service());
+    error_return!(let api_keys = api_key_service
+        .get_api_keys_for_account(&account_session.account_id)
+        .await);
+
+    let api_key_dtos: Vec<ApiKeyDTO> = api_keys.into_iter().map(ApiKeyDTO::from).collect();
+
+    (StatusCode::OK, Json(json!({"tokens": api_key_dtos})))
+}
+
+#[axum::debug_handler()]
+async fn self_create_token(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+    Json(dto): Json<CreateApiKeyRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    // API keys must not be able to mint further keys
+    error_return!(account_session.require_session());
+    error_return!(account_session.forbid_impersonation());
+    error_return!(let api_key_service = auth_services.api_key_service());
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(let (api_key, token) = api_key_service
+        .create_api_key(&role_service, &account_session.account_id, dto)
+        .await);
+
+    (
+        StatusCode::CREATED,
+        Json(json!(ApiKeySecretDto {
+            api_key: ApiKeyDTO::from(api_key),
+            token,
+        })),
+    )
+}
+
+#[axum::debug_handler()]
+async fn self_revoke_token(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    // API keys must not be able to list or revoke keys
+    error_return!(account_session.require_session());
+    error_return!(account_session.forbid_impersonation());
+    error_return!(let api_key_id = ApiKeyModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidApiKeyId)));
+    error_return!(let api_key_service = auth_services.api_key_service());
+    error_return!(
+        api_key_service
+            .revoke_api_key(&account_session.account_id, &api_key_id)
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Token revoked successfully"})),
+    )
+}
+
 pub fn routes() -> axum::Router {
     axum::Router::new()
         .route("/me", axum::routing::get(self_get_account))
         .route("/me", axum::routing::delete(self_delete_account))
         .route("/me", axum::routing::patch(self_update_account))
+        .route(
+            "/me/tokens",
+            axum::routing::get(self_list_tokens).post(self_create_token),
+        )
+        .route("/me/tokens/{id}", axum::routing::delete(self_revoke_token))
         .route("/all", axum::routing::get(list_all_accounts))
         .route("/{id}", axum::routing::get(get_account_by_id))
         .route("/{id}", axum::routing::patch(update_account_by_id))
         .route("/{id}", axum::routing::delete(delete_account_by_id))
+        .route("/{id}/unlock", axum::routing::patch(unlock_account_by_id))
+        .route(
+            "/{id}/require-password-change",
+            axum::routing::patch(require_password_change_by_id),
+        )
+        .route(
+            "/{id}/impersonate",
+            axum::routing::post(impersonate_account_by_id),
+        )
+        .route(
+            "/{id}/impersonation-events",
+            axum::routing::get(list_impersonation_events_by_id),
+        )
+        .route(
+            "/impersonation/stop",
+            axum::routing::post(stop_impersonation),
+        )
+        .route(
+            "/lockout/ip/{ip_address}",
+            axum::routing::delete(clear_lockout_for_ip),
+        )
 }
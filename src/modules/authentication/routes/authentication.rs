@@ -4,19 +4,26 @@ use crate::{
         authentication::{
             auth_services::AuthenticationServiceGuard,
             auth_state::{AuthenticatedGuard, NotAuthenticatedGuard},
-            authentication_dto::SignInRequestDto,
+            authentication_dto::{ChangePasswordRequestDto, SignInRequestDto, SignUpRequestDto},
+            dtos::magic_link::{MagicLinkRequestDto, MagicLinkVerifyRequestDto},
+            dtos::password_reset::{ForgotPasswordRequestDto, ResetPasswordRequestDto},
         },
         base::exports::request_info::RequestInfoExtractor,
     },
 };
-use axum::{Json, http::StatusCode};
+use axum::{
+    Json,
+    http::{HeaderMap, StatusCode},
+};
 use serde_json::{Value, json};
 
+use super::error_response;
+
 #[axum::debug_handler()]
 async fn sign_up(
     request_info: RequestInfoExtractor,
     auth_services: AuthenticationServiceGuard,
-    Json(dto): Json<SignInRequestDto>,
+    Json(dto): Json<SignUpRequestDto>,
 ) -> (StatusCode, Json<Value>) {
     error_return!(let (
         authentication_service,
@@ -25,6 +32,8 @@ async fn sign_up(
         session_service,
         token_service,
     ) = auth_services.authentication_service_with_deps());
+    error_return!(let email_verification_service = auth_services.email_verification_service());
+    error_return!(let mailer = auth_services.mailer());
 
     error_return!(let auth_response = authentication_service
         .register(
@@ -32,6 +41,8 @@ async fn sign_up(
             &token_service,
             &session_service,
             &password_service,
+            &email_verification_service,
+            mailer.as_ref(),
             request_info,
             dto,
         )
@@ -46,57 +57,267 @@ async fn sign_in(
     auth_services: AuthenticationServiceGuard,
     _: NotAuthenticatedGuard,
     Json(dto): Json<SignInRequestDto>,
-) -> (StatusCode, Json<Value>) {
-    error_return!(let (
-        authentication_service,
-        account_service,
-        password_service,
-        session_service,
-        token_service,
-    ) = auth_services.authentication_service_with_deps());
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let services = auth_services
+        .authentication_service_with_deps()
+        .and_then(|deps| {
+            Ok((
+                deps,
+                auth_services.mfa_service()?,
+                auth_services.cookie_service()?,
+            ))
+        });
+    let (
+        (authentication_service, account_service, password_service, session_service, token_service),
+        mfa_service,
+        cookie_service,
+    ) = match services {
+        Ok(services) => services,
+        Err(e) => return error_response(e),
+    };
 
-    error_return!(let auth_response = authentication_service
+    let use_cookies = dto.use_cookies;
+    if let Err(e) = cookie_service.ensure_available(use_cookies) {
+        return error_response(e);
+    }
+
+    let response = authentication_service
         .authenticate(
             &account_service,
             &session_service,
             &token_service,
             &password_service,
+            &mfa_service,
             request_info,
             dto,
         )
-        .await);
+        .await
+        .and_then(|sign_in_response| {
+            cookie_service.sign_in_response(sign_in_response, use_cookies)
+        });
 
-    (StatusCode::OK, Json(json!(auth_response)))
+    match response {
+        Ok((headers, body)) => (StatusCode::OK, headers, Json(body)),
+        Err(e) => error_response(e),
+    }
 }
 
 #[axum::debug_handler()]
 async fn sign_out(
     auth_services: AuthenticationServiceGuard,
     account_session: AuthenticatedGuard,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let services = auth_services
+        .authentication_service_with_deps()
+        .and_then(|deps| Ok((deps, auth_services.cookie_service()?)));
+    let (
+        (
+            authentication_service,
+            _account_service,
+            _password_service,
+            session_service,
+            _token_service,
+        ),
+        cookie_service,
+    ) = match services {
+        Ok(services) => services,
+        Err(e) => return error_response(e),
+    };
+
+    let response = match account_session.require_session() {
+        Ok(session_id) => authentication_service
+            .logout(&session_service, session_id)
+            .await
+            .and_then(|_| cookie_service.clear_session_cookies()),
+        Err(e) => Err(e),
+    };
+
+    match response {
+        Ok(headers) => (
+            StatusCode::OK,
+            headers,
+            Json(json!({"message": "Logged out successfully"})),
+        ),
+        Err(e) => error_response(e),
+    }
+}
+
+#[axum::debug_handler()]
+async fn forgot_password(
+    auth_services: AuthenticationServiceGuard,
+    _: NotAuthenticatedGuard,
+    Json(dto): Json<ForgotPasswordRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(let password_reset_service = auth_services.password_reset_service());
+    error_return!(let mailer = auth_services.mailer());
+
+    error_return!(
+        password_reset_service
+            .request_reset(&account_service, mailer.as_ref(), &dto.username)
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "If the account exists, a password reset mail has been sent"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn reset_password(
+    auth_services: AuthenticationServiceGuard,
+    _: NotAuthenticatedGuard,
+    Json(dto): Json<ResetPasswordRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let (account_service, password_service) = auth_services.account_service_with_deps());
+    error_return!(let session_service = auth_services.session_service());
+    error_return!(let password_reset_service = auth_services.password_reset_service());
+
+    error_return!(
+        password_reset_service
+            .reset_password(&account_service, &password_service, &session_service, dto)
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Password reset successfully"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn change_password(
+    auth_services: AuthenticationServiceGuard,
+    _: NotAuthenticatedGuard,
+    Json(dto): Json<ChangePasswordRequestDto>,
 ) -> (StatusCode, Json<Value>) {
     error_return!(let (
         authentication_service,
-        _account_service,
-        _password_service,
+        account_service,
+        password_service,
         session_service,
-        _token_service,
+        token_service,
     ) = auth_services.authentication_service_with_deps());
+    error_return!(let revocation_service = auth_services.revocation_service());
 
     error_return!(
         authentication_service
-            .logout(&session_service, &account_session.session_id)
+            .change_required_password(
+                &account_service,
+                &session_service,
+                &token_service,
+                &password_service,
+                &revocation_service,
+                dto,
+            )
             .await
     );
 
     (
         StatusCode::OK,
-        Json(json!({"message": "Logged out successfully"})),
+        Json(json!({"message": "Password changed successfully, please sign in again"})),
     )
 }
 
+#[axum::debug_handler()]
+async fn request_magic_link(
+    request_info: RequestInfoExtractor,
+    auth_services: AuthenticationServiceGuard,
+    _: NotAuthenticatedGuard,
+    Json(dto): Json<MagicLinkRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(let magic_link_service = auth_services.magic_link_service());
+    error_return!(let mailer = auth_services.mailer());
+
+    error_return!(
+        magic_link_service
+            .request_sign_in(
+                &account_service,
+                mailer.as_ref(),
+                &request_info.ip_address,
+                &dto.email,
+            )
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "If the account exists, a sign-in link has been sent"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn verify_magic_link(
+    request_info: RequestInfoExtractor,
+    auth_services: AuthenticationServiceGuard,
+    _: NotAuthenticatedGuard,
+    Json(dto): Json<MagicLinkVerifyRequestDto>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let services = auth_services
+        .authentication_service_with_deps()
+        .and_then(|deps| {
+            Ok((
+                deps,
+                auth_services.magic_link_service()?,
+                auth_services.mfa_service()?,
+                auth_services.cookie_service()?,
+            ))
+        });
+    let (
+        (
+            authentication_service,
+            account_service,
+            _password_service,
+            session_service,
+            token_service,
+        ),
+        magic_link_service,
+        mfa_service,
+        cookie_service,
+    ) = match services {
+        Ok(services) => services,
+        Err(e) => return error_response(e),
+    };
+
+    if let Err(e) = cookie_service.ensure_available(dto.use_cookies) {
+        return error_response(e);
+    }
+
+    let account = match magic_link_service.redeem(&account_service, &dto).await {
+        Ok(account) => account,
+        Err(e) => return error_response(e),
+    };
+
+    let response = authentication_service
+        .complete_sign_in(
+            &account_service,
+            &session_service,
+            &token_service,
+            &mfa_service,
+            request_info,
+            account,
+        )
+        .await
+        .and_then(|sign_in_response| {
+            cookie_service.sign_in_response(sign_in_response, dto.use_cookies)
+        });
+
+    match response {
+        Ok((headers, body)) => (StatusCode::OK, headers, Json(body)),
+        Err(e) => error_response(e),
+    }
+}
+
 pub fn routes() -> axum::Router {
     axum::Router::new()
         .route("/sign-up", axum::routing::post(sign_up))
         .route("/sign-in", axum::routing::post(sign_in))
         .route("/sign-out", axum::routing::post(sign_out))
+        .route("/password/forgot", axum::routing::post(forgot_password))
+        .route("/password/reset", axum::routing::post(reset_password))
+        .route("/password/change", axum::routing::post(change_password))
+        .route("/magic-link", axum::routing::post(request_magic_link))
+        .route("/magic-link/verify", axum::routing::post(verify_magic_link))
 }
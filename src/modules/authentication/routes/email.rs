@@ -0,0 +1,54 @@
+use crate::{
+    error_return,
+    modules::authentication::{
+        auth_services::AuthenticationServiceGuard, auth_state::AuthenticatedGuard,
+        dtos::email_verification::ConfirmEmailRequestDto,
+    },
+};
+use axum::{Json, http::StatusCode};
+use serde_json::{Value, json};
+
+#[axum::debug_handler()]
+async fn send_verification(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+) -> (StatusCode, Json<Value>) {
+    error_return!(account_session.forbid_impersonation());
+    error_return!(let email_verification_service = auth_services.email_verification_service());
+    error_return!(let mailer = auth_services.mailer());
+    error_return!(
+        email_verification_service
+            .send_verification(mailer.as_ref(), &account_session.account)
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Verification mail sent successfully"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn confirm_verification(
+    auth_services: AuthenticationServiceGuard,
+    Json(dto): Json<ConfirmEmailRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(let email_verification_service = auth_services.email_verification_service());
+    error_return!(
+        email_verification_service
+            .confirm(&account_service, &dto.token)
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Email address verified successfully"})),
+    )
+}
+
+pub fn routes() -> axum::Router {
+    axum::Router::new()
+        .route("/verify/send", axum::routing::post(send_verification))
+        .route("/verify/confirm", axum::routing::post(confirm_verification))
+}
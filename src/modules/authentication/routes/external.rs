@@ -0,0 +1,98 @@
+use crate::{
+    error_return,
+    modules::{
+        authentication::{
+            auth_services::AuthenticationServiceGuard,
+            auth_state::{AuthenticatedGuard, NotAuthenticatedGuard},
+            dtos::external_login::{
+                ExternalAuthorizationResponseDto, ExternalCallbackRequestDto, IdentityDTO,
+            },
+        },
+        base::exports::request_info::RequestInfoExtractor,
+    },
+};
+use axum::{Json, extract::Path, http::StatusCode};
+use serde_json::{Value, json};
+
+#[axum::debug_handler()]
+async fn list_providers(auth_services: AuthenticationServiceGuard) -> (StatusCode, Json<Value>) {
+    error_return!(let external_login_service = auth_services.external_login_service());
+
+    (
+        StatusCode::OK,
+        Json(json!({"providers": external_login_service.get_providers()})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn authorize(
+    auth_services: AuthenticationServiceGuard,
+    _: NotAuthenticatedGuard,
+    Path(provider): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let external_login_service = auth_services.external_login_service());
+    error_return!(let redirect_to = external_login_service.start(&provider).await);
+
+    (
+        StatusCode::OK,
+        Json(json!(ExternalAuthorizationResponseDto { redirect_to })),
+    )
+}
+
+#[axum::debug_handler()]
+async fn callback(
+    request_info: RequestInfoExtractor,
+    auth_services: AuthenticationServiceGuard,
+    _: NotAuthenticatedGuard,
+    Path(provider): Path<String>,
+    Json(dto): Json<ExternalCallbackRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let (
+        authentication_service,
+        account_service,
+        password_service,
+        session_service,
+        token_service,
+    ) = auth_services.authentication_service_with_deps());
+    error_return!(let mfa_service = auth_services.mfa_service());
+    error_return!(let external_login_service = auth_services.external_login_service());
+
+    error_return!(let account = external_login_service
+        .finish(&account_service, &password_service, &provider, dto)
+        .await);
+    error_return!(let auth_response = authentication_service
+        .complete_sign_in(
+            &account_service,
+            &session_service,
+            &token_service,
+            &mfa_service,
+            request_info,
+            account,
+        )
+        .await);
+
+    (StatusCode::OK, Json(json!(auth_response)))
+}
+
+#[axum::debug_handler()]
+async fn list_identities(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let external_login_service = auth_services.external_login_service());
+    error_return!(let identities = external_login_service
+        .get_identities_for_account(&account_session.account.id)
+        .await);
+
+    let identity_dtos: Vec<IdentityDTO> = identities.into_iter().map(IdentityDTO::from).collect();
+
+    (StatusCode::OK, Json(json!({"identities": identity_dtos})))
+}
+
+pub fn routes() -> axum::Router {
+    axum::Router::new()
+        .route("/providers", axum::routing::get(list_providers))
+        .route("/identities", axum::routing::get(list_identities))
+        .route("/{provider}/authorize", axum::routing::post(authorize))
+        .route("/{provider}/callback", axum::routing::post(callback))
+}
@@ -0,0 +1,144 @@
+use crate::{
+    error_return,
+    modules::{
+        authentication::{
+            auth_services::AuthenticationServiceGuard,
+            auth_state::{AuthenticatedGuard, NotAuthenticatedGuard},
+            dtos::mfa::{MfaVerifyRequestDto, TotpCodeRequestDto},
+        },
+        base::exports::request_info::RequestInfoExtractor,
+    },
+};
+use axum::{
+    Json,
+    http::{HeaderMap, StatusCode},
+};
+use serde_json::{Value, json};
+
+use super::error_response;
+
+#[axum::debug_handler()]
+async fn begin_totp_enrollment(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+) -> (StatusCode, Json<Value>) {
+    error_return!(account_session.forbid_impersonation());
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(let mfa_service = auth_services.mfa_service());
+    error_return!(let enrollment = mfa_service
+        .begin_totp_enrollment(&account_service, &account_session.account)
+        .await);
+
+    (StatusCode::OK, Json(json!(enrollment)))
+}
+
+#[axum::debug_handler()]
+async fn confirm_totp_enrollment(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+    Json(dto): Json<TotpCodeRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(account_session.forbid_impersonation());
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(let mfa_service = auth_services.mfa_service());
+    error_return!(let recovery_codes = mfa_service
+        .confirm_totp_enrollment(&account_service, &account_session.account, &dto.code)
+        .await);
+
+    (StatusCode::OK, Json(json!(recovery_codes)))
+}
+
+#[axum::debug_handler()]
+async fn disable_totp(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+    Json(dto): Json<TotpCodeRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(account_session.forbid_impersonation());
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(let mfa_service = auth_services.mfa_service());
+    error_return!(
+        mfa_service
+            .disable_totp(&account_service, &account_session.account, &dto.code)
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Two-factor authentication disabled successfully"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn verify_challenge(
+    request_info: RequestInfoExtractor,
+    auth_services: AuthenticationServiceGuard,
+    _: NotAuthenticatedGuard,
+    Json(dto): Json<MfaVerifyRequestDto>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let services = auth_services
+        .authentication_service_with_deps()
+        .and_then(|deps| {
+            Ok((
+                deps,
+                auth_services.mfa_service()?,
+                auth_services.cookie_service()?,
+            ))
+        });
+    let (
+        (
+            authentication_service,
+            account_service,
+            _password_service,
+            session_service,
+            token_service,
+        ),
+        mfa_service,
+        cookie_service,
+    ) = match services {
+        Ok(services) => services,
+        Err(e) => return error_response(e),
+    };
+
+    let use_cookies = dto.use_cookies;
+    if let Err(e) = cookie_service.ensure_available(use_cookies) {
+        return error_response(e);
+    }
+
+    let sign_in_response = match mfa_service
+        .verify_challenge(&account_service, &token_service, dto)
+        .await
+    {
+        Ok(account) => {
+            authentication_service
+                .finish_sign_in(
+                    &account_service,
+                    &session_service,
+                    &token_service,
+                    request_info,
+                    account,
+                )
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    let response = sign_in_response.and_then(|sign_in_response| {
+        cookie_service.sign_in_response(sign_in_response, use_cookies)
+    });
+
+    match response {
+        Ok((headers, body)) => (StatusCode::OK, headers, Json(body)),
+        Err(e) => error_response(e),
+    }
+}
+
+pub fn routes() -> axum::Router {
+    axum::Router::new()
+        .route("/totp/enroll", axum::routing::post(begin_totp_enrollment))
+        .route(
+            "/totp/confirm",
+            axum::routing::post(confirm_totp_enrollment),
+        )
+        .route("/totp/disable", axum::routing::post(disable_totp))
+        .route("/verify", axum::routing::post(verify_challenge))
+}
@@ -1,10 +1,55 @@
+use crate::modules::authentication::errors::service::AuthenticationServiceError;
+use axum::{
+    Json,
+    http::{HeaderMap, StatusCode},
+};
+use serde_json::{Value, json};
+
 mod account;
 mod authentication;
+mod email;
+mod external;
+mod mfa;
+mod oauth;
+mod oidc;
+mod role;
+mod service_account;
 mod session;
+mod signing_key;
+mod webauthn;
+
+/// The `error_return!` mapping for handlers that also return headers, such as session cookies.
+fn error_response(e: AuthenticationServiceError) -> (StatusCode, HeaderMap, Json<Value>) {
+    let status = if e.is_client_error() {
+        tracing::debug!("Client error: {:?}", e);
+        StatusCode::BAD_REQUEST
+    } else {
+        tracing::error!("Error: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    (
+        status,
+        HeaderMap::new(),
+        Json(json!({"error": format!("{}", e)})),
+    )
+}
 
 pub fn routes() -> axum::Router {
     axum::Router::new()
-        .nest("/auth", authentication::routes())
+        .nest(
+            "/auth",
+            authentication::routes()
+                .nest("/email", email::routes())
+                .nest("/external", external::routes())
+                .nest("/mfa", mfa::routes())
+                .nest("/webauthn", webauthn::routes()),
+        )
         .nest("/account", account::routes())
         .nest("/session", session::routes())
+        .nest("/role", role::routes())
+        .nest("/oauth", oauth::routes())
+        .nest("/service-account", service_account::routes())
+        .nest("/signing-key", signing_key::routes())
+        .nest("/.well-known", oidc::routes())
 }
@@ -0,0 +1,583 @@
+use crate::{
+    common::model::DatabaseModel,
+    error_return,
+    modules::{
+        authentication::{
+            auth_services::AuthenticationServiceGuard,
+            auth_state::AuthenticatedGuard,
+            dtos::oauth::{
+                AuthorizationResponseDto, AuthorizeRequestDto, CreateOAuthClientRequestDto,
+                IntrospectionRequestDto, OAuthClientDTO, OAuthClientSecretDto,
+                RevocationRequestDto, TokenRequestDto, TokenResponseDto,
+                UpdateOAuthClientRequestDto,
+            },
+            errors::service::*,
+            models::{oauth_client::OAuthClientModel, service_account::ServiceAccountModel},
+            permission::{
+                OAuthClientsRead, OAuthClientsWrite, RequirePermission, TokensIntrospect,
+            },
+            services::token::FIRST_PARTY_AUDIENCE,
+        },
+        base::exports::request_info::RequestInfoExtractor,
+    },
+};
+use axum::{
+    Form, Json,
+    extract::{Path, Query},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde_json::{Value, json};
+
+const GRANT_TYPE_AUTHORIZATION_CODE: &str = "authorization_code";
+const GRANT_TYPE_REFRESH_TOKEN: &str = "refresh_token";
+const GRANT_TYPE_CLIENT_CREDENTIALS: &str = "client_credentials";
+
+type TokenEndpointResponse = (
+    StatusCode,
+    [(header::HeaderName, HeaderValue); 1],
+    Json<Value>,
+);
+
+fn token_endpoint_response(status: StatusCode, body: Value) -> TokenEndpointResponse {
+    (
+        status,
+        [(header::CACHE_CONTROL, HeaderValue::from_static("no-store"))],
+        Json(body),
+    )
+}
+
+/// Token endpoint errors use the format from RFC 6749 section 5.2.
+fn token_endpoint_error(e: AuthenticationServiceError) -> TokenEndpointResponse {
+    match e {
+        AuthenticationServiceError::ClientError(client_error) => {
+            tracing::debug!("OAuth client error: {:?}", client_error);
+            let error_code = client_error.oauth_error_code();
+            let status = if error_code == "invalid_client" {
+                StatusCode::UNAUTHORIZED
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            token_endpoint_response(
+                status,
+                json!({"error": error_code, "error_description": client_error.to_string()}),
+            )
+        }
+        AuthenticationServiceError::ServerError(e) => {
+            tracing::error!("Error: {:?}", e);
+            token_endpoint_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({"error": "server_error"}),
+            )
+        }
+    }
+}
+
+/// Reads client credentials from HTTP Basic authentication or from the request body.
+fn client_credentials(
+    headers: &HeaderMap,
+    client_id: Option<&str>,
+    client_secret: Option<&str>,
+) -> Option<(String, Option<String>)> {
+    let basic_credentials = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Basic "))
+        .and_then(|value| STANDARD.decode(value.trim()).ok())
+        .and_then(|value| String::from_utf8(value).ok())
+        .and_then(|value| {
+            value
+                .split_once(':')
+                .map(|(id, secret)| (id.to_string(), Some(secret.to_string())))
+        });
+
+    basic_credentials.or_else(|| {
+        client_id.map(|client_id| (client_id.to_string(), client_secret.map(String::from)))
+    })
+}
+
+#[axum::debug_handler()]
+async fn get_authorization_details(
+    auth_services: AuthenticationServiceGuard,
+    Query(dto): Query<AuthorizeRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let oauth_service = auth_services.oauth_service());
+    error_return!(let details = oauth_service.get_authorization_details(&dto).await);
+
+    (StatusCode::OK, Json(json!(details)))
+}
+
+#[axum::debug_handler()]
+async fn authorize(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+    Json(dto): Json<AuthorizeRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    // Issued tokens would outlive the impersonation and carry no trace of the impersonator
+    error_return!(account_session.forbid_impersonation());
+    error_return!(let oauth_service = auth_services.oauth_service());
+    error_return!(let redirect_to = oauth_service.authorize(&account_session.account, dto).await);
+
+    (
+        StatusCode::OK,
+        Json(json!(AuthorizationResponseDto { redirect_to })),
+    )
+}
+
+#[axum::debug_handler()]
+async fn token(
+    request_info: RequestInfoExtractor,
+    auth_services: AuthenticationServiceGuard,
+    headers: HeaderMap,
+    Form(dto): Form<TokenRequestDto>,
+) -> TokenEndpointResponse {
+    let services = auth_services.oauth_service().and_then(|oauth_service| {
+        let (session_service, token_service) = auth_services.session_service_with_deps()?;
+        let account_service = auth_services.account_service()?;
+        let oidc_service = auth_services.oidc_service()?;
+
+        Ok((
+            oauth_service,
+            account_service,
+            oidc_service,
+            session_service,
+            token_service,
+        ))
+    });
+    let (oauth_service, account_service, oidc_service, session_service, token_service) =
+        match services {
+            Ok(services) => services,
+            Err(e) => return token_endpoint_error(e),
+        };
+
+    let Some((client_id, client_secret)) = client_credentials(
+        &headers,
+        dto.client_id.as_deref(),
+        dto.client_secret.as_deref(),
+    ) else {
+        return token_endpoint_error(AuthenticationServiceError::client(
+            AuthenticationClientError::InvalidClient,
+        ));
+    };
+
+    // Client credentials are issued to service accounts, not to registered OAuth clients
+    if dto.grant_type == GRANT_TYPE_CLIENT_CREDENTIALS {
+        let token_response = match auth_services.service_account_service() {
+            Ok(service_account_service) => match client_secret.as_deref() {
+                Some(client_secret) => match service_account_service
+                    .authenticate(&client_id, client_secret)
+                    .await
+                {
+                    Ok(service_account) => service_account_service.issue_token(
+                        &token_service,
+                        &service_account,
+                        dto.scope.as_deref(),
+                    ),
+                    Err(e) => Err(e),
+                },
+                None => Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::InvalidClient,
+                )),
+            },
+            Err(e) => Err(e),
+        };
+
+        return match token_response {
+            Ok(token_response) => token_endpoint_response(StatusCode::OK, json!(token_response)),
+            Err(e) => token_endpoint_error(e),
+        };
+    }
+
+    let client = match oauth_service
+        .authenticate_client(&client_id, client_secret.as_deref())
+        .await
+    {
+        Ok(client) => client,
+        Err(e) => return token_endpoint_error(e),
+    };
+
+    let token_response: Result<TokenResponseDto, AuthenticationServiceError> =
+        match dto.grant_type.as_str() {
+            GRANT_TYPE_AUTHORIZATION_CODE => match dto.code.as_deref() {
+                Some(code) => {
+                    oauth_service
+                        .exchange_authorization_code(
+                            &account_service,
+                            &oidc_service,
+                            &session_service,
+                            &token_service,
+                            request_info,
+                            &client,
+                            code,
+                            dto.redirect_uri.as_deref(),
+                            dto.code_verifier.as_deref(),
+                        )
+                        .await
+                }
+                None => Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::InvalidOAuthRequest("code is required".to_string()),
+                )),
+            },
+            GRANT_TYPE_REFRESH_TOKEN => match dto.refresh_token.as_deref() {
+                Some(refresh_token) => {
+                    oauth_service
+                        .refresh(
+                            &account_service,
+                            &oidc_service,
+                            &session_service,
+                            &token_service,
+                            request_info,
+                            &client,
+                            refresh_token,
+                        )
+                        .await
+                }
+                None => Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::InvalidOAuthRequest(
+                        "refresh_token is required".to_string(),
+                    ),
+                )),
+            },
+            _ => Err(AuthenticationServiceError::client(
+                AuthenticationClientError::UnsupportedGrantType,
+            )),
+        };
+
+    match token_response {
+        Ok(token_response) => token_endpoint_response(StatusCode::OK, json!(token_response)),
+        Err(e) => token_endpoint_error(e),
+    }
+}
+
+#[axum::debug_handler()]
+async fn introspect(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<TokensIntrospect>,
+    Form(dto): Form<IntrospectionRequestDto>,
+) -> TokenEndpointResponse {
+    let services = auth_services.oauth_service().and_then(|oauth_service| {
+        let (session_service, token_service) = auth_services.session_service_with_deps()?;
+        let service_account_service = auth_services.service_account_service()?;
+        let revocation_service = auth_services.revocation_service()?;
+
+        Ok((
+            oauth_service,
+            session_service,
+            service_account_service,
+            revocation_service,
+            token_service,
+        ))
+    });
+    let (
+        oauth_service,
+        session_service,
+        service_account_service,
+        revocation_service,
+        token_service,
+    ) = match services {
+        Ok(services) => services,
+        Err(e) => return token_endpoint_error(e),
+    };
+
+    match oauth_service
+        .introspect(
+            &session_service,
+            &service_account_service,
+            &revocation_service,
+            &token_service,
+            &dto.token,
+            dto.token_type_hint.as_deref(),
+        )
+        .await
+    {
+        Ok(introspection) => token_endpoint_response(StatusCode::OK, json!(introspection)),
+        Err(e) => token_endpoint_error(e),
+    }
+}
+
+/// Resolves whose tokens the caller may revoke, public first party callers prove possession of
+/// the token alone.
+async fn revocation_client_id(
+    auth_services: &AuthenticationServiceGuard,
+    headers: &HeaderMap,
+    dto: &RevocationRequestDto,
+) -> Result<String, AuthenticationServiceError> {
+    let Some((client_id, client_secret)) = client_credentials(
+        headers,
+        dto.client_id.as_deref(),
+        dto.client_secret.as_deref(),
+    ) else {
+        return Ok(FIRST_PARTY_AUDIENCE.to_string());
+    };
+
+    if ServiceAccountModel::from_named_format(&client_id).is_some() {
+        let service_account_service = auth_services.service_account_service()?;
+        let service_account = service_account_service
+            .authenticate(
+                &client_id,
+                client_secret
+                    .as_deref()
+                    .ok_or(AuthenticationServiceError::client(
+                        AuthenticationClientError::InvalidClient,
+                    ))?,
+            )
+            .await?;
+
+        return Ok(ServiceAccountModel::to_named_format(&service_account.id));
+    }
+
+    let oauth_service = auth_services.oauth_service()?;
+    let client = oauth_service
+        .authenticate_client(&client_id, client_secret.as_deref())
+        .await?;
+
+    Ok(OAuthClientModel::to_named_format(&client.id))
+}
+
+#[axum::debug_handler()]
+async fn revoke(
+    auth_services: AuthenticationServiceGuard,
+    headers: HeaderMap,
+    Form(dto): Form<RevocationRequestDto>,
+) -> TokenEndpointResponse {
+    let client_id = match revocation_client_id(&auth_services, &headers, &dto).await {
+        Ok(client_id) => client_id,
+        Err(e) => return token_endpoint_error(e),
+    };
+
+    let services = auth_services.oauth_service().and_then(|oauth_service| {
+        let (session_service, token_service) = auth_services.session_service_with_deps()?;
+        let revocation_service = auth_services.revocation_service()?;
+
+        Ok((
+            oauth_service,
+            session_service,
+            revocation_service,
+            token_service,
+        ))
+    });
+    let (oauth_service, session_service, revocation_service, token_service) = match services {
+        Ok(services) => services,
+        Err(e) => return token_endpoint_error(e),
+    };
+
+    match oauth_service
+        .revoke(
+            &session_service,
+            &revocation_service,
+            &token_service,
+            &client_id,
+            &dto.token,
+            dto.token_type_hint.as_deref(),
+        )
+        .await
+    {
+        Ok(()) => token_endpoint_response(StatusCode::OK, json!({})),
+        Err(e) => token_endpoint_error(e),
+    }
+}
+
+/// Userinfo errors are reported through `WWW-Authenticate` as described in RFC 6750 section 3.
+fn userinfo_error(e: AuthenticationServiceError) -> (StatusCode, HeaderMap, Json<Value>) {
+    let mut headers = HeaderMap::new();
+    match e {
+        AuthenticationServiceError::ClientError(client_error) => {
+            tracing::debug!("Userinfo client error: {:?}", client_error);
+            let (status, error_code) = match client_error {
+                AuthenticationClientError::InvalidScope => {
+                    (StatusCode::FORBIDDEN, "insufficient_scope")
+                }
+                _ => (StatusCode::UNAUTHORIZED, "invalid_token"),
+            };
+
+            if let Ok(value) = HeaderValue::from_str(&format!("Bearer error=\"{}\"", error_code)) {
+                headers.insert(header::WWW_AUTHENTICATE, value);
+            }
+
+            (status, headers, Json(json!({"error": error_code})))
+        }
+        AuthenticationServiceError::ServerError(e) => {
+            tracing::error!("Error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                headers,
+                Json(json!({"error": "server_error"})),
+            )
+        }
+    }
+}
+
+#[axum::debug_handler()]
+async fn userinfo(
+    auth_services: AuthenticationServiceGuard,
+    headers: HeaderMap,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let Some(access_token) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+    else {
+        return userinfo_error(AuthenticationServiceError::client(
+            AuthenticationClientError::InvalidAccessToken,
+        ));
+    };
+
+    let services = auth_services.oidc_service().and_then(|oidc_service| {
+        let (session_service, token_service) = auth_services.session_service_with_deps()?;
+        let account_service = auth_services.account_service()?;
+
+        Ok((
+            oidc_service,
+            account_service,
+            session_service,
+            token_service,
+        ))
+    });
+    let (oidc_service, account_service, session_service, token_service) = match services {
+        Ok(services) => services,
+        Err(e) => return userinfo_error(e),
+    };
+
+    match oidc_service
+        .userinfo(
+            &account_service,
+            &session_service,
+            &token_service,
+            access_token,
+        )
+        .await
+    {
+        Ok(claims) => (
+            StatusCode::OK,
+            HeaderMap::new(),
+            Json(Value::Object(claims)),
+        ),
+        Err(e) => userinfo_error(e),
+    }
+}
+
+#[axum::debug_handler()]
+async fn list_clients(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<OAuthClientsRead>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let oauth_service = auth_services.oauth_service());
+    error_return!(let clients = oauth_service.get_all_clients().await);
+
+    let client_dtos: Vec<OAuthClientDTO> = clients.into_iter().map(OAuthClientDTO::from).collect();
+
+    (StatusCode::OK, Json(json!({"clients": client_dtos})))
+}
+
+#[axum::debug_handler()]
+async fn create_client(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<OAuthClientsWrite>,
+    Json(dto): Json<CreateOAuthClientRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let oauth_service = auth_services.oauth_service());
+    error_return!(let (client, client_secret) = oauth_service.create_client(dto).await);
+
+    (
+        StatusCode::CREATED,
+        Json(json!(OAuthClientSecretDto {
+            client: OAuthClientDTO::from(client),
+            client_secret,
+        })),
+    )
+}
+
+#[axum::debug_handler()]
+async fn get_client(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<OAuthClientsRead>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let oauth_service = auth_services.oauth_service());
+    error_return!(let client = oauth_service.get_client_by_client_id(&id).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"client": OAuthClientDTO::from(client)})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn update_client(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<OAuthClientsWrite>,
+    Path(id): Path<String>,
+    Json(dto): Json<UpdateOAuthClientRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let client_id = OAuthClientModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidOAuthClientId)));
+    error_return!(let oauth_service = auth_services.oauth_service());
+    error_return!(let client = oauth_service.update_client(&client_id, dto).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"client": OAuthClientDTO::from(client)})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn rotate_client_secret(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<OAuthClientsWrite>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let client_id = OAuthClientModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidOAuthClientId)));
+    error_return!(let oauth_service = auth_services.oauth_service());
+    error_return!(let (client, client_secret) = oauth_service.rotate_client_secret(&client_id).await);
+
+    (
+        StatusCode::OK,
+        Json(json!(OAuthClientSecretDto {
+            client: OAuthClientDTO::from(client),
+            client_secret: Some(client_secret),
+        })),
+    )
+}
+
+#[axum::debug_handler()]
+async fn delete_client(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<OAuthClientsWrite>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let client_id = OAuthClientModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidOAuthClientId)));
+    error_return!(let oauth_service = auth_services.oauth_service());
+    error_return!(oauth_service.delete_client(&client_id).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "OAuth client deleted successfully"})),
+    )
+}
+
+pub fn routes() -> axum::Router {
+    axum::Router::new()
+        .route(
+            "/authorize",
+            axum::routing::get(get_authorization_details).post(authorize),
+        )
+        .route("/token", axum::routing::post(token))
+        .route("/introspect", axum::routing::post(introspect))
+        .route("/revoke", axum::routing::post(revoke))
+        .route("/userinfo", axum::routing::get(userinfo).post(userinfo))
+        .route(
+            "/clients",
+            axum::routing::get(list_clients).post(create_client),
+        )
+        .route(
+            "/clients/{id}",
+            axum::routing::get(get_client)
+                .patch(update_client)
+                .delete(delete_client),
+        )
+        .route(
+            "/clients/{id}/secret",
+            axum::routing::post(rotate_client_secret),
+        )
+}
@@ -0,0 +1,32 @@
+use crate::{error_return, modules::authentication::auth_services::AuthenticationServiceGuard};
+use axum::{Json, http::StatusCode};
+use serde_json::{Value, json};
+
+#[axum::debug_handler()]
+async fn openid_configuration(
+    auth_services: AuthenticationServiceGuard,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let oidc_service = auth_services.oidc_service());
+    error_return!(let token_service = auth_services.token_service());
+
+    (
+        StatusCode::OK,
+        Json(json!(oidc_service.discovery_document(&token_service))),
+    )
+}
+
+#[axum::debug_handler()]
+async fn jwks(auth_services: AuthenticationServiceGuard) -> (StatusCode, Json<Value>) {
+    error_return!(let token_service = auth_services.token_service());
+
+    (StatusCode::OK, Json(json!(token_service.jwks())))
+}
+
+pub fn routes() -> axum::Router {
+    axum::Router::new()
+        .route(
+            "/openid-configuration",
+            axum::routing::get(openid_configuration),
+        )
+        .route("/jwks.json", axum::routing::get(jwks))
+}
@@ -0,0 +1,190 @@
+use crate::{
+    common::model::DatabaseModel,
+    error_return,
+    modules::authentication::{
+        account_model::AccountModel,
+        auth_services::AuthenticationServiceGuard,
+        auth_state::SessionOrApiKeyGuard,
+        errors::service::*,
+        permission::{RequirePermission, RolesRead, RolesWrite},
+        role_dto::*,
+        role_model::RoleModel,
+        services::role::RoleService,
+    },
+};
+use axum::{Json, extract::Path, http::StatusCode};
+use serde_json::{Value, json};
+
+#[axum::debug_handler()]
+async fn self_get_roles(
+    auth_services: AuthenticationServiceGuard,
+    SessionOrApiKeyGuard(account_session): SessionOrApiKeyGuard,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(let roles = role_service.get_roles_for_account(&account_session.account_id).await);
+    error_return!(let permissions = role_service.get_permissions_for_account(&account_session.account_id).await);
+
+    let dto = AccountRolesDTO {
+        account_id: AccountModel::to_named_format(&account_session.account_id),
+        roles: roles.into_iter().map(RoleDTO::from).collect(),
+        permissions,
+    };
+
+    (StatusCode::OK, Json(json!(dto)))
+}
+
+#[axum::debug_handler()]
+async fn list_all_roles(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<RolesRead>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(let roles = role_service.get_all_roles().await);
+
+    let role_dtos: Vec<RoleDTO> = roles.into_iter().map(RoleDTO::from).collect();
+
+    (StatusCode::OK, Json(json!({"roles": role_dtos})))
+}
+
+#[axum::debug_handler()]
+async fn create_role(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<RolesWrite>,
+    Json(dto): Json<CreateRoleRequestDTO>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(let role = role_service.create_role(dto).await);
+
+    (
+        StatusCode::CREATED,
+        Json(json!({"role": RoleDTO::from(role)})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn get_role_by_id(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<RolesRead>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let role_id = RoleModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidRoleId)));
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(let role = role_service.get_role_by_id(&role_id).await);
+
+    (StatusCode::OK, Json(json!({"role": RoleDTO::from(role)})))
+}
+
+#[axum::debug_handler()]
+async fn update_role_by_id(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<RolesWrite>,
+    Path(id): Path<String>,
+    Json(dto): Json<UpdateRoleRequestDTO>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let role_id = RoleModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidRoleId)));
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(let role = role_service.update_role(&role_id, dto).await);
+
+    (StatusCode::OK, Json(json!({"role": RoleDTO::from(role)})))
+}
+
+#[axum::debug_handler()]
+async fn delete_role_by_id(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<RolesWrite>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let role_id = RoleModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidRoleId)));
+    if role_id == RoleService::admin_role_id() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "The admin role cannot be deleted"})),
+        );
+    }
+
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(role_service.delete_role(&role_id).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Role deleted successfully"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn list_roles_for_account(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<RolesRead>,
+    Path(account_id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_id = AccountModel::from_named_format(&account_id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidAccountId)));
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(let roles = role_service.get_roles_for_account(&account_id).await);
+    error_return!(let permissions = role_service.get_permissions_for_account(&account_id).await);
+
+    let dto = AccountRolesDTO {
+        account_id: AccountModel::to_named_format(&account_id),
+        roles: roles.into_iter().map(RoleDTO::from).collect(),
+        permissions,
+    };
+
+    (StatusCode::OK, Json(json!(dto)))
+}
+
+#[axum::debug_handler()]
+async fn assign_role_to_account(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<RolesWrite>,
+    Path((account_id, role_id)): Path<(String, String)>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_id = AccountModel::from_named_format(&account_id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidAccountId)));
+    error_return!(let role_id = RoleModel::from_named_format(&role_id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidRoleId)));
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(account_service.get_account_by_id(&account_id).await);
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(role_service.assign_role(&account_id, &role_id).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Role assigned successfully"})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn unassign_role_from_account(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<RolesWrite>,
+    Path((account_id, role_id)): Path<(String, String)>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_id = AccountModel::from_named_format(&account_id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidAccountId)));
+    error_return!(let role_id = RoleModel::from_named_format(&role_id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidRoleId)));
+    error_return!(let role_service = auth_services.role_service());
+    error_return!(role_service.unassign_role(&account_id, &role_id).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Role unassigned successfully"})),
+    )
+}
+
+pub fn routes() -> axum::Router {
+    axum::Router::new()
+        .route("/self", axum::routing::get(self_get_roles))
+        .route("/all", axum::routing::get(list_all_roles))
+        .route("/", axum::routing::post(create_role))
+        .route("/{id}", axum::routing::get(get_role_by_id))
+        .route("/{id}", axum::routing::patch(update_role_by_id))
+        .route("/{id}", axum::routing::delete(delete_role_by_id))
+        .route(
+            "/account/{account_id}",
+            axum::routing::get(list_roles_for_account),
+        )
+        .route(
+            "/account/{account_id}/{role_id}",
+            axum::routing::put(assign_role_to_account),
+        )
+        .route(
+            "/account/{account_id}/{role_id}",
+            axum::routing::delete(unassign_role_from_account),
+        )
+}
@@ -0,0 +1,160 @@
+use crate::{
+    common::model::DatabaseModel,
+    error_return,
+    modules::authentication::{
+        auth_services::AuthenticationServiceGuard,
+        dtos::service_account::{
+            CreateServiceAccountRequestDto, ServiceAccountDTO, ServiceAccountSecretDto,
+            UpdateServiceAccountRequestDto,
+        },
+        errors::service::*,
+        models::service_account::ServiceAccountModel,
+        permission::{RequirePermission, ServiceAccountsRead, ServiceAccountsWrite},
+    },
+};
+use axum::{Json, extract::Path, http::StatusCode};
+use serde_json::{Value, json};
+
+/// Service accounts must not be able to mint credentials for other service accounts.
+fn require_human(
+    permission: &RequirePermission<ServiceAccountsWrite>,
+) -> Result<(), AuthenticationServiceError> {
+    if !permission.auth.is_human() {
+        return Err(AuthenticationServiceError::client(
+            AuthenticationClientError::PermissionDenied,
+        ));
+    }
+
+    Ok(())
+}
+
+#[axum::debug_handler()]
+async fn list_service_accounts(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<ServiceAccountsRead>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let service_account_service = auth_services.service_account_service());
+    error_return!(let service_accounts = service_account_service.get_all_service_accounts().await);
+
+    let service_account_dtos: Vec<ServiceAccountDTO> = service_accounts
+        .into_iter()
+        .map(ServiceAccountDTO::from)
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(json!({"service_accounts": service_account_dtos})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn create_service_account(
+    auth_services: AuthenticationServiceGuard,
+    permission: RequirePermission<ServiceAccountsWrite>,
+    Json(dto): Json<CreateServiceAccountRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(require_human(&permission));
+    error_return!(let service_account_service = auth_services.service_account_service());
+    error_return!(let (service_account, client_secret) = service_account_service.create_service_account(dto).await);
+
+    (
+        StatusCode::CREATED,
+        Json(json!(ServiceAccountSecretDto {
+            service_account: ServiceAccountDTO::from(service_account),
+            client_secret,
+        })),
+    )
+}
+
+#[axum::debug_handler()]
+async fn get_service_account(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<ServiceAccountsRead>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let service_account_id = ServiceAccountModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidServiceAccountId)));
+    error_return!(let service_account_service = auth_services.service_account_service());
+    error_return!(let service_account = service_account_service.get_service_account_by_id(&service_account_id).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"service_account": ServiceAccountDTO::from(service_account)})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn update_service_account(
+    auth_services: AuthenticationServiceGuard,
+    permission: RequirePermission<ServiceAccountsWrite>,
+    Path(id): Path<String>,
+    Json(dto): Json<UpdateServiceAccountRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(require_human(&permission));
+    error_return!(let service_account_id = ServiceAccountModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidServiceAccountId)));
+    error_return!(let service_account_service = auth_services.service_account_service());
+    error_return!(let service_account = service_account_service.update_service_account(&service_account_id, dto).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"service_account": ServiceAccountDTO::from(service_account)})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn rotate_service_account_secret(
+    auth_services: AuthenticationServiceGuard,
+    permission: RequirePermission<ServiceAccountsWrite>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(require_human(&permission));
+    error_return!(let service_account_id = ServiceAccountModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidServiceAccountId)));
+    error_return!(let service_account_service = auth_services.service_account_service());
+    error_return!(let (service_account, client_secret) = service_account_service.rotate_secret(&service_account_id).await);
+
+    (
+        StatusCode::OK,
+        Json(json!(ServiceAccountSecretDto {
+            service_account: ServiceAccountDTO::from(service_account),
+            client_secret,
+        })),
+    )
+}
+
+#[axum::debug_handler()]
+async fn delete_service_account(
+    auth_services: AuthenticationServiceGuard,
+    permission: RequirePermission<ServiceAccountsWrite>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(require_human(&permission));
+    error_return!(let service_account_id = ServiceAccountModel::from_named_format(&id).ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidServiceAccountId)));
+    error_return!(let service_account_service = auth_services.service_account_service());
+    error_return!(
+        service_account_service
+            .delete_service_account(&service_account_id)
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Service account deleted successfully"})),
+    )
+}
+
+pub fn routes() -> axum::Router {
+    axum::Router::new()
+        .route(
+            "/",
+            axum::routing::get(list_service_accounts).post(create_service_account),
+        )
+        .route(
+            "/{id}",
+            axum::routing::get(get_service_account)
+                .patch(update_service_account)
+                .delete(delete_service_account),
+        )
+        .route(
+            "/{id}/secret",
+            axum::routing::post(rotate_service_account_secret),
+        )
+}
@@ -5,29 +5,54 @@ use crate::{
         account_model::AccountModel,
         auth_services::AuthenticationServiceGuard,
         auth_state::{AuthenticatedGuard, RefreshTokenGuard},
+        dtos::session::RenameSessionRequestDto,
         errors::service::*,
+        permission::{RequirePermission, SessionsRead, SessionsWrite},
+        services::token::FIRST_PARTY_AUDIENCE,
         session_dto::SessionDTO,
         session_model::SessionModel,
     },
+    modules::base::exports::request_info::RequestInfoExtractor,
+};
+use axum::{
+    Json,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
 };
-use axum::{Json, extract::Path, http::StatusCode};
 use serde_json::{Value, json};
 
+use super::error_response;
+
 #[axum::debug_handler()]
 async fn refresh_session(
+    request_info: RequestInfoExtractor,
     auth_services: AuthenticationServiceGuard,
     refresh: RefreshTokenGuard,
-) -> (StatusCode, Json<Value>) {
-    error_return!(let (session_service, token_service) = auth_services.session_service_with_deps());
-    error_return!(let auth_response = session_service
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let services = auth_services
+        .session_service_with_deps()
+        .and_then(|deps| Ok((deps, auth_services.cookie_service()?)));
+    let ((session_service, token_service), cookie_service) = match services {
+        Ok(services) => services,
+        Err(e) => return error_response(e),
+    };
+
+    let response = session_service
         .refresh_session(
             &token_service,
             refresh.refresh_token_hash,
-            "core-auth".to_string(),
+            FIRST_PARTY_AUDIENCE.to_string(),
+            &request_info,
         )
-        .await);
+        .await
+        .and_then(|auth_response| {
+            cookie_service.session_response(auth_response, refresh.from_cookie)
+        });
 
-    (StatusCode::OK, Json(json!(auth_response)))
+    match response {
+        Ok((headers, body)) => (StatusCode::OK, headers, Json(body)),
+        Err(e) => error_response(e),
+    }
 }
 
 #[axum::debug_handler()]
@@ -35,6 +60,8 @@ async fn self_list_sessions(
     auth_services: AuthenticationServiceGuard,
     account_session: AuthenticatedGuard,
 ) -> (StatusCode, Json<Value>) {
+    // API keys act within their scopes, managing sessions needs an interactive session
+    error_return!(account_session.require_session());
     let account_id = account_session.account_id;
     error_return!(let (session_service, _token_service) = auth_services.session_service_with_deps());
 
@@ -55,6 +82,9 @@ async fn self_revoke_all_sessions(
     account_session: AuthenticatedGuard,
     auth_services: AuthenticationServiceGuard,
 ) -> (StatusCode, Json<Value>) {
+    // API keys act within their scopes, managing sessions needs an interactive session
+    error_return!(account_session.require_session());
+    error_return!(account_session.forbid_impersonation());
     error_return!(let (session_service, _token_service) = auth_services.session_service_with_deps());
     let account_id = account_session.account_id;
 
@@ -72,16 +102,23 @@ async fn self_revoke_all_sessions(
 
 #[axum::debug_handler()]
 async fn self_revoke_session(
-    _: AuthenticatedGuard,
+    account_session: AuthenticatedGuard,
     auth_services: AuthenticationServiceGuard,
     Path(session_id): Path<String>,
 ) -> (StatusCode, Json<Value>) {
+    // API keys act within their scopes, managing sessions needs an interactive session
+    error_return!(account_session.require_session());
+    error_return!(account_session.forbid_impersonation());
     error_return!(let session_id = SessionModel::from_named_format(&session_id)
         .ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidSessionId))
     );
 
     error_return!(let (session_service, _token_service) = auth_services.session_service_with_deps());
-    error_return!(session_service.deactivate_session(&session_id).await);
+    error_return!(
+        session_service
+            .deactivate_session_for_account(&session_id, &account_session.account_id)
+            .await
+    );
 
     (
         StatusCode::OK,
@@ -89,9 +126,33 @@ async fn self_revoke_session(
     )
 }
 
+#[axum::debug_handler()]
+async fn self_rename_session(
+    account_session: AuthenticatedGuard,
+    auth_services: AuthenticationServiceGuard,
+    Path(session_id): Path<String>,
+    Json(dto): Json<RenameSessionRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    // API keys act within their scopes, managing sessions needs an interactive session
+    error_return!(account_session.require_session());
+    error_return!(let session_id = SessionModel::from_named_format(&session_id)
+        .ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidSessionId))
+    );
+
+    error_return!(let session_service = auth_services.session_service());
+    error_return!(let session = session_service
+        .rename_session_for_account(&session_id, &account_session.account_id, dto.name.as_deref())
+        .await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"session": SessionDTO::from(session)})),
+    )
+}
+
 #[axum::debug_handler()]
 async fn list_sessions_for_account(
-    _: AuthenticatedGuard,
+    _: RequirePermission<SessionsRead>,
     auth_services: AuthenticationServiceGuard,
     Path(account_id): Path<String>,
 ) -> (StatusCode, Json<Value>) {
@@ -114,7 +175,7 @@ async fn list_sessions_for_account(
 
 #[axum::debug_handler()]
 async fn revoke_all_sessions_by_account_id(
-    _: AuthenticatedGuard,
+    _: RequirePermission<SessionsWrite>,
     auth_services: AuthenticationServiceGuard,
     Path(account_id): Path<String>,
 ) -> (StatusCode, Json<Value>) {
@@ -137,7 +198,7 @@ async fn revoke_all_sessions_by_account_id(
 
 #[axum::debug_handler()]
 async fn revoke_session_for_account_by_id(
-    _: AuthenticatedGuard,
+    _: RequirePermission<SessionsWrite>,
     auth_services: AuthenticationServiceGuard,
     Path((account_id, session_id)): Path<(String, String)>,
 ) -> (StatusCode, Json<Value>) {
@@ -164,7 +225,7 @@ async fn revoke_session_for_account_by_id(
 
 #[axum::debug_handler()]
 async fn list_all_sessions(
-    _: AuthenticatedGuard,
+    _: RequirePermission<SessionsRead>,
     auth_services: AuthenticationServiceGuard,
 ) -> (StatusCode, Json<Value>) {
     error_return!(let (session_service, _token_service) = auth_services.session_service_with_deps());
@@ -186,6 +247,10 @@ pub fn routes() -> axum::Router {
             "/self/revoke/{session_id}",
             axum::routing::patch(self_revoke_session),
         )
+        .route(
+            "/self/{session_id}",
+            axum::routing::patch(self_rename_session),
+        )
         .route("/all", axum::routing::get(list_all_sessions))
         .route(
             "/{account_id}",
@@ -0,0 +1,82 @@
+use crate::{
+    error_return,
+    modules::authentication::{
+        auth_services::AuthenticationServiceGuard,
+        dtos::signing_key::{CreateSigningKeyRequestDto, SigningKeyDTO},
+        permission::{RequirePermission, SigningKeysRead, SigningKeysWrite},
+    },
+};
+use axum::{Json, extract::Path, http::StatusCode};
+use serde_json::{Value, json};
+
+#[axum::debug_handler()]
+async fn list_signing_keys(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<SigningKeysRead>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let signing_key_service = auth_services.signing_key_service());
+    error_return!(let signing_keys = signing_key_service.get_all_keys().await);
+
+    let signing_key_dtos: Vec<SigningKeyDTO> =
+        signing_keys.into_iter().map(SigningKeyDTO::from).collect();
+
+    (
+        StatusCode::OK,
+        Json(json!({"signing_keys": signing_key_dtos})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn add_signing_key(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<SigningKeysWrite>,
+    Json(dto): Json<CreateSigningKeyRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let signing_key_service = auth_services.signing_key_service());
+    error_return!(let signing_key = signing_key_service.add_key(dto).await);
+
+    (
+        StatusCode::CREATED,
+        Json(json!({"signing_key": SigningKeyDTO::from(signing_key)})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn activate_signing_key(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<SigningKeysWrite>,
+    Path(kid): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let signing_key_service = auth_services.signing_key_service());
+    error_return!(let signing_key = signing_key_service.activate_key(&kid).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"signing_key": SigningKeyDTO::from(signing_key)})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn retire_signing_key(
+    auth_services: AuthenticationServiceGuard,
+    _: RequirePermission<SigningKeysWrite>,
+    Path(kid): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let signing_key_service = auth_services.signing_key_service());
+    error_return!(let signing_key = signing_key_service.retire_key(&kid).await);
+
+    (
+        StatusCode::OK,
+        Json(json!({"signing_key": SigningKeyDTO::from(signing_key)})),
+    )
+}
+
+pub fn routes() -> axum::Router {
+    axum::Router::new()
+        .route(
+            "/",
+            axum::routing::get(list_signing_keys).post(add_signing_key),
+        )
+        .route("/{kid}/activate", axum::routing::post(activate_signing_key))
+        .route("/{kid}/retire", axum::routing::post(retire_signing_key))
+}
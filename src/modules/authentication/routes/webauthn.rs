@@ -0,0 +1,161 @@
+use crate::{
+    common::model::DatabaseModel,
+    error_return,
+    modules::{
+        authentication::{
+            auth_services::AuthenticationServiceGuard,
+            auth_state::{AuthenticatedGuard, NotAuthenticatedGuard},
+            dtos::webauthn::{
+                CredentialDTO, WebAuthnAuthenticationRequestDto,
+                WebAuthnAuthenticationStartRequestDto, WebAuthnRegistrationRequestDto,
+            },
+            errors::service::{AuthenticationClientError, AuthenticationServiceError},
+            models::credential::CredentialModel,
+        },
+        base::exports::request_info::RequestInfoExtractor,
+    },
+};
+use axum::{Json, extract::Path, http::StatusCode};
+use serde_json::{Value, json};
+
+#[axum::debug_handler()]
+async fn start_registration(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+) -> (StatusCode, Json<Value>) {
+    error_return!(account_session.forbid_impersonation());
+    error_return!(let webauthn_service = auth_services.webauthn_service());
+    error_return!(let options = webauthn_service
+        .start_registration(&account_session.account)
+        .await);
+
+    (StatusCode::OK, Json(json!(options)))
+}
+
+#[axum::debug_handler()]
+async fn finish_registration(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+    Json(dto): Json<WebAuthnRegistrationRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(account_session.forbid_impersonation());
+    error_return!(let webauthn_service = auth_services.webauthn_service());
+    error_return!(let credential = webauthn_service
+        .finish_registration(&account_session.account, dto)
+        .await);
+
+    (
+        StatusCode::CREATED,
+        Json(json!({"credential": CredentialDTO::from(credential)})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn start_authentication(
+    auth_services: AuthenticationServiceGuard,
+    _: NotAuthenticatedGuard,
+    Json(dto): Json<WebAuthnAuthenticationStartRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let account_service = auth_services.account_service());
+    error_return!(let webauthn_service = auth_services.webauthn_service());
+    error_return!(let options = webauthn_service
+        .start_authentication(&account_service, dto)
+        .await);
+
+    (StatusCode::OK, Json(json!(options)))
+}
+
+#[axum::debug_handler()]
+async fn finish_authentication(
+    request_info: RequestInfoExtractor,
+    auth_services: AuthenticationServiceGuard,
+    _: NotAuthenticatedGuard,
+    Json(dto): Json<WebAuthnAuthenticationRequestDto>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let (
+        authentication_service,
+        account_service,
+        _password_service,
+        session_service,
+        token_service,
+    ) = auth_services.authentication_service_with_deps());
+    error_return!(let webauthn_service = auth_services.webauthn_service());
+    error_return!(let account = webauthn_service
+        .finish_authentication(&account_service, dto)
+        .await);
+
+    // A forced password change or an expired password applies to passkey sign-ins as well
+    error_return!(let sign_in_response = authentication_service
+        .finish_sign_in(
+            &account_service,
+            &session_service,
+            &token_service,
+            request_info,
+            account,
+        )
+        .await);
+
+    (StatusCode::OK, Json(json!(sign_in_response)))
+}
+
+#[axum::debug_handler()]
+async fn list_credentials(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+) -> (StatusCode, Json<Value>) {
+    error_return!(let webauthn_service = auth_services.webauthn_service());
+    error_return!(let credentials = webauthn_service
+        .get_credentials_for_account(&account_session.account_id)
+        .await);
+
+    let credential_dtos: Vec<CredentialDTO> =
+        credentials.into_iter().map(CredentialDTO::from).collect();
+
+    (
+        StatusCode::OK,
+        Json(json!({"credentials": credential_dtos})),
+    )
+}
+
+#[axum::debug_handler()]
+async fn delete_credential(
+    auth_services: AuthenticationServiceGuard,
+    account_session: AuthenticatedGuard,
+    Path(credential_id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    error_return!(account_session.forbid_impersonation());
+    error_return!(let credential_id = CredentialModel::from_named_format(&credential_id)
+        .ok_or(AuthenticationServiceError::client(AuthenticationClientError::InvalidCredentialId))
+    );
+
+    error_return!(let webauthn_service = auth_services.webauthn_service());
+    error_return!(
+        webauthn_service
+            .delete_credential_for_account(&credential_id, &account_session.account_id)
+            .await
+    );
+
+    (
+        StatusCode::OK,
+        Json(json!({"message": "Credential deleted successfully"})),
+    )
+}
+
+pub fn routes() -> axum::Router {
+    axum::Router::new()
+        .route("/register/start", axum::routing::post(start_registration))
+        .route("/register/finish", axum::routing::post(finish_registration))
+        .route(
+            "/authenticate/start",
+            axum::routing::post(start_authentication),
+        )
+        .route(
+            "/authenticate/finish",
+            axum::routing::post(finish_authentication),
+        )
+        .route("/credentials", axum::routing::get(list_credentials))
+        .route(
+            "/credentials/{id}",
+            axum::routing::delete(delete_credential),
+        )
+}
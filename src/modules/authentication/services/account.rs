@@ -2,22 +2,44 @@ use crate::{
     common::model::DatabaseModel,
     modules::{
         authentication::{
-            dtos::account::CreateAccountRequestDTO, errors::service::*,
-            models::account::AccountModel, services::password::PasswordService,
+            config::authentication::AuthenticationConfiguration,
+            dtos::{
+                account::{CreateAccountRequestDTO, CreatePasswordHistoryOptions},
+                authentication::PasswordChangeRequiredDto,
+            },
+            errors::service::*,
+            models::{
+                account::AccountModel, authorization_code::AuthorizationCodeModel,
+                email_verification_token::EmailVerificationTokenModel,
+                failed_sign_in::FailedSignInModel, impersonation_event::ImpersonationEventModel,
+                magic_link_token::MagicLinkTokenModel, password_history::PasswordHistoryModel,
+                password_reset_token::PasswordResetTokenModel,
+                webauthn_challenge::WebAuthnChallengeModel,
+            },
+            services::{password::PasswordService, token::TokenService},
         },
-        base::exports::{BaseId, DatabaseConnection},
+        base::exports::{BaseDateTime, BaseId, DatabaseConnection},
     },
 };
 
+// Have to match the unique indexes defined in `migrations/account.rs`
+const USERNAME_INDEX: &str = "account_username_unique";
+const EMAIL_INDEX: &str = "account_email_unique";
+
 #[derive(Debug, Clone)]
 pub struct AccountService {
     database_connection: DatabaseConnection,
+    authentication_config: AuthenticationConfiguration,
 }
 
 impl AccountService {
-    pub fn new(database_connection: DatabaseConnection) -> Self {
+    pub fn new(
+        authentication_config: AuthenticationConfiguration,
+        database_connection: DatabaseConnection,
+    ) -> Self {
         Self {
             database_connection,
+            authentication_config,
         }
     }
 
@@ -56,7 +78,7 @@ impl AccountService {
             .bind(("table", AccountModel::table_name()))
             .bind(("username", username.to_string()))
             .await
-            .map_err(|e| AuthenticationServiceError::ServerError(e.into()))?
+            .map_err(AuthenticationServiceError::from_error)?
             .take(0)
             .map_err(|_| {
                 AuthenticationServiceError::client(AuthenticationClientError::AccountNotFound)
@@ -73,20 +95,206 @@ impl AccountService {
     pub async fn get_account_by_username_and_password(
         &self,
         pasword_service: &PasswordService,
+        ip_address: &str,
         username: &str,
         password: &str,
     ) -> Result<AccountModel, AuthenticationServiceError> {
-        let account = self.get_account_by_username(username).await?;
+        let lockout_config = &self.authentication_config.lockout;
+        if lockout_config.max_failed_attempts_per_ip > 0 {
+            let failed_for_ip = self.count_recent_failed_sign_ins_for_ip(ip_address).await?;
+            if failed_for_ip >= lockout_config.max_failed_attempts_per_ip as usize {
+                return Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::TooManySignInAttempts,
+                ));
+            }
+        }
+
+        let mut account = match self.get_account_by_username(username).await {
+            Ok(account) => account,
+            Err(e) => {
+                if matches!(
+                    e,
+                    AuthenticationServiceError::ClientError(
+                        AuthenticationClientError::AccountNotFound
+                    )
+                ) {
+                    self.record_failed_sign_in(ip_address, username).await?;
+                }
+                return Err(e);
+            }
+        };
+
+        if account.is_locked() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::AccountLocked,
+            ));
+        }
+
         let is_password_valid = pasword_service.verify_password(&account.password, password)?;
         if !is_password_valid {
+            self.record_failed_sign_in(ip_address, username).await?;
+            self.register_failed_sign_in_attempt(&account.id).await?;
             return Err(AuthenticationServiceError::client(
                 AuthenticationClientError::InvalidCredentials,
             ));
         }
 
+        if account.failed_sign_in_attempts > 0 || account.locked_until.is_some() {
+            self.unlock_account(&account.id).await?;
+        }
+
+        // The plain password is only available here, so outdated hashes are upgraded on sign-in
+        if pasword_service.needs_rehash(&account.password) {
+            match self
+                .rehash_password(pasword_service, &account.id, password)
+                .await
+            {
+                Ok(password_hash) => account.password = password_hash,
+                Err(e) => tracing::error!("Failed to rehash password: {:?}", e),
+            }
+        }
+
         Ok(account)
     }
 
+    /// Replaces the hash of an unchanged password, unlike a password change this skips the policy.
+    async fn rehash_password(
+        &self,
+        password_service: &PasswordService,
+        account_id: &BaseId,
+        password: &str,
+    ) -> Result<String, AuthenticationServiceError> {
+        let password_hash = password_service.hash_password(password)?;
+
+        self.database_connection
+            .query("UPDATE type::table($table) SET password = $password WHERE id = $id")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("password", password_hash.clone()))
+            .bind(("id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(password_hash)
+    }
+
+    pub async fn register_failed_sign_in_attempt(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        let accounts: Vec<AccountModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET failed_sign_in_attempts += 1 WHERE id = $id RETURN AFTER")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let lockout_config = &self.authentication_config.lockout;
+        let failed_attempts = accounts
+            .first()
+            .map(|account| account.failed_sign_in_attempts)
+            .unwrap_or_default();
+        if lockout_config.max_failed_attempts == 0
+            || failed_attempts < lockout_config.max_failed_attempts
+        {
+            return Ok(());
+        }
+
+        let locked_until = chrono::Utc::now() + lockout_config.lockout_duration(failed_attempts);
+        tracing::info!(
+            "Locking account {} until {} after {} failed sign-in attempts",
+            AccountModel::to_named_format(account_id),
+            locked_until,
+            failed_attempts
+        );
+
+        self.database_connection
+            .query("UPDATE type::table($table) SET locked_until = $locked_until WHERE id = $id")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("id", account_id.clone()))
+            .bind(("locked_until", BaseDateTime::from(locked_until)))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    pub async fn unlock_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("UPDATE type::table($table) SET failed_sign_in_attempts = 0, locked_until = NONE WHERE id = $id")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    async fn record_failed_sign_in(
+        &self,
+        ip_address: &str,
+        username: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        let window_start = chrono::Utc::now()
+            - chrono::Duration::seconds(
+                self.authentication_config.lockout.ip_window_seconds as i64,
+            );
+
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE created_at < $window_start")
+            .query("CREATE type::table($table) SET ip_address = $ip_address, username = $username")
+            .bind(("table", FailedSignInModel::table_name()))
+            .bind(("window_start", BaseDateTime::from(window_start)))
+            .bind(("ip_address", ip_address.to_string()))
+            .bind(("username", username.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    pub async fn count_recent_failed_sign_ins_for_ip(
+        &self,
+        ip_address: &str,
+    ) -> Result<usize, AuthenticationServiceError> {
+        let window_start = chrono::Utc::now()
+            - chrono::Duration::seconds(
+                self.authentication_config.lockout.ip_window_seconds as i64,
+            );
+
+        let count: Option<usize> = self
+            .database_connection
+            .query("SELECT count() AS count FROM type::table($table) WHERE ip_address = $ip_address AND created_at > $window_start GROUP ALL")
+            .bind(("table", FailedSignInModel::table_name()))
+            .bind(("ip_address", ip_address.to_string()))
+            .bind(("window_start", BaseDateTime::from(window_start)))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take((0, "count"))
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(count.unwrap_or_default())
+    }
+
+    pub async fn clear_failed_sign_ins_for_ip(
+        &self,
+        ip_address: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE ip_address = $ip_address")
+            .bind(("table", FailedSignInModel::table_name()))
+            .bind(("ip_address", ip_address.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
     pub async fn exists_username(
         &self,
         username: &str,
@@ -101,6 +309,77 @@ impl AccountService {
         }
     }
 
+    pub fn normalize_email(&self, email: &str) -> Result<String, AuthenticationServiceError> {
+        let email = email.trim().to_lowercase();
+        let is_valid = email.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+        }) && !email.chars().any(char::is_whitespace);
+
+        if !is_valid {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidEmail,
+            ));
+        }
+
+        Ok(email)
+    }
+
+    pub async fn get_account_by_email(
+        &self,
+        email: &str,
+    ) -> Result<AccountModel, AuthenticationServiceError> {
+        let accounts: Vec<AccountModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE email = $email")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("email", email.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        accounts
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::AccountNotFound,
+            ))
+    }
+
+    pub async fn exists_email(&self, email: &str) -> Result<bool, AuthenticationServiceError> {
+        let res = self.get_account_by_email(email).await;
+        match res {
+            Err(AuthenticationServiceError::ClientError(
+                AuthenticationClientError::AccountNotFound,
+            )) => Ok(false),
+            Err(e) => Err(e),
+            _ => Ok(true),
+        }
+    }
+
+    pub fn requires_verified_email(&self) -> bool {
+        self.authentication_config
+            .email_verification
+            .require_verified_email
+    }
+
+    pub fn ensure_email_verified(
+        &self,
+        account: &AccountModel,
+    ) -> Result<(), AuthenticationServiceError> {
+        if self.requires_verified_email() && !account.email_verified {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::EmailNotVerified,
+            ));
+        }
+
+        Ok(())
+    }
+
     pub async fn create_account(
         &self,
         password_service: &PasswordService,
@@ -113,6 +392,16 @@ impl AccountService {
             ));
         }
 
+        if let Some(email) = create_account.email.as_deref() {
+            let email = self.normalize_email(email)?;
+            if self.exists_email(&email).await? {
+                return Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::EmailAlreadyInUse,
+                ));
+            }
+            create_account.email = Some(email);
+        }
+
         let hashed_password = password_service.hash_password(&create_account.password)?;
         create_account.password = hashed_password;
 
@@ -121,7 +410,7 @@ impl AccountService {
             .insert(AccountModel::table_name())
             .content(create_account)
             .await
-            .map_err(AuthenticationServiceError::from_error)?;
+            .map_err(unique_index_error)?;
 
         created_accounts
             .first()
@@ -137,13 +426,168 @@ impl AccountService {
         account_id: &BaseId,
         new_password: &str,
     ) -> Result<(), AuthenticationServiceError> {
+        let account = self.get_account_by_id(account_id).await?;
+        self.validate_new_password(password_service, &account, new_password)
+            .await?;
+        self.store_password(password_service, account, new_password)
+            .await
+    }
+
+    /// Checks the policy, the breached list and the password history without changing anything.
+    pub async fn validate_new_password(
+        &self,
+        password_service: &PasswordService,
+        account: &AccountModel,
+        new_password: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        password_service.validate_password(new_password, &account.username)?;
+
+        let history_size = self.authentication_config.password_policy.history_size;
+        if history_size > 0 {
+            let previous_hashes = self.get_password_history(&account.id).await?;
+            for hash in std::iter::once(&account.password)
+                .chain(previous_hashes.iter().take(history_size - 1))
+            {
+                if password_service.verify_password(hash, new_password)? {
+                    return Err(AuthenticationServiceError::client(
+                        AuthenticationClientError::PasswordReused(history_size),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stores a password that already passed `validate_new_password`.
+    pub async fn store_password(
+        &self,
+        password_service: &PasswordService,
+        account: AccountModel,
+        new_password: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        let account_id = &account.id;
+        let history_size = self.authentication_config.password_policy.history_size;
         let hashed_password = password_service.hash_password(new_password)?;
 
         self.database_connection
-            .query("UPDATE type::table($table) SET password = $password, updated_at = time::now() WHERE id = $id")
+            .query("UPDATE type::table($table) SET password = $password, must_change_password = false, password_changed_at = time::now(), updated_at = time::now() WHERE id = $id")
             .bind(("table", AccountModel::table_name()))
             .bind(("password", hashed_password))
-            .bind(("id", account_id.key().to_string()))
+            .bind(("id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        if history_size > 1 {
+            self.add_password_history(account_id, account.password, history_size - 1)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Previous password hashes of an account, newest first. The current hash is not included.
+    async fn get_password_history(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<Vec<String>, AuthenticationServiceError> {
+        let history: Vec<PasswordHistoryModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE account_id = $account_id ORDER BY created_at DESC")
+            .bind(("table", PasswordHistoryModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(history
+            .into_iter()
+            .map(|entry| entry.password_hash)
+            .collect())
+    }
+
+    async fn add_password_history(
+        &self,
+        account_id: &BaseId,
+        password_hash: String,
+        keep: usize,
+    ) -> Result<(), AuthenticationServiceError> {
+        let _: Vec<PasswordHistoryModel> = self
+            .database_connection
+            .insert(PasswordHistoryModel::table_name())
+            .content(CreatePasswordHistoryOptions {
+                account_id: account_id.clone(),
+                password_hash,
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let history: Vec<PasswordHistoryModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE account_id = $account_id ORDER BY created_at DESC")
+            .bind(("table", PasswordHistoryModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        for entry in history.into_iter().skip(keep) {
+            let _: Option<PasswordHistoryModel> =
+                self.database_connection
+                    .delete(entry.id)
+                    .await
+                    .map_err(AuthenticationServiceError::from_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether the account was flagged for a password change or its password exceeded the
+    /// configured maximum age.
+    pub fn requires_password_change(&self, account: &AccountModel) -> bool {
+        let is_expired = self
+            .authentication_config
+            .password_policy
+            .max_age_days
+            .is_some_and(|max_age_days| {
+                let changed_at = account.password_changed_at().clone().into_inner().0;
+                changed_at + chrono::Duration::days(max_age_days as i64) <= chrono::Utc::now()
+            });
+
+        account.must_change_password || is_expired
+    }
+
+    /// Issues a token that can only be used to set a new password, in place of a session.
+    pub fn create_password_change_challenge(
+        &self,
+        token_service: &TokenService,
+        account: &AccountModel,
+    ) -> Result<PasswordChangeRequiredDto, AuthenticationServiceError> {
+        let (change_token, change_token_expires_at) = token_service
+            .generate_password_change_token(
+                &account.id,
+                self.authentication_config
+                    .password_policy
+                    .change_token_expiration_seconds,
+            )?;
+
+        Ok(PasswordChangeRequiredDto {
+            password_change_required: true,
+            change_token,
+            change_token_expires_at,
+        })
+    }
+
+    pub async fn require_password_change(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("UPDATE type::table($table) SET must_change_password = true, updated_at = time::now() WHERE id = $id")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("id", account_id.clone()))
             .await
             .map_err(AuthenticationServiceError::from_error)?;
 
@@ -159,23 +603,207 @@ impl AccountService {
             .query("UPDATE type::table($table) SET username = $username, updated_at = time::now() WHERE id = $id")
             .bind(("table", AccountModel::table_name()))
             .bind(("username", new_username.to_string()))
-            .bind(("id", account_id.key().to_string()))
+            .bind(("id", account_id.clone()))
             .await
             .map_err(AuthenticationServiceError::from_error)?;
 
         Ok(())
     }
 
+    pub async fn update_account_email(
+        &self,
+        account_id: &BaseId,
+        new_email: &str,
+    ) -> Result<bool, AuthenticationServiceError> {
+        let email = self.normalize_email(new_email)?;
+        match self.get_account_by_email(&email).await {
+            Ok(account) if &account.id == account_id => return Ok(false),
+            Ok(_) => {
+                return Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::EmailAlreadyInUse,
+                ));
+            }
+            Err(AuthenticationServiceError::ClientError(
+                AuthenticationClientError::AccountNotFound,
+            )) => {}
+            Err(e) => return Err(e),
+        }
+
+        self.database_connection
+            .query("UPDATE type::table($table) SET email = $email, email_verified = false, email_verified_at = NONE, updated_at = time::now() WHERE id = $id")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("email", email))
+            .bind(("id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .check()
+            .map_err(unique_index_error)?;
+
+        Ok(true)
+    }
+
+    pub async fn mark_email_verified(
+        &self,
+        account_id: &BaseId,
+        email: &str,
+    ) -> Result<bool, AuthenticationServiceError> {
+        let accounts: Vec<AccountModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET email_verified = true, email_verified_at = time::now() WHERE id = $id AND email = $email RETURN AFTER")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("id", account_id.clone()))
+            .bind(("email", email.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(!accounts.is_empty())
+    }
+
     pub async fn delete_account(
         &self,
         account_id: &BaseId,
     ) -> Result<(), AuthenticationServiceError> {
-        let _: Option<AccountModel> = self
+        let account: Option<AccountModel> = self
             .database_connection
             .delete(account_id)
             .await
             .map_err(AuthenticationServiceError::from_error)?;
 
+        // Records that only exist for the account go with it, the tables have no ON DELETE rules
+        for table in [
+            PasswordHistoryModel::table_name(),
+            PasswordResetTokenModel::table_name(),
+            EmailVerificationTokenModel::table_name(),
+            MagicLinkTokenModel::table_name(),
+            AuthorizationCodeModel::table_name(),
+            WebAuthnChallengeModel::table_name(),
+        ] {
+            self.database_connection
+                .query("DELETE FROM type::table($table) WHERE account_id = $account_id")
+                .bind(("table", table))
+                .bind(("account_id", account_id.clone()))
+                .await
+                .map_err(AuthenticationServiceError::from_error)?;
+        }
+
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE account_id = $account_id OR actor_id = $account_id")
+            .bind(("table", ImpersonationEventModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        if let Some(account) = account {
+            self.database_connection
+                .query("DELETE FROM type::table($table) WHERE username = $username")
+                .bind(("table", FailedSignInModel::table_name()))
+                .bind(("username", account.username))
+                .await
+                .map_err(AuthenticationServiceError::from_error)?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn set_totp_pending_secret(
+        &self,
+        account_id: &BaseId,
+        encrypted_secret: String,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("UPDATE type::table($table) SET totp_pending_secret = $secret WHERE id = $id")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("id", account_id.clone()))
+            .bind(("secret", encrypted_secret))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    pub async fn enable_totp(
+        &self,
+        account_id: &BaseId,
+        encrypted_secret: String,
+        recovery_code_hashes: Vec<String>,
+        last_used_step: u64,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("UPDATE type::table($table) SET totp_enabled = true, totp_secret = $secret, totp_pending_secret = NONE, totp_recovery_codes = $recovery_codes, totp_last_used_step = $step WHERE id = $id")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("id", account_id.clone()))
+            .bind(("secret", encrypted_secret))
+            .bind(("recovery_codes", recovery_code_hashes))
+            .bind(("step", last_used_step))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    pub async fn disable_totp(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("UPDATE type::table($table) SET totp_enabled = false, totp_secret = NONE, totp_pending_secret = NONE, totp_recovery_codes = [], totp_last_used_step = NONE WHERE id = $id")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
         Ok(())
     }
+
+    pub async fn mark_totp_step_used(
+        &self,
+        account_id: &BaseId,
+        step: u64,
+    ) -> Result<bool, AuthenticationServiceError> {
+        let accounts: Vec<AccountModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET totp_last_used_step = $step WHERE id = $id AND (totp_last_used_step = NONE OR totp_last_used_step < $step) RETURN AFTER")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("id", account_id.clone()))
+            .bind(("step", step))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(!accounts.is_empty())
+    }
+
+    pub async fn consume_recovery_code(
+        &self,
+        account_id: &BaseId,
+        recovery_code_hash: String,
+    ) -> Result<bool, AuthenticationServiceError> {
+        let accounts: Vec<AccountModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET totp_recovery_codes -= $hash WHERE id = $id AND totp_recovery_codes CONTAINS $hash RETURN AFTER")
+            .bind(("table", AccountModel::table_name()))
+            .bind(("id", account_id.clone()))
+            .bind(("hash", recovery_code_hash))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(!accounts.is_empty())
+    }
+}
+
+/// The existence checks before writes can race, the unique indexes settle it.
+fn unique_index_error(e: surrealdb::Error) -> AuthenticationServiceError {
+    let message = e.to_string();
+    if message.contains(&format!("`{}`", EMAIL_INDEX)) {
+        AuthenticationServiceError::client(AuthenticationClientError::EmailAlreadyInUse)
+    } else if message.contains(&format!("`{}`", USERNAME_INDEX)) {
+        AuthenticationServiceError::client(AuthenticationClientError::AccountAlreadyExists)
+    } else {
+        AuthenticationServiceError::from_error(e)
+    }
 }
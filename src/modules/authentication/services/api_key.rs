@@ -0,0 +1,215 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            config::authentication::ApiKeyConfiguration,
+            dtos::api_key::{CreateApiKeyOptions, CreateApiKeyRequestDto},
+            errors::service::*,
+            models::api_key::ApiKeyModel,
+            services::role::RoleService,
+        },
+        base::exports::{BaseDateTime, BaseId, DatabaseConnection},
+    },
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sha2::{Digest, Sha256};
+
+const TOKEN_PREFIX: &str = "cpat_";
+const DISPLAYED_PREFIX_LENGTH: usize = 12;
+
+pub struct ApiKeyService {
+    database_connection: DatabaseConnection,
+    api_key_config: ApiKeyConfiguration,
+}
+
+impl ApiKeyService {
+    pub fn new(
+        api_key_config: ApiKeyConfiguration,
+        database_connection: DatabaseConnection,
+    ) -> Self {
+        Self {
+            database_connection,
+            api_key_config,
+        }
+    }
+
+    fn generate_token(&self) -> String {
+        use rand::Rng;
+        let token: [u8; 32] = rand::rng().random();
+        format!("{}{}", TOKEN_PREFIX, URL_SAFE_NO_PAD.encode(token))
+    }
+
+    pub fn hash_token(&self, token: &str) -> String {
+        Sha256::digest(token.trim().as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// Expiry is capped by `maxExpirationDays`, keys without an expiry get the maximum.
+    fn expires_at(&self, expires_in_days: Option<u64>) -> Option<BaseDateTime> {
+        let expires_in_days = match (expires_in_days, self.api_key_config.max_expiration_days) {
+            (Some(days), Some(max_days)) => Some(days.min(max_days)),
+            (requested, max_days) => requested.or(max_days),
+        }?;
+
+        Some(BaseDateTime::from(
+            chrono::Utc::now() + chrono::Duration::days(expires_in_days.max(1) as i64),
+        ))
+    }
+
+    pub async fn get_api_keys_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<Vec<ApiKeyModel>, AuthenticationServiceError> {
+        let api_keys: Vec<ApiKeyModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE account_id = $account_id ORDER BY created_at DESC")
+            .bind(("table", ApiKeyModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(api_keys)
+    }
+
+    /// Returns the key together with the plain token, which is not stored and only shown once.
+    pub async fn create_api_key(
+        &self,
+        role_service: &RoleService,
+        account_id: &BaseId,
+        create_api_key: CreateApiKeyRequestDto,
+    ) -> Result<(ApiKeyModel, String), AuthenticationServiceError> {
+        let existing_keys = self.get_api_keys_for_account(account_id).await?;
+        if existing_keys.len() >= self.api_key_config.max_keys_per_account {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::ApiKeyLimitReached,
+            ));
+        }
+
+        let mut scopes = create_api_key.scopes;
+        scopes.sort();
+        scopes.dedup();
+        for scope in &scopes {
+            if !role_service
+                .account_has_permission(account_id, scope)
+                .await?
+            {
+                return Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::InvalidApiKeyScope,
+                ));
+            }
+        }
+
+        let token = self.generate_token();
+        let created_keys: Vec<ApiKeyModel> = self
+            .database_connection
+            .insert(ApiKeyModel::table_name())
+            .content(CreateApiKeyOptions {
+                account_id: account_id.clone(),
+                name: create_api_key.name,
+                token_hash: self.hash_token(&token),
+                token_prefix: token.chars().take(DISPLAYED_PREFIX_LENGTH).collect(),
+                scopes,
+                expires_at: self.expires_at(create_api_key.expires_in_days),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let api_key =
+            created_keys
+                .into_iter()
+                .next()
+                .ok_or(AuthenticationServiceError::ServerError(anyhow::anyhow!(
+                    "API key creation failed without a specific error."
+                )))?;
+
+        Ok((api_key, token))
+    }
+
+    pub async fn revoke_api_key(
+        &self,
+        account_id: &BaseId,
+        api_key_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        let deleted: Vec<ApiKeyModel> = self
+            .database_connection
+            .query("DELETE FROM type::table($table) WHERE id = $id AND account_id = $account_id RETURN BEFORE")
+            .bind(("table", ApiKeyModel::table_name()))
+            .bind(("id", api_key_id.clone()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        if deleted.is_empty() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::ApiKeyNotFound,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub async fn delete_all_api_keys_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE account_id = $account_id")
+            .bind(("table", ApiKeyModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    /// Resolves the key for a presented token and records its usage.
+    pub async fn authenticate(
+        &self,
+        token: &str,
+    ) -> Result<ApiKeyModel, AuthenticationServiceError> {
+        let api_keys: Vec<ApiKeyModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE token_hash = $token_hash")
+            .bind(("table", ApiKeyModel::table_name()))
+            .bind(("token_hash", self.hash_token(token)))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let api_key = api_keys
+            .into_iter()
+            .next()
+            .filter(|api_key| !api_key.is_expired())
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidApiKey,
+            ))?;
+
+        // Usage is only written once per interval to keep requests cheap
+        let update_before = BaseDateTime::from(
+            chrono::Utc::now()
+                - chrono::Duration::seconds(
+                    self.api_key_config.last_used_update_interval_seconds as i64,
+                ),
+        );
+        if api_key
+            .last_used_at
+            .as_ref()
+            .is_none_or(|last_used_at| last_used_at < &update_before)
+        {
+            self.database_connection
+                .query("UPDATE $id SET last_used_at = time::now()")
+                .bind(("id", api_key.id.clone()))
+                .await
+                .map_err(AuthenticationServiceError::from_error)?;
+        }
+
+        Ok(api_key)
+    }
+}
@@ -1,13 +1,28 @@
+use crate::common::model::DatabaseModel;
 use crate::modules::{
     authentication::{
         dtos::{
             account::CreateAccountRequestDTO,
-            authentication::{AuthenticationResponseDto, SignInRequestDto},
+            authentication::{
+                ChangePasswordRequestDto, SignInRequestDto, SignInResponseDto, SignUpRequestDto,
+                SignUpResultDto, VerificationPendingDto,
+            },
         },
-        errors::service::AuthenticationServiceError,
+        errors::service::{AuthenticationClientError, AuthenticationServiceError},
+        models::account::AccountModel,
         services::{
-            account::AccountService, password::PasswordService, session::SessionService,
-            token::TokenService,
+            account::AccountService,
+            api_key::ApiKeyService,
+            email_verification::EmailVerificationService,
+            external_login::ExternalLoginService,
+            mailer::Mailer,
+            mfa::MfaService,
+            password::PasswordService,
+            revocation::RevocationService,
+            role::RoleService,
+            session::SessionService,
+            token::{FIRST_PARTY_AUDIENCE, TokenService},
+            webauthn::WebAuthnService,
         },
     },
     base::exports::{BaseId, request_info::RequestInfoExtractor},
@@ -21,53 +36,141 @@ impl AuthenticationService {
         Self {}
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn authenticate(
         &self,
         account_service: &AccountService,
         session_service: &SessionService,
         token_service: &TokenService,
         pasword_service: &PasswordService,
+        mfa_service: &MfaService,
         request_info: RequestInfoExtractor,
         signin: SignInRequestDto,
-    ) -> Result<AuthenticationResponseDto, AuthenticationServiceError> {
+    ) -> Result<SignInResponseDto, AuthenticationServiceError> {
         let account = account_service
             .get_account_by_username_and_password(
                 pasword_service,
+                &request_info.ip_address,
                 &signin.username,
                 &signin.password,
             )
             .await?;
 
+        self.complete_sign_in(
+            account_service,
+            session_service,
+            token_service,
+            mfa_service,
+            request_info,
+            account,
+        )
+        .await
+    }
+
+    /// Finishes a sign-in for an account whose primary factor has been verified.
+    pub async fn complete_sign_in(
+        &self,
+        account_service: &AccountService,
+        session_service: &SessionService,
+        token_service: &TokenService,
+        mfa_service: &MfaService,
+        request_info: RequestInfoExtractor,
+        account: AccountModel,
+    ) -> Result<SignInResponseDto, AuthenticationServiceError> {
+        account_service.ensure_email_verified(&account)?;
+
+        if account.totp_enabled {
+            return mfa_service
+                .create_challenge(token_service, &account)
+                .map(SignInResponseDto::MfaRequired);
+        }
+
+        self.finish_sign_in(
+            account_service,
+            session_service,
+            token_service,
+            request_info,
+            account,
+        )
+        .await
+    }
+
+    /// Finishes a sign-in once every required factor has been verified. A pending password change
+    /// is only revealed at this point, so the change token cannot be used to skip a second factor.
+    pub async fn finish_sign_in(
+        &self,
+        account_service: &AccountService,
+        session_service: &SessionService,
+        token_service: &TokenService,
+        request_info: RequestInfoExtractor,
+        account: AccountModel,
+    ) -> Result<SignInResponseDto, AuthenticationServiceError> {
+        if account_service.requires_password_change(&account) {
+            return account_service
+                .create_password_change_challenge(token_service, &account)
+                .map(SignInResponseDto::PasswordChangeRequired);
+        }
+
         session_service
             .create_session(
                 token_service,
                 &account.id,
                 request_info,
-                "core-auth".to_string(),
+                FIRST_PARTY_AUDIENCE.to_string(),
             )
             .await
+            .map(SignInResponseDto::Authenticated)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn register(
         &self,
         account_service: &AccountService,
         token_service: &TokenService,
         session_service: &SessionService,
         password_service: &PasswordService,
+        email_verification_service: &EmailVerificationService,
+        mailer: &dyn Mailer,
         request_info: RequestInfoExtractor,
-        signin: SignInRequestDto,
-    ) -> Result<AuthenticationResponseDto, AuthenticationServiceError> {
-        tracing::debug!("Registering new user: {}", &signin.username);
+        signup: SignUpRequestDto,
+    ) -> Result<SignUpResultDto, AuthenticationServiceError> {
+        tracing::debug!("Registering new user: {}", &signup.username);
+        // Without an address the account could never be verified and never sign in
+        if account_service.requires_verified_email() && signup.email.is_none() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::EmailMissing,
+            ));
+        }
+        password_service.validate_password(&signup.password, &signup.username)?;
         let account = account_service
             .create_account(
                 password_service,
                 CreateAccountRequestDTO {
-                    password: signin.password,
-                    username: signin.username,
+                    password: signup.password,
+                    username: signup.username,
+                    email: signup.email,
                 },
             )
             .await?;
 
+        if account.email.is_some()
+            && let Err(e) = email_verification_service
+                .send_verification(mailer, &account)
+                .await
+        {
+            tracing::error!("Failed to send verification mail: {}", e);
+        }
+
+        // Sign-in is blocked until the address is verified, so sign-up must not bypass it
+        if account_service.ensure_email_verified(&account).is_err() {
+            return Ok(SignUpResultDto::VerificationPending(
+                VerificationPendingDto {
+                    account_id: AccountModel::to_named_format(&account.id),
+                    email_verification_required: true,
+                },
+            ));
+        }
+
         tracing::debug!("Creating session for new user: {}", &account.username);
 
         session_service
@@ -75,9 +178,58 @@ impl AuthenticationService {
                 token_service,
                 &account.id,
                 request_info,
-                "core-auth".to_string(),
+                FIRST_PARTY_AUDIENCE.to_string(),
             )
             .await
+            .map(SignUpResultDto::Authenticated)
+    }
+
+    /// Sets the password of an account that was required to change it on sign-in. No session is
+    /// issued, the account signs in again with the new password and any second factor.
+    /// Change tokens are single use, their id is recorded as revoked before the password changes.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn change_required_password(
+        &self,
+        account_service: &AccountService,
+        session_service: &SessionService,
+        token_service: &TokenService,
+        password_service: &PasswordService,
+        revocation_service: &RevocationService,
+        change: ChangePasswordRequestDto,
+    ) -> Result<(), AuthenticationServiceError> {
+        let claims = token_service.verify_password_change_token(&change.change_token)?;
+        if revocation_service
+            .is_token_id_revoked(&claims.token_id)
+            .await?
+        {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidPasswordChangeToken,
+            ));
+        }
+
+        let account = account_service
+            .get_account_by_id(&claims.account_id)
+            .await?;
+        if account.is_locked() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::AccountLocked,
+            ));
+        }
+
+        // Policy violations are reported before the token is used up, so the user can retry
+        password_service.validate_password(&change.new_password, &account.username)?;
+        revocation_service
+            .revoke_token_id(&claims.token_id, claims.expires_at)
+            .await?;
+
+        account_service
+            .update_account_password(password_service, &account.id, &change.new_password)
+            .await?;
+        session_service
+            .deactivate_all_sessions_for_account(&account.id)
+            .await?;
+
+        Ok(())
     }
 
     pub async fn logout(
@@ -98,16 +250,33 @@ impl AuthenticationService {
             .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn delete_account(
         // Here because we need to delete sessions as well
         &self,
         account_service: &AccountService,
         session_service: &SessionService,
+        role_service: &RoleService,
+        webauthn_service: &WebAuthnService,
+        external_login_service: &ExternalLoginService,
+        api_key_service: &ApiKeyService,
         account_id: &BaseId,
     ) -> Result<(), AuthenticationServiceError> {
         session_service
             .delete_all_sessions_for_account(account_id)
             .await?;
+        role_service
+            .unassign_all_roles_for_account(account_id)
+            .await?;
+        webauthn_service
+            .delete_all_credentials_for_account(account_id)
+            .await?;
+        external_login_service
+            .delete_all_identities_for_account(account_id)
+            .await?;
+        api_key_service
+            .delete_all_api_keys_for_account(account_id)
+            .await?;
         account_service.delete_account(account_id).await?;
 
         Ok(())
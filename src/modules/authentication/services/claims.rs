@@ -0,0 +1,90 @@
+use crate::modules::authentication::{
+    errors::service::AuthenticationServiceError, models::account::AccountModel,
+};
+use serde_json::{Map, Value, json};
+use std::sync::{Arc, LazyLock, RwLock};
+
+pub const OPENID_SCOPE: &str = "openid";
+pub const PROFILE_SCOPE: &str = "profile";
+pub const EMAIL_SCOPE: &str = "email";
+
+static CLAIM_PROVIDERS: LazyLock<RwLock<Vec<Arc<dyn ClaimProvider>>>> = LazyLock::new(|| {
+    RwLock::new(vec![
+        Arc::new(ProfileClaimProvider),
+        Arc::new(EmailClaimProvider),
+    ])
+});
+
+pub type Claims = Map<String, Value>;
+
+/// Contributes claims to id tokens and userinfo responses when its scope was granted.
+#[async_trait::async_trait]
+pub trait ClaimProvider: Send + Sync {
+    fn scope(&self) -> &'static str;
+
+    fn claim_names(&self) -> &'static [&'static str];
+
+    async fn claims(&self, account: &AccountModel) -> Result<Claims, AuthenticationServiceError>;
+}
+
+/// Registers an additional claim provider, modules should call this during initialization.
+pub fn register_claim_provider(provider: impl ClaimProvider + 'static) {
+    match CLAIM_PROVIDERS.write() {
+        Ok(mut providers) => providers.push(Arc::new(provider)),
+        Err(e) => tracing::error!("Failed to register claim provider: {}", e),
+    }
+}
+
+pub fn claim_providers() -> Vec<Arc<dyn ClaimProvider>> {
+    CLAIM_PROVIDERS
+        .read()
+        .map(|providers| providers.clone())
+        .unwrap_or_default()
+}
+
+pub struct ProfileClaimProvider;
+
+#[async_trait::async_trait]
+impl ClaimProvider for ProfileClaimProvider {
+    fn scope(&self) -> &'static str {
+        PROFILE_SCOPE
+    }
+
+    fn claim_names(&self) -> &'static [&'static str] {
+        &["preferred_username", "updated_at"]
+    }
+
+    async fn claims(&self, account: &AccountModel) -> Result<Claims, AuthenticationServiceError> {
+        let mut claims = Claims::new();
+        claims.insert("preferred_username".to_string(), json!(account.username));
+        claims.insert(
+            "updated_at".to_string(),
+            json!(account.updated_at.clone().into_inner().0.timestamp()),
+        );
+
+        Ok(claims)
+    }
+}
+
+pub struct EmailClaimProvider;
+
+#[async_trait::async_trait]
+impl ClaimProvider for EmailClaimProvider {
+    fn scope(&self) -> &'static str {
+        EMAIL_SCOPE
+    }
+
+    fn claim_names(&self) -> &'static [&'static str] {
+        &["email", "email_verified"]
+    }
+
+    async fn claims(&self, account: &AccountModel) -> Result<Claims, AuthenticationServiceError> {
+        let mut claims = Claims::new();
+        if let Some(email) = account.email.as_ref() {
+            claims.insert("email".to_string(), json!(email));
+            claims.insert("email_verified".to_string(), json!(account.email_verified));
+        }
+
+        Ok(claims)
+    }
+}
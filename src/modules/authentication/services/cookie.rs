@@ -0,0 +1,317 @@
+use crate::modules::authentication::{
+    config::authentication::{CookieConfiguration, CookieSameSite},
+    dtos::authentication::{
+        AuthenticationResponseDto, CookieAuthenticationResponseDto, SignInResponseDto,
+    },
+    errors::service::*,
+};
+use axum::http::{HeaderMap, HeaderValue, Method, header};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::Utc;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+pub struct CookieService {
+    cookie_config: CookieConfiguration,
+}
+
+impl CookieService {
+    pub fn new(cookie_config: CookieConfiguration) -> Self {
+        Self { cookie_config }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.cookie_config.enabled
+    }
+
+    /// Checked before signing in, so no session is created that cannot be handed out.
+    pub fn ensure_available(&self, use_cookies: bool) -> Result<(), AuthenticationServiceError> {
+        if use_cookies && !self.is_enabled() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::CookieSessionsDisabled,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn generate_csrf_token(&self) -> String {
+        use rand::Rng;
+        let token: [u8; 32] = rand::rng().random();
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    pub fn get_cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(cookie_name, _)| *cookie_name == name)
+            .map(|(_, value)| value.trim_matches('"'))
+            .filter(|value| !value.is_empty())
+    }
+
+    pub fn access_token<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        if !self.is_enabled() || !self.cookie_config.access_cookie {
+            return None;
+        }
+
+        Self::get_cookie(headers, &self.cookie_config.access_cookie_name)
+    }
+
+    pub fn refresh_token<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        Self::get_cookie(headers, &self.cookie_config.refresh_cookie_name)
+    }
+
+    /// Double-submit check for cookie authenticated requests, a cross-site form can send the
+    /// cookies but cannot read the CSRF cookie to repeat it in the header.
+    pub fn verify_csrf(&self, method: &Method, headers: &HeaderMap) -> bool {
+        if matches!(
+            *method,
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+        ) {
+            return true;
+        }
+
+        let cookie = Self::get_cookie(headers, &self.cookie_config.csrf_cookie_name);
+        let header = headers
+            .get(self.cookie_config.csrf_header_name.as_str())
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim);
+
+        match (cookie, header) {
+            // Digests are compared so the comparison time does not depend on the token
+            (Some(cookie), Some(header)) => {
+                Sha256::digest(cookie.as_bytes()) == Sha256::digest(header.as_bytes())
+            }
+            _ => false,
+        }
+    }
+
+    fn build_cookie(
+        &self,
+        name: &str,
+        value: &str,
+        path: &str,
+        max_age_seconds: i64,
+        http_only: bool,
+    ) -> Result<HeaderValue, AuthenticationServiceError> {
+        let mut cookie = format!(
+            "{}={}; Path={}; Max-Age={}",
+            name,
+            value,
+            path,
+            max_age_seconds.max(0)
+        );
+        if let Some(domain) = self.cookie_config.domain.as_deref() {
+            cookie.push_str(&format!("; Domain={}", domain));
+        }
+        if self.cookie_config.secure {
+            cookie.push_str("; Secure");
+        }
+        if http_only {
+            cookie.push_str("; HttpOnly");
+        }
+        cookie.push_str(match self.cookie_config.same_site {
+            CookieSameSite::Strict => "; SameSite=Strict",
+            CookieSameSite::Lax => "; SameSite=Lax",
+            CookieSameSite::None => "; SameSite=None",
+        });
+
+        HeaderValue::from_str(&cookie).map_err(AuthenticationServiceError::from_error)
+    }
+
+    /// Sets the session as cookies, the body keeps everything but the tokens held in HttpOnly
+    /// cookies and gains the CSRF token.
+    pub fn session_cookies(
+        &self,
+        auth_response: AuthenticationResponseDto,
+    ) -> Result<(HeaderMap, CookieAuthenticationResponseDto), AuthenticationServiceError> {
+        let config = &self.cookie_config;
+        let now = Utc::now();
+        let access_max_age = (auth_response.access_token_expires_at - now).num_seconds();
+        let refresh_max_age = (auth_response.refresh_token_expires_at - now).num_seconds();
+        let csrf_token = self.generate_csrf_token();
+
+        let mut headers = HeaderMap::new();
+        headers.append(
+            header::SET_COOKIE,
+            self.build_cookie(
+                &config.refresh_cookie_name,
+                &auth_response.refresh_token,
+                &config.refresh_cookie_path,
+                refresh_max_age,
+                true,
+            )?,
+        );
+        if config.access_cookie {
+            headers.append(
+                header::SET_COOKIE,
+                self.build_cookie(
+                    &config.access_cookie_name,
+                    &auth_response.access_token,
+                    &config.path,
+                    access_max_age,
+                    true,
+                )?,
+            );
+        }
+        headers.append(
+            header::SET_COOKIE,
+            self.build_cookie(
+                &config.csrf_cookie_name,
+                &csrf_token,
+                &config.path,
+                refresh_max_age,
+                false,
+            )?,
+        );
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+
+        Ok((
+            headers,
+            CookieAuthenticationResponseDto {
+                session_id: auth_response.session_id,
+                account_id: auth_response.account_id,
+                access_token: (!config.access_cookie).then_some(auth_response.access_token),
+                access_token_expires_at: auth_response.access_token_expires_at,
+                refresh_token_expires_at: auth_response.refresh_token_expires_at,
+                csrf_token,
+            },
+        ))
+    }
+
+    /// Response for a new session, as cookies when the client asked for them.
+    pub fn session_response(
+        &self,
+        auth_response: AuthenticationResponseDto,
+        use_cookies: bool,
+    ) -> Result<(HeaderMap, Value), AuthenticationServiceError> {
+        if !use_cookies {
+            return Ok((HeaderMap::new(), json!(auth_response)));
+        }
+
+        self.ensure_available(use_cookies)?;
+        let (headers, cookie_response) = self.session_cookies(auth_response)?;
+
+        Ok((headers, json!(cookie_response)))
+    }
+
+    /// Like `session_response`, sign-ins still waiting for a second factor or a password change
+    /// are returned as they are.
+    pub fn sign_in_response(
+        &self,
+        sign_in_response: SignInResponseDto,
+        use_cookies: bool,
+    ) -> Result<(HeaderMap, Value), AuthenticationServiceError> {
+        match sign_in_response {
+            SignInResponseDto::Authenticated(auth_response) => {
+                self.session_response(auth_response, use_cookies)
+            }
+            sign_in_response => Ok((HeaderMap::new(), json!(sign_in_response))),
+        }
+    }
+
+    pub fn clear_session_cookies(&self) -> Result<HeaderMap, AuthenticationServiceError> {
+        let config = &self.cookie_config;
+        let mut headers = HeaderMap::new();
+        if !self.is_enabled() {
+            return Ok(headers);
+        }
+
+        for (name, path) in [
+            (&config.refresh_cookie_name, &config.refresh_cookie_path),
+            (&config.access_cookie_name, &config.path),
+            (&config.csrf_cookie_name, &config.path),
+        ] {
+            headers.append(
+                header::SET_COOKIE,
+                self.build_cookie(name, "", path, 0, true)?,
+            );
+        }
+
+        Ok(headers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSRF_TOKEN: &str = "csrf-token-value";
+
+    fn service() -> CookieService {
+        CookieService::new(CookieConfiguration {
+            enabled: true,
+            ..CookieConfiguration::default()
+        })
+    }
+
+    fn headers(cookie: Option<&str>, csrf_header: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(cookie) = cookie {
+            headers.insert(header::COOKIE, HeaderValue::from_str(cookie).unwrap());
+        }
+        if let Some(csrf_header) = csrf_header {
+            headers.insert("X-CSRF-Token", HeaderValue::from_str(csrf_header).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn accepts_matching_double_submit() {
+        let cookie = format!("refresh_token=abc; csrf_token={}", CSRF_TOKEN);
+
+        for method in [Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+            assert!(
+                service().verify_csrf(&method, &headers(Some(&cookie), Some(CSRF_TOKEN))),
+                "{}",
+                method
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_missing_or_mismatched_tokens() {
+        let service = service();
+        let cookie = format!("csrf_token={}", CSRF_TOKEN);
+        let cases = [
+            (Some(cookie.as_str()), None),
+            (None, Some(CSRF_TOKEN)),
+            (None, None),
+            (Some(cookie.as_str()), Some("other-token")),
+            (Some(cookie.as_str()), Some("")),
+            (Some("csrf_token="), Some("")),
+            // Only the configured CSRF cookie counts, not any cookie with the same value
+            (Some("access_token=csrf-token-value"), Some(CSRF_TOKEN)),
+        ];
+
+        for (cookie, csrf_header) in cases {
+            assert!(
+                !service.verify_csrf(&Method::POST, &headers(cookie, csrf_header)),
+                "cookie {:?} with header {:?}",
+                cookie,
+                csrf_header
+            );
+        }
+    }
+
+    #[test]
+    fn exempts_safe_methods() {
+        let service = service();
+
+        for method in [Method::GET, Method::HEAD, Method::OPTIONS, Method::TRACE] {
+            assert!(
+                service.verify_csrf(&method, &headers(Some("refresh_token=abc"), None)),
+                "{}",
+                method
+            );
+        }
+    }
+}
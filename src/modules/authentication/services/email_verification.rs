@@ -0,0 +1,186 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            config::authentication::EmailVerificationConfiguration,
+            dtos::email_verification::CreateEmailVerificationTokenOptions,
+            errors::service::*,
+            models::{
+                account::AccountModel, email_verification_token::EmailVerificationTokenModel,
+            },
+            services::{
+                account::AccountService,
+                mailer::{MailMessage, Mailer},
+            },
+        },
+        base::exports::{BaseDateTime, BaseId, DatabaseConnection},
+    },
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sha2::{Digest, Sha256};
+
+pub struct EmailVerificationService {
+    database_connection: DatabaseConnection,
+    email_verification_config: EmailVerificationConfiguration,
+}
+
+impl EmailVerificationService {
+    pub fn new(
+        email_verification_config: EmailVerificationConfiguration,
+        database_connection: DatabaseConnection,
+    ) -> Self {
+        Self {
+            database_connection,
+            email_verification_config,
+        }
+    }
+
+    fn generate_token(&self) -> String {
+        use rand::Rng;
+        let token: [u8; 32] = rand::rng().random();
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    fn hash_token(&self, token: &str) -> String {
+        Sha256::digest(token.trim().as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    async fn get_latest_token_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<Option<EmailVerificationTokenModel>, AuthenticationServiceError> {
+        let tokens: Vec<EmailVerificationTokenModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE account_id = $account_id ORDER BY created_at DESC LIMIT 1")
+            .bind(("table", EmailVerificationTokenModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(tokens.into_iter().next())
+    }
+
+    async fn delete_tokens_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE account_id = $account_id OR expires_at < time::now()")
+            .bind(("table", EmailVerificationTokenModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    pub async fn send_verification(
+        &self,
+        mailer: &dyn Mailer,
+        account: &AccountModel,
+    ) -> Result<(), AuthenticationServiceError> {
+        let email = account
+            .email
+            .clone()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::EmailMissing,
+            ))?;
+
+        if account.email_verified {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::EmailAlreadyVerified,
+            ));
+        }
+
+        let resend_after = BaseDateTime::from(
+            chrono::Utc::now()
+                - chrono::Duration::seconds(
+                    self.email_verification_config.resend_interval_seconds as i64,
+                ),
+        );
+        let latest_token = self.get_latest_token_for_account(&account.id).await?;
+        if latest_token.is_some_and(|token| token.email == email && token.created_at > resend_after)
+        {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::EmailVerificationThrottled,
+            ));
+        }
+
+        self.delete_tokens_for_account(&account.id).await?;
+
+        let token = self.generate_token();
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::seconds(
+                self.email_verification_config.token_expiration_seconds as i64,
+            );
+
+        let _: Vec<EmailVerificationTokenModel> = self
+            .database_connection
+            .insert(EmailVerificationTokenModel::table_name())
+            .content(CreateEmailVerificationTokenOptions {
+                account_id: account.id.clone(),
+                email: email.clone(),
+                token_hash: self.hash_token(&token),
+                expires_at: BaseDateTime::from(expires_at),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let verify_url = self
+            .email_verification_config
+            .verify_url
+            .replace("{token}", &token);
+
+        mailer
+            .send(MailMessage {
+                to: email,
+                subject: "Verify your email address".to_string(),
+                body: format!(
+                    "Please confirm your email address for the account {} using the following link:\n{}\n\nIf you did not create this account you can ignore this mail.",
+                    account.username, verify_url
+                ),
+            })
+            .await
+    }
+
+    pub async fn confirm(
+        &self,
+        account_service: &AccountService,
+        token: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        let tokens: Vec<EmailVerificationTokenModel> = self
+            .database_connection
+            .query("DELETE FROM type::table($table) WHERE token_hash = $token_hash RETURN BEFORE")
+            .bind(("table", EmailVerificationTokenModel::table_name()))
+            .bind(("token_hash", self.hash_token(token)))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let token = tokens
+            .into_iter()
+            .next()
+            .filter(|token| token.expires_at > BaseDateTime::from(chrono::Utc::now()))
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidEmailVerificationToken,
+            ))?;
+
+        // The address may have changed after the token was sent
+        let verified = account_service
+            .mark_email_verified(&token.account_id, &token.email)
+            .await?;
+        if !verified {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidEmailVerificationToken,
+            ));
+        }
+
+        Ok(())
+    }
+}
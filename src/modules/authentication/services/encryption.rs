@@ -0,0 +1,69 @@
+use crate::modules::authentication::errors::service::AuthenticationServiceError;
+use aes_gcm::{
+    Aes256Gcm, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+const SERVICE_NAME: &str = "EncryptionService";
+const NONCE_LENGTH: usize = 12;
+
+pub struct EncryptionService {
+    cipher: Aes256Gcm,
+}
+
+impl EncryptionService {
+    pub fn new(encryption_key: &str) -> Result<Self, AuthenticationServiceError> {
+        let key = STANDARD.decode(encryption_key).map_err(|e| {
+            AuthenticationServiceError::ServerError(crate::log!(
+                tracing::error,
+                "{} Invalid encryption key encoding: {}",
+                SERVICE_NAME,
+                e
+            ))
+        })?;
+
+        let cipher = Aes256Gcm::new_from_slice(&key).map_err(|_| {
+            AuthenticationServiceError::ServerError(crate::log!(
+                tracing::error,
+                "{} Encryption key must be 32 bytes long",
+                SERVICE_NAME
+            ))
+        })?;
+
+        Ok(Self { cipher })
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, AuthenticationServiceError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| AuthenticationServiceError::ServerError(anyhow::anyhow!("{}", e)))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+
+        Ok(STANDARD.encode(payload))
+    }
+
+    pub fn decrypt(&self, encrypted: &str) -> Result<String, AuthenticationServiceError> {
+        let payload = STANDARD
+            .decode(encrypted)
+            .map_err(AuthenticationServiceError::from_error)?;
+        if payload.len() <= NONCE_LENGTH {
+            return Err(AuthenticationServiceError::ServerError(anyhow::anyhow!(
+                "{} Encrypted payload is too short",
+                SERVICE_NAME
+            )));
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|e| AuthenticationServiceError::ServerError(anyhow::anyhow!("{}", e)))?;
+
+        String::from_utf8(plaintext).map_err(AuthenticationServiceError::from_error)
+    }
+}
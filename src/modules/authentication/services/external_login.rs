@@ -0,0 +1,1059 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            config::authentication::{ExternalLoginConfiguration, ExternalProviderConfiguration},
+            dtos::{
+                account::CreateAccountRequestDTO,
+                external_login::{
+                    CreateExternalLoginStateOptions, CreateIdentityOptions,
+                    ExternalCallbackRequestDto, ExternalProviderDto,
+                },
+            },
+            errors::service::*,
+            models::{
+                account::AccountModel, external_login_state::ExternalLoginStateModel,
+                identity::IdentityModel,
+            },
+            services::{account::AccountService, password::PasswordService},
+        },
+        base::exports::{BaseDateTime, BaseId, DatabaseConnection},
+    },
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, jwk::JwkSet};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+const SERVICE_NAME: &str = "ExternalLoginService";
+const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+const HTTP_TIMEOUT_SECONDS: u64 = 10;
+const MAX_USERNAME_ATTEMPTS: usize = 5;
+const ALLOWED_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+static PROVIDER_CACHE: LazyLock<Mutex<HashMap<String, CachedProvider>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone)]
+struct CachedProvider {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+    fetched_at: Instant,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProviderTokenResponse {
+    id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExternalIdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    // Some providers send the flag as a string
+    email_verified: Option<serde_json::Value>,
+    preferred_username: Option<String>,
+}
+
+impl ExternalIdTokenClaims {
+    fn is_email_verified(&self) -> bool {
+        match &self.email_verified {
+            Some(serde_json::Value::Bool(verified)) => *verified,
+            Some(serde_json::Value::String(verified)) => verified == "true",
+            _ => false,
+        }
+    }
+}
+
+pub struct ExternalLoginService {
+    database_connection: DatabaseConnection,
+    external_login_config: ExternalLoginConfiguration,
+    http_client: reqwest::Client,
+}
+
+impl ExternalLoginService {
+    pub fn new(
+        external_login_config: ExternalLoginConfiguration,
+        database_connection: DatabaseConnection,
+    ) -> Result<Self, AuthenticationServiceError> {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(HTTP_TIMEOUT_SECONDS))
+            .build()
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(Self {
+            database_connection,
+            external_login_config,
+            http_client,
+        })
+    }
+
+    fn generate_secret(&self) -> String {
+        use rand::Rng;
+        let secret: [u8; 32] = rand::rng().random();
+        URL_SAFE_NO_PAD.encode(secret)
+    }
+
+    fn hash_secret(&self, secret: &str) -> String {
+        Sha256::digest(secret.trim().as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    fn login_failed(&self, reason: &str) -> AuthenticationServiceError {
+        AuthenticationServiceError::client(AuthenticationClientError::ExternalLoginFailed(
+            reason.to_string(),
+        ))
+    }
+
+    pub fn get_providers(&self) -> Vec<ExternalProviderDto> {
+        self.external_login_config
+            .providers
+            .iter()
+            .map(ExternalProviderDto::from)
+            .collect()
+    }
+
+    fn get_provider(
+        &self,
+        provider_id: &str,
+    ) -> Result<&ExternalProviderConfiguration, AuthenticationServiceError> {
+        self.external_login_config
+            .providers
+            .iter()
+            .find(|provider| provider.id == provider_id)
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::ExternalProviderNotFound,
+            ))
+    }
+
+    async fn fetch_json<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<T, AuthenticationServiceError> {
+        self.http_client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(AuthenticationServiceError::from_error)?
+            .json::<T>()
+            .await
+            .map_err(AuthenticationServiceError::from_error)
+    }
+
+    async fn fetch_metadata(
+        &self,
+        provider: &ExternalProviderConfiguration,
+    ) -> Result<ProviderMetadata, AuthenticationServiceError> {
+        let issuer = provider.issuer.trim_end_matches('/');
+
+        if let (Some(authorization_endpoint), Some(token_endpoint), Some(jwks_uri)) = (
+            provider.authorization_endpoint.clone(),
+            provider.token_endpoint.clone(),
+            provider.jwks_uri.clone(),
+        ) {
+            return Ok(ProviderMetadata {
+                issuer: issuer.to_string(),
+                authorization_endpoint,
+                token_endpoint,
+                jwks_uri,
+            });
+        }
+
+        let mut metadata: ProviderMetadata = self
+            .fetch_json(&format!("{}{}", issuer, DISCOVERY_PATH))
+            .await?;
+
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(AuthenticationServiceError::ServerError(crate::log!(
+                tracing::error,
+                "{} Provider {} advertises issuer {} instead of {}",
+                SERVICE_NAME,
+                provider.id,
+                metadata.issuer,
+                issuer
+            )));
+        }
+
+        if let Some(authorization_endpoint) = provider.authorization_endpoint.clone() {
+            metadata.authorization_endpoint = authorization_endpoint;
+        }
+        if let Some(token_endpoint) = provider.token_endpoint.clone() {
+            metadata.token_endpoint = token_endpoint;
+        }
+        if let Some(jwks_uri) = provider.jwks_uri.clone() {
+            metadata.jwks_uri = jwks_uri;
+        }
+
+        Ok(metadata)
+    }
+
+    /// Returns the provider metadata and signing keys, refreshing them when the cache is stale.
+    async fn resolve_provider(
+        &self,
+        provider: &ExternalProviderConfiguration,
+        force_refresh: bool,
+    ) -> Result<CachedProvider, AuthenticationServiceError> {
+        let max_age = Duration::from_secs(self.external_login_config.metadata_cache_seconds);
+
+        if !force_refresh
+            && let Some(cached) = PROVIDER_CACHE
+                .lock()
+                .ok()
+                .and_then(|cache| cache.get(&provider.id).cloned())
+            && cached.fetched_at.elapsed() < max_age
+        {
+            return Ok(cached);
+        }
+
+        let metadata = self.fetch_metadata(provider).await?;
+        let jwks: JwkSet = self.fetch_json(&metadata.jwks_uri).await?;
+        let cached = CachedProvider {
+            metadata,
+            jwks,
+            fetched_at: Instant::now(),
+        };
+
+        if let Ok(mut cache) = PROVIDER_CACHE.lock() {
+            cache.insert(provider.id.clone(), cached.clone());
+        }
+
+        Ok(cached)
+    }
+
+    /// Starts a sign-in and returns the provider URI the user agent has to be redirected to.
+    pub async fn start(&self, provider_id: &str) -> Result<String, AuthenticationServiceError> {
+        let provider = self.get_provider(provider_id)?;
+        let cached = self.resolve_provider(provider, false).await?;
+
+        let state = self.generate_secret();
+        let nonce = self.generate_secret();
+        let code_verifier = self.generate_secret();
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::seconds(self.external_login_config.state_expiration_seconds as i64);
+
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE expires_at < time::now()")
+            .bind(("table", ExternalLoginStateModel::table_name()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let _: Vec<ExternalLoginStateModel> = self
+            .database_connection
+            .insert(ExternalLoginStateModel::table_name())
+            .content(CreateExternalLoginStateOptions {
+                state_hash: self.hash_secret(&state),
+                provider: provider.id.clone(),
+                nonce: nonce.clone(),
+                code_verifier,
+                expires_at: BaseDateTime::from(expires_at),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let mut redirect_to = url::Url::parse(&cached.metadata.authorization_endpoint)
+            .map_err(AuthenticationServiceError::from_error)?;
+        redirect_to
+            .query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &provider.client_id)
+            .append_pair("redirect_uri", &provider.redirect_uri)
+            .append_pair("scope", &provider.scopes.join(" "))
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(redirect_to.to_string())
+    }
+
+    async fn consume_state(
+        &self,
+        provider_id: &str,
+        state: &str,
+    ) -> Result<ExternalLoginStateModel, AuthenticationServiceError> {
+        let states: Vec<ExternalLoginStateModel> = self
+            .database_connection
+            .query("DELETE FROM type::table($table) WHERE state_hash = $state_hash RETURN BEFORE")
+            .bind(("table", ExternalLoginStateModel::table_name()))
+            .bind(("state_hash", self.hash_secret(state)))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        self.check_login_state(states.into_iter().next(), provider_id)
+    }
+
+    /// Consumed states are deleted, so a replayed callback finds none.
+    fn check_login_state(
+        &self,
+        login_state: Option<ExternalLoginStateModel>,
+        provider_id: &str,
+    ) -> Result<ExternalLoginStateModel, AuthenticationServiceError> {
+        login_state
+            .filter(|login_state| {
+                login_state.provider == provider_id
+                    && login_state.expires_at > BaseDateTime::from(chrono::Utc::now())
+            })
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidExternalLoginState,
+            ))
+    }
+
+    async fn exchange_code(
+        &self,
+        provider: &ExternalProviderConfiguration,
+        metadata: &ProviderMetadata,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<String, AuthenticationServiceError> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("client_id", provider.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(client_secret) = provider.client_secret.as_deref() {
+            form.push(("client_secret", client_secret));
+        }
+
+        let response = self
+            .http_client
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        if !response.status().is_success() {
+            tracing::debug!(
+                "{} Token endpoint of {} responded with {}",
+                SERVICE_NAME,
+                provider.id,
+                response.status()
+            );
+            return Err(self.login_failed("the provider rejected the authorization code"));
+        }
+
+        response
+            .json::<ProviderTokenResponse>()
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .id_token
+            .ok_or_else(|| self.login_failed("the provider did not return an id token"))
+    }
+
+    async fn validate_id_token(
+        &self,
+        provider: &ExternalProviderConfiguration,
+        cached: CachedProvider,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<ExternalIdTokenClaims, AuthenticationServiceError> {
+        let header = jsonwebtoken::decode_header(id_token)
+            .map_err(|_| self.login_failed("the id token is malformed"))?;
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            return Err(self.login_failed("the id token uses an unsupported algorithm"));
+        }
+
+        let find_key = |jwks: &JwkSet| match header.kid.as_deref() {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+
+        // The provider may have rotated its keys since they were cached
+        let jwk = match find_key(&cached.jwks) {
+            Some(jwk) => jwk,
+            None => {
+                let refreshed = self.resolve_provider(provider, true).await?;
+                find_key(&refreshed.jwks)
+                    .ok_or_else(|| self.login_failed("the id token signing key is unknown"))?
+            }
+        };
+
+        let key = DecodingKey::from_jwk(&jwk).map_err(AuthenticationServiceError::from_error)?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[cached.metadata.issuer.as_str()]);
+        validation.set_audience(&[provider.client_id.as_str()]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = jsonwebtoken::decode::<ExternalIdTokenClaims>(id_token, &key, &validation)
+            .map_err(|e| {
+                tracing::debug!("{} Rejected id token: {}", SERVICE_NAME, e);
+                self.login_failed("the id token is invalid")
+            })?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(self.login_failed("the id token nonce does not match"));
+        }
+
+        Ok(claims)
+    }
+
+    /// Exchanges the authorization code and returns the claims of the validated id token.
+    async fn verified_claims(
+        &self,
+        provider: &ExternalProviderConfiguration,
+        login_state: &ExternalLoginStateModel,
+        code: &str,
+    ) -> Result<ExternalIdTokenClaims, AuthenticationServiceError> {
+        let cached = self.resolve_provider(provider, false).await?;
+
+        let id_token = self
+            .exchange_code(provider, &cached.metadata, code, &login_state.code_verifier)
+            .await?;
+
+        self.validate_id_token(provider, cached, &id_token, &login_state.nonce)
+            .await
+    }
+
+    async fn get_identity(
+        &self,
+        provider_id: &str,
+        subject: &str,
+    ) -> Result<Option<IdentityModel>, AuthenticationServiceError> {
+        let identities: Vec<IdentityModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE provider = $provider AND subject = $subject")
+            .bind(("table", IdentityModel::table_name()))
+            .bind(("provider", provider_id.to_string()))
+            .bind(("subject", subject.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(identities.into_iter().next())
+    }
+
+    async fn touch_identity(
+        &self,
+        identity_id: &BaseId,
+        email: Option<String>,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("UPDATE $id SET email = $email, last_used_at = time::now()")
+            .bind(("id", identity_id.clone()))
+            .bind(("email", email))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    async fn available_username(
+        &self,
+        account_service: &AccountService,
+        provider: &ExternalProviderConfiguration,
+        claims: &ExternalIdTokenClaims,
+    ) -> Result<String, AuthenticationServiceError> {
+        let base_username = claims
+            .preferred_username
+            .clone()
+            .or_else(|| {
+                claims
+                    .email
+                    .as_deref()
+                    .and_then(|email| email.split_once('@'))
+                    .map(|(local, _)| local.to_string())
+            })
+            .map(|username| username.trim().to_string())
+            .filter(|username| !username.is_empty())
+            .unwrap_or_else(|| format!("{}_{}", provider.id, claims.sub));
+
+        if !account_service.exists_username(&base_username).await? {
+            return Ok(base_username);
+        }
+
+        for _ in 0..MAX_USERNAME_ATTEMPTS {
+            use rand::Rng;
+            let suffix: u16 = rand::rng().random();
+            let username = format!("{}-{:04x}", base_username, suffix);
+            if !account_service.exists_username(&username).await? {
+                return Ok(username);
+            }
+        }
+
+        Err(AuthenticationServiceError::client(
+            AuthenticationClientError::AccountAlreadyExists,
+        ))
+    }
+
+    /// Existing accounts are never linked by email, the owner could be someone else.
+    fn provisioning_email(
+        &self,
+        provider: &ExternalProviderConfiguration,
+        email: Option<String>,
+        email_in_use: bool,
+    ) -> Option<String> {
+        if email.is_some() && email_in_use {
+            tracing::info!(
+                "{} Email of new {} identity is already in use, provisioning without email",
+                SERVICE_NAME,
+                provider.id
+            );
+            return None;
+        }
+
+        email
+    }
+
+    /// Creates an account for an external identity that signs in for the first time.
+    async fn provision_account(
+        &self,
+        account_service: &AccountService,
+        password_service: &PasswordService,
+        provider: &ExternalProviderConfiguration,
+        claims: &ExternalIdTokenClaims,
+    ) -> Result<AccountModel, AuthenticationServiceError> {
+        let username = self
+            .available_username(account_service, provider, claims)
+            .await?;
+
+        let normalized_email = claims
+            .email
+            .as_deref()
+            .and_then(|email| account_service.normalize_email(email).ok());
+        let email_in_use = match normalized_email.as_deref() {
+            Some(email) => account_service.exists_email(email).await?,
+            None => false,
+        };
+        let email = self.provisioning_email(provider, normalized_email, email_in_use);
+
+        // External accounts start without a usable password, a reset sets one
+        let account = account_service
+            .create_account(
+                password_service,
+                CreateAccountRequestDTO {
+                    username,
+                    password: self.generate_secret(),
+                    email: email.clone(),
+                },
+            )
+            .await?;
+
+        if let Some(email) = email.as_deref()
+            && claims.is_email_verified()
+        {
+            account_service
+                .mark_email_verified(&account.id, email)
+                .await?;
+        }
+
+        let _: Vec<IdentityModel> = self
+            .database_connection
+            .insert(IdentityModel::table_name())
+            .content(CreateIdentityOptions {
+                account_id: account.id.clone(),
+                provider: provider.id.clone(),
+                subject: claims.sub.clone(),
+                email: claims.email.clone(),
+                last_used_at: Some(BaseDateTime::from(chrono::Utc::now())),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        account_service.get_account_by_id(&account.id).await
+    }
+
+    /// Completes a sign-in and returns the linked, possibly just provisioned, account.
+    pub async fn finish(
+        &self,
+        account_service: &AccountService,
+        password_service: &PasswordService,
+        provider_id: &str,
+        callback: ExternalCallbackRequestDto,
+    ) -> Result<AccountModel, AuthenticationServiceError> {
+        let provider = self.get_provider(provider_id)?;
+        let login_state = self.consume_state(&provider.id, &callback.state).await?;
+        let claims = self
+            .verified_claims(provider, &login_state, &callback.code)
+            .await?;
+
+        if let Some(identity) = self.get_identity(&provider.id, &claims.sub).await? {
+            self.touch_identity(&identity.id, claims.email.clone())
+                .await?;
+            return account_service
+                .get_account_by_id(&identity.account_id)
+                .await;
+        }
+
+        if !self.external_login_config.allow_signup {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::ExternalIdentityNotLinked,
+            ));
+        }
+
+        self.provision_account(account_service, password_service, provider, &claims)
+            .await
+    }
+
+    pub async fn get_identities_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<Vec<IdentityModel>, AuthenticationServiceError> {
+        let identities: Vec<IdentityModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE account_id = $account_id ORDER BY created_at ASC")
+            .bind(("table", IdentityModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(identities)
+    }
+
+    pub async fn delete_all_identities_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE account_id = $account_id")
+            .bind(("table", IdentityModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+    use ring::{
+        rand::SystemRandom,
+        signature::{ECDSA_P256_SHA256_ASN1_SIGNING, EcdsaKeyPair, KeyPair},
+    };
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const ISSUER: &str = "https://idp.example";
+    const CLIENT_ID: &str = "core-client";
+    const NONCE: &str = "test-nonce";
+
+    struct SigningKey {
+        kid: String,
+        pkcs8: Vec<u8>,
+        jwk: serde_json::Value,
+    }
+
+    impl SigningKey {
+        fn generate(kid: &str) -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng)
+                .unwrap()
+                .as_ref()
+                .to_vec();
+            let key_pair =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &pkcs8, &rng).unwrap();
+            let point = key_pair.public_key().as_ref();
+
+            Self {
+                kid: kid.to_string(),
+                jwk: serde_json::json!({
+                    "kty": "EC",
+                    "crv": "P-256",
+                    "alg": "ES256",
+                    "use": "sig",
+                    "kid": kid,
+                    "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+                    "y": URL_SAFE_NO_PAD.encode(&point[33..65]),
+                }),
+                pkcs8,
+            }
+        }
+
+        fn sign(&self, claims: &serde_json::Value) -> String {
+            let mut header = Header::new(Algorithm::ES256);
+            header.kid = Some(self.kid.clone());
+            jsonwebtoken::encode(&header, claims, &EncodingKey::from_ec_der(&self.pkcs8)).unwrap()
+        }
+    }
+
+    fn jwks(keys: &[&SigningKey]) -> serde_json::Value {
+        serde_json::json!({ "keys": keys.iter().map(|key| key.jwk.clone()).collect::<Vec<_>>() })
+    }
+
+    fn claims(issuer: &str, audience: &str, nonce: &str) -> serde_json::Value {
+        serde_json::json!({
+            "iss": issuer,
+            "aud": audience,
+            "sub": "external-subject",
+            "exp": chrono::Utc::now().timestamp() + 300,
+            "nonce": nonce,
+            "email": "Someone@Example.com",
+            "email_verified": true,
+        })
+    }
+
+    fn provider(id: &str, issuer: &str) -> ExternalProviderConfiguration {
+        ExternalProviderConfiguration {
+            id: id.to_string(),
+            name: id.to_string(),
+            issuer: issuer.to_string(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: None,
+            redirect_uri: "http://localhost:3000/callback".to_string(),
+            scopes: vec!["openid".to_string()],
+            authorization_endpoint: None,
+            token_endpoint: None,
+            jwks_uri: None,
+        }
+    }
+
+    fn cached_provider(issuer: &str, keys: &[&SigningKey]) -> CachedProvider {
+        CachedProvider {
+            metadata: ProviderMetadata {
+                issuer: issuer.to_string(),
+                authorization_endpoint: format!("{}/authorize", issuer),
+                token_endpoint: format!("{}/token", issuer),
+                jwks_uri: format!("{}/jwks", issuer),
+            },
+            jwks: serde_json::from_value(jwks(keys)).unwrap(),
+            fetched_at: Instant::now(),
+        }
+    }
+
+    fn login_state(provider_id: &str, expires_in_seconds: i64) -> ExternalLoginStateModel {
+        ExternalLoginStateModel {
+            id: surrealdb::RecordId::from((ExternalLoginStateModel::table_name(), "test")),
+            state_hash: "hash".to_string(),
+            provider: provider_id.to_string(),
+            nonce: NONCE.to_string(),
+            code_verifier: "test-verifier".to_string(),
+            expires_at: BaseDateTime::from(
+                chrono::Utc::now() + chrono::Duration::seconds(expires_in_seconds),
+            ),
+            created_at: BaseDateTime::from(chrono::Utc::now()),
+        }
+    }
+
+    fn service() -> ExternalLoginService {
+        ExternalLoginService::new(
+            ExternalLoginConfiguration::default(),
+            DatabaseConnection::init(),
+        )
+        .unwrap()
+    }
+
+    fn client_error<T>(result: Result<T, AuthenticationServiceError>) -> AuthenticationClientError {
+        match result {
+            Err(AuthenticationServiceError::ClientError(client_error)) => client_error,
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("the sign-in was accepted"),
+        }
+    }
+
+    fn failure_reason<T>(result: Result<T, AuthenticationServiceError>) -> String {
+        match client_error(result) {
+            AuthenticationClientError::ExternalLoginFailed(reason) => reason,
+            other => panic!("unexpected client error: {}", other),
+        }
+    }
+
+    #[derive(Default)]
+    struct MockProvider {
+        jwks: serde_json::Value,
+        id_token: String,
+        jwks_requests: usize,
+        token_requests: Vec<String>,
+    }
+
+    /// Serves discovery, keys and the token endpoint of a provider on a local port.
+    async fn serve(mock: Arc<Mutex<MockProvider>>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let base_url = issuer.clone();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mock = mock.clone();
+                let issuer = issuer.clone();
+                tokio::spawn(async move {
+                    let (path, body) = read_request(&mut stream).await;
+                    let response = {
+                        let mut mock = mock.lock().unwrap();
+                        match path.as_str() {
+                            DISCOVERY_PATH => serde_json::json!({
+                                "issuer": issuer,
+                                "authorization_endpoint": format!("{}/authorize", issuer),
+                                "token_endpoint": format!("{}/token", issuer),
+                                "jwks_uri": format!("{}/jwks", issuer),
+                            }),
+                            "/jwks" => {
+                                mock.jwks_requests += 1;
+                                mock.jwks.clone()
+                            }
+                            "/token" => {
+                                mock.token_requests.push(body);
+                                serde_json::json!({ "id_token": mock.id_token })
+                            }
+                            _ => serde_json::Value::Null,
+                        }
+                        .to_string()
+                    };
+                    let _ = stream
+                        .write_all(
+                            format!(
+                                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                                response.len(),
+                                response
+                            )
+                            .as_bytes(),
+                        )
+                        .await;
+                });
+            }
+        });
+
+        base_url
+    }
+
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> (String, String) {
+        let mut request = Vec::new();
+        let mut buffer = [0; 4096];
+        loop {
+            let read = stream.read(&mut buffer).await.unwrap();
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+
+            let text = String::from_utf8_lossy(&request);
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                let content_length = text[..header_end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if request.len() >= header_end + 4 + content_length {
+                    break;
+                }
+            }
+        }
+
+        let text = String::from_utf8_lossy(&request).to_string();
+        let (head, body) = text.split_once("\r\n\r\n").unwrap_or((&text, ""));
+        let path = head.split_whitespace().nth(1).unwrap_or_default();
+        (path.to_string(), body.to_string())
+    }
+
+    #[tokio::test]
+    async fn accepts_valid_id_token() {
+        let key = SigningKey::generate("current");
+        let id_token = key.sign(&claims(ISSUER, CLIENT_ID, NONCE));
+
+        let claims = service()
+            .validate_id_token(
+                &provider("valid", ISSUER),
+                cached_provider(ISSUER, &[&key]),
+                &id_token,
+                NONCE,
+            )
+            .await
+            .unwrap();
+        assert_eq!(claims.sub, "external-subject");
+        assert!(claims.is_email_verified());
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_nonce_issuer_and_audience() {
+        let service = service();
+        let provider = provider("mismatch", ISSUER);
+        let key = SigningKey::generate("current");
+
+        let wrong_nonce = key.sign(&claims(ISSUER, CLIENT_ID, "other-nonce"));
+        assert_eq!(
+            failure_reason(
+                service
+                    .validate_id_token(
+                        &provider,
+                        cached_provider(ISSUER, &[&key]),
+                        &wrong_nonce,
+                        NONCE
+                    )
+                    .await
+            ),
+            "the id token nonce does not match"
+        );
+
+        let wrong_issuer = key.sign(&claims("https://attacker.example", CLIENT_ID, NONCE));
+        let wrong_audience = key.sign(&claims(ISSUER, "other-client", NONCE));
+        let mut expired_claims = claims(ISSUER, CLIENT_ID, NONCE);
+        expired_claims["exp"] = serde_json::json!(chrono::Utc::now().timestamp() - 3600);
+        let expired = key.sign(&expired_claims);
+
+        for id_token in [wrong_issuer, wrong_audience, expired] {
+            assert_eq!(
+                failure_reason(
+                    service
+                        .validate_id_token(
+                            &provider,
+                            cached_provider(ISSUER, &[&key]),
+                            &id_token,
+                            NONCE
+                        )
+                        .await
+                ),
+                "the id token is invalid"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn refreshes_keys_for_unknown_kid() {
+        let old_key = SigningKey::generate("old");
+        let new_key = SigningKey::generate("new");
+        let mock = Arc::new(Mutex::new(MockProvider {
+            jwks: jwks(&[&new_key]),
+            ..MockProvider::default()
+        }));
+        let issuer = serve(mock.clone()).await;
+        let provider = provider("rotated", &issuer);
+        let service = service();
+
+        let id_token = new_key.sign(&claims(&issuer, CLIENT_ID, NONCE));
+        let validated = service
+            .validate_id_token(
+                &provider,
+                cached_provider(&issuer, &[&old_key]),
+                &id_token,
+                NONCE,
+            )
+            .await
+            .unwrap();
+        assert_eq!(validated.sub, "external-subject");
+        assert_eq!(mock.lock().unwrap().jwks_requests, 1);
+
+        // A key the provider does not publish even after the refresh is rejected
+        let unknown_key = SigningKey::generate("unknown");
+        let id_token = unknown_key.sign(&claims(&issuer, CLIENT_ID, NONCE));
+        assert_eq!(
+            failure_reason(
+                service
+                    .validate_id_token(
+                        &provider,
+                        cached_provider(&issuer, &[&old_key]),
+                        &id_token,
+                        NONCE
+                    )
+                    .await
+            ),
+            "the id token signing key is unknown"
+        );
+        assert_eq!(mock.lock().unwrap().jwks_requests, 2);
+    }
+
+    #[tokio::test]
+    async fn exchanges_code_for_validated_claims() {
+        let key = SigningKey::generate("current");
+        let mock = Arc::new(Mutex::new(MockProvider {
+            jwks: jwks(&[&key]),
+            ..MockProvider::default()
+        }));
+        let issuer = serve(mock.clone()).await;
+        let provider = provider("callback", &issuer);
+        let service = service();
+        mock.lock().unwrap().id_token = key.sign(&claims(&issuer, CLIENT_ID, NONCE));
+
+        let claims = service
+            .verified_claims(&provider, &login_state("callback", 60), "test-code")
+            .await
+            .unwrap();
+        assert_eq!(claims.sub, "external-subject");
+
+        let token_request = mock.lock().unwrap().token_requests[0].clone();
+        assert!(token_request.contains("code=test-code"));
+        assert!(token_request.contains("code_verifier=test-verifier"));
+
+        // The token has to carry the nonce stored with the state of this sign-in
+        let mut other_state = login_state("callback", 60);
+        other_state.nonce = "other-nonce".to_string();
+        assert_eq!(
+            failure_reason(
+                service
+                    .verified_claims(&provider, &other_state, "test-code")
+                    .await
+            ),
+            "the id token nonce does not match"
+        );
+    }
+
+    #[test]
+    fn rejects_replayed_expired_and_foreign_states() {
+        let service = service();
+
+        assert!(
+            service
+                .check_login_state(Some(login_state("state", 60)), "state")
+                .is_ok()
+        );
+
+        for (login_state, provider_id) in [
+            // A replayed callback finds the state already deleted
+            (None, "state"),
+            (Some(login_state("state", -1)), "state"),
+            (Some(login_state("other", 60)), "state"),
+        ] {
+            assert!(matches!(
+                client_error(service.check_login_state(login_state, provider_id)),
+                AuthenticationClientError::InvalidExternalLoginState
+            ));
+        }
+    }
+
+    #[test]
+    fn never_links_existing_accounts_by_email() {
+        let service = service();
+        let provider = provider("email", ISSUER);
+        let email = Some("someone@example.com".to_string());
+
+        assert_eq!(
+            service.provisioning_email(&provider, email.clone(), true),
+            None
+        );
+        assert_eq!(
+            service.provisioning_email(&provider, email.clone(), false),
+            email
+        );
+        assert_eq!(service.provisioning_email(&provider, None, false), None);
+    }
+}
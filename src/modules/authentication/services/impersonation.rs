@@ -0,0 +1,140 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            dtos::{
+                authentication::AuthenticationResponseDto,
+                impersonation::CreateImpersonationEventOptions,
+            },
+            errors::service::*,
+            models::{
+                account::AccountModel, impersonation_event::ImpersonationEventModel,
+                session::SessionModel,
+            },
+            services::{account::AccountService, session::SessionService, token::TokenService},
+        },
+        base::exports::{BaseId, DatabaseConnection, request_info::RequestInfoExtractor},
+    },
+};
+
+const ACTION_STARTED: &str = "started";
+const ACTION_STOPPED: &str = "stopped";
+
+pub struct ImpersonationService {
+    database_connection: DatabaseConnection,
+}
+
+impl ImpersonationService {
+    pub fn new(database_connection: DatabaseConnection) -> Self {
+        Self {
+            database_connection,
+        }
+    }
+
+    async fn record_event(
+        &self,
+        actor_id: &BaseId,
+        account_id: &BaseId,
+        session_id: &BaseId,
+        action: &str,
+        ip_address: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        tracing::info!(
+            "Impersonation of account {} by {} {} (session {})",
+            AccountModel::to_named_format(account_id),
+            AccountModel::to_named_format(actor_id),
+            action,
+            SessionModel::to_named_format(session_id)
+        );
+
+        let _: Vec<ImpersonationEventModel> = self
+            .database_connection
+            .insert(ImpersonationEventModel::table_name())
+            .content(CreateImpersonationEventOptions {
+                actor_id: actor_id.clone(),
+                account_id: account_id.clone(),
+                session_id: session_id.clone(),
+                action: action.to_string(),
+                ip_address: ip_address.to_string(),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    /// Opens a session acting as `account_id`, whether the actor may impersonate that account
+    /// is up to the caller.
+    pub async fn start(
+        &self,
+        account_service: &AccountService,
+        session_service: &SessionService,
+        token_service: &TokenService,
+        actor_id: &BaseId,
+        account_id: &BaseId,
+        request_info: RequestInfoExtractor,
+    ) -> Result<AuthenticationResponseDto, AuthenticationServiceError> {
+        if actor_id == account_id {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::CannotImpersonate,
+            ));
+        }
+
+        let account = account_service.get_account_by_id(account_id).await?;
+
+        let ip_address = request_info.ip_address.clone();
+        let auth_response = session_service
+            .create_impersonation_session(token_service, &account.id, actor_id, request_info)
+            .await?;
+
+        let session_id = SessionModel::from_named_format(&auth_response.session_id).ok_or(
+            AuthenticationServiceError::client(AuthenticationClientError::InvalidSessionId),
+        )?;
+        self.record_event(
+            actor_id,
+            &account.id,
+            &session_id,
+            ACTION_STARTED,
+            &ip_address,
+        )
+        .await?;
+
+        Ok(auth_response)
+    }
+
+    /// Ends an impersonation session, the session is deactivated rather than deleted so the
+    /// events keep pointing at it.
+    pub async fn stop(
+        &self,
+        session_service: &SessionService,
+        actor_id: &BaseId,
+        account_id: &BaseId,
+        session_id: &BaseId,
+        ip_address: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        session_service
+            .deactivate_session_for_account(session_id, account_id)
+            .await?;
+
+        self.record_event(actor_id, account_id, session_id, ACTION_STOPPED, ip_address)
+            .await
+    }
+
+    /// Events where the account was impersonated or impersonated someone, newest first.
+    pub async fn get_events_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<Vec<ImpersonationEventModel>, AuthenticationServiceError> {
+        let events: Vec<ImpersonationEventModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE account_id = $account_id OR actor_id = $account_id ORDER BY created_at DESC")
+            .bind(("table", ImpersonationEventModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(events)
+    }
+}
@@ -0,0 +1,282 @@
+use crate::modules::authentication::{
+    config::authentication::JwtAlgorithm, errors::service::AuthenticationServiceError,
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters,
+        EllipticCurveKeyType, Jwk, KeyAlgorithm, OctetKeyPairParameters, OctetKeyPairType,
+        PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+};
+use ring::{
+    rand::SystemRandom,
+    signature::{
+        ECDSA_P256_SHA256_FIXED_SIGNING, EcdsaKeyPair, Ed25519KeyPair, KeyPair, RsaKeyPair,
+        RsaPublicKeyComponents,
+    },
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, RwLock},
+    time::{Duration, Instant},
+};
+
+const PKCS8_PEM_TAG: &str = "PRIVATE KEY";
+const PKCS1_PEM_TAG: &str = "RSA PRIVATE KEY";
+
+/// Process wide copy of the keyring stored in the database, token signing stays synchronous.
+static KEYRING: LazyLock<RwLock<Keyring>> = LazyLock::new(|| RwLock::new(Keyring::default()));
+
+#[derive(Default)]
+struct Keyring {
+    active_kid: Option<String>,
+    keys: HashMap<String, Arc<SigningKey>>,
+    loaded_at: Option<Instant>,
+}
+
+pub struct SigningKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub encoding_key: EncodingKey,
+    pub decoding_key: DecodingKey,
+    /// Public half of an asymmetric key, shared keys are never published.
+    pub jwk: Option<Jwk>,
+}
+
+impl SigningKey {
+    /// Builds a key from its material, a PEM private key or the shared secret for HS256.
+    pub fn new(kid: String, algorithm: JwtAlgorithm, key_material: &str) -> anyhow::Result<Self> {
+        if algorithm == JwtAlgorithm::HS256 {
+            if key_material.is_empty() {
+                anyhow::bail!("HS256 secret must not be empty");
+            }
+
+            return Ok(Self {
+                kid,
+                algorithm: Algorithm::HS256,
+                encoding_key: EncodingKey::from_secret(key_material.as_bytes()),
+                decoding_key: DecodingKey::from_secret(key_material.as_bytes()),
+                jwk: None,
+            });
+        }
+
+        let pem = pem::parse(key_material)?;
+        let der = pem.contents();
+
+        let (encoding_key, parameters) = match algorithm {
+            JwtAlgorithm::HS256 => unreachable!(),
+            JwtAlgorithm::RS256 => {
+                let key_pair = match pem.tag() {
+                    PKCS1_PEM_TAG => RsaKeyPair::from_der(der),
+                    PKCS8_PEM_TAG => RsaKeyPair::from_pkcs8(der),
+                    tag => anyhow::bail!("Unsupported RSA key format '{}'", tag),
+                }
+                .map_err(|e| anyhow::anyhow!("Invalid RSA private key: {}", e))?;
+                let public_key = RsaPublicKeyComponents::<Vec<u8>>::from(key_pair.public());
+
+                (
+                    EncodingKey::from_rsa_pem(key_material.as_bytes())?,
+                    AlgorithmParameters::RSA(RSAKeyParameters {
+                        key_type: RSAKeyType::RSA,
+                        n: URL_SAFE_NO_PAD.encode(public_key.n),
+                        e: URL_SAFE_NO_PAD.encode(public_key.e),
+                    }),
+                )
+            }
+            JwtAlgorithm::ES256 => {
+                let key_pair = EcdsaKeyPair::from_pkcs8(
+                    &ECDSA_P256_SHA256_FIXED_SIGNING,
+                    der,
+                    &SystemRandom::new(),
+                )
+                .map_err(|e| anyhow::anyhow!("Invalid P-256 private key: {}", e))?;
+                // Uncompressed point, a 0x04 marker followed by the x and y coordinates
+                let point = key_pair.public_key().as_ref();
+                let (x, y) = point[1..].split_at(32);
+
+                (
+                    EncodingKey::from_ec_der(der),
+                    AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+                        key_type: EllipticCurveKeyType::EC,
+                        curve: EllipticCurve::P256,
+                        x: URL_SAFE_NO_PAD.encode(x),
+                        y: URL_SAFE_NO_PAD.encode(y),
+                    }),
+                )
+            }
+            JwtAlgorithm::EdDSA => {
+                let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der)
+                    .map_err(|e| anyhow::anyhow!("Invalid Ed25519 private key: {}", e))?;
+
+                (
+                    EncodingKey::from_ed_der(der),
+                    AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                        key_type: OctetKeyPairType::OctetKeyPair,
+                        curve: EllipticCurve::Ed25519,
+                        x: URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref()),
+                    }),
+                )
+            }
+        };
+
+        let jwk = Jwk {
+            common: CommonParameters {
+                public_key_use: Some(PublicKeyUse::Signature),
+                key_algorithm: Some(algorithm.key_algorithm()),
+                key_id: Some(kid.clone()),
+                ..Default::default()
+            },
+            algorithm: parameters,
+        };
+
+        Ok(Self {
+            kid,
+            algorithm: algorithm.algorithm(),
+            encoding_key,
+            decoding_key: DecodingKey::from_jwk(&jwk)?,
+            jwk: Some(jwk),
+        })
+    }
+}
+
+impl JwtAlgorithm {
+    pub fn algorithm(self) -> Algorithm {
+        match self {
+            JwtAlgorithm::HS256 => Algorithm::HS256,
+            JwtAlgorithm::RS256 => Algorithm::RS256,
+            JwtAlgorithm::ES256 => Algorithm::ES256,
+            JwtAlgorithm::EdDSA => Algorithm::EdDSA,
+        }
+    }
+
+    fn key_algorithm(self) -> KeyAlgorithm {
+        match self {
+            JwtAlgorithm::HS256 => KeyAlgorithm::HS256,
+            JwtAlgorithm::RS256 => KeyAlgorithm::RS256,
+            JwtAlgorithm::ES256 => KeyAlgorithm::ES256,
+            JwtAlgorithm::EdDSA => KeyAlgorithm::EdDSA,
+        }
+    }
+
+    /// Generates new key material, RSA keys cannot be generated and have to be imported.
+    pub fn generate_key_material(self) -> anyhow::Result<String> {
+        let rng = SystemRandom::new();
+        let pkcs8 = match self {
+            JwtAlgorithm::HS256 => {
+                use rand::Rng;
+                let secret: [u8; 32] = rand::rng().random();
+                return Ok(URL_SAFE_NO_PAD.encode(secret));
+            }
+            JwtAlgorithm::RS256 => anyhow::bail!("RS256 keys must be imported as PEM"),
+            JwtAlgorithm::ES256 => {
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+            }
+            JwtAlgorithm::EdDSA => Ed25519KeyPair::generate_pkcs8(&rng),
+        }
+        .map_err(|e| anyhow::anyhow!("Key generation failed: {}", e))?;
+
+        Ok(pem::encode(&pem::Pem::new(
+            PKCS8_PEM_TAG,
+            pkcs8.as_ref().to_vec(),
+        )))
+    }
+}
+
+pub fn generate_kid() -> String {
+    use rand::Rng;
+    let kid: [u8; 12] = rand::rng().random();
+    URL_SAFE_NO_PAD.encode(kid)
+}
+
+/// Swaps in a freshly loaded keyring.
+pub fn replace(active_kid: Option<String>, keys: Vec<SigningKey>) {
+    let keys = keys
+        .into_iter()
+        .map(|key| (key.kid.clone(), Arc::new(key)))
+        .collect();
+
+    if let Ok(mut keyring) = KEYRING.write() {
+        *keyring = Keyring {
+            active_kid,
+            keys,
+            loaded_at: Some(Instant::now()),
+        };
+    }
+}
+
+/// Returns true for exactly one caller once the keyring is older than `max_age`.
+pub fn claim_refresh(max_age: Duration) -> bool {
+    let Ok(mut keyring) = KEYRING.write() else {
+        return false;
+    };
+
+    if keyring
+        .loaded_at
+        .is_some_and(|loaded_at| loaded_at.elapsed() < max_age)
+    {
+        return false;
+    }
+
+    keyring.loaded_at = Some(Instant::now());
+    true
+}
+
+pub fn active_key() -> Result<Arc<SigningKey>, AuthenticationServiceError> {
+    let keyring = KEYRING
+        .read()
+        .map_err(|e| AuthenticationServiceError::ServerError(anyhow::anyhow!("{}", e)))?;
+
+    keyring
+        .active_kid
+        .as_ref()
+        .and_then(|kid| keyring.keys.get(kid))
+        .cloned()
+        .ok_or_else(|| {
+            AuthenticationServiceError::ServerError(crate::log!(
+                tracing::error,
+                "No active signing key in the keyring"
+            ))
+        })
+}
+
+/// Keys that may have signed a token, tokens without a kid predate the keyring and try every key.
+pub fn verification_keys(kid: Option<&str>) -> Vec<Arc<SigningKey>> {
+    let Ok(keyring) = KEYRING.read() else {
+        return Vec::new();
+    };
+
+    match kid {
+        Some(kid) => keyring.keys.get(kid).cloned().into_iter().collect(),
+        None => keyring.keys.values().cloned().collect(),
+    }
+}
+
+pub fn published_keys() -> Vec<Jwk> {
+    let Ok(keyring) = KEYRING.read() else {
+        return Vec::new();
+    };
+
+    let mut jwks: Vec<Jwk> = keyring
+        .keys
+        .values()
+        .filter_map(|key| key.jwk.clone())
+        .collect();
+    jwks.sort_by(|a, b| a.common.key_id.cmp(&b.common.key_id));
+    jwks
+}
+
+pub fn algorithms() -> Vec<Algorithm> {
+    let Ok(keyring) = KEYRING.read() else {
+        return Vec::new();
+    };
+
+    let mut algorithms: Vec<Algorithm> = Vec::new();
+    for key in keyring.keys.values() {
+        if !algorithms.contains(&key.algorithm) {
+            algorithms.push(key.algorithm);
+        }
+    }
+    algorithms
+}
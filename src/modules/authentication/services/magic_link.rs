@@ -0,0 +1,336 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            config::authentication::MagicLinkConfiguration,
+            dtos::magic_link::{CreateMagicLinkTokenOptions, MagicLinkVerifyRequestDto},
+            errors::service::*,
+            models::{account::AccountModel, magic_link_token::MagicLinkTokenModel},
+            services::{
+                account::AccountService,
+                mailer::{MailMessage, Mailer},
+            },
+        },
+        base::exports::{BaseDateTime, BaseId, DatabaseConnection},
+    },
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sha2::{Digest, Sha256};
+
+pub struct MagicLinkService {
+    database_connection: DatabaseConnection,
+    magic_link_config: MagicLinkConfiguration,
+}
+
+impl MagicLinkService {
+    pub fn new(
+        magic_link_config: MagicLinkConfiguration,
+        database_connection: DatabaseConnection,
+    ) -> Self {
+        Self {
+            database_connection,
+            magic_link_config,
+        }
+    }
+
+    fn ensure_enabled(&self) -> Result<(), AuthenticationServiceError> {
+        if !self.magic_link_config.enabled {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::MagicLinkDisabled,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn generate_token(&self) -> String {
+        use rand::Rng;
+        let token: [u8; 32] = rand::rng().random();
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    fn generate_code(&self) -> String {
+        use rand::Rng;
+        format!("{:06}", rand::rng().random_range(0..1_000_000))
+    }
+
+    fn hash_token(&self, token: &str) -> String {
+        Sha256::digest(token.trim().as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    fn window_start(&self) -> BaseDateTime {
+        BaseDateTime::from(
+            chrono::Utc::now()
+                - chrono::Duration::seconds(self.magic_link_config.request_window_seconds as i64),
+        )
+    }
+
+    /// Used tokens are kept until the request window has passed, they still count as requests.
+    async fn delete_stale_tokens(&self) -> Result<(), AuthenticationServiceError> {
+        let retention_seconds = self
+            .magic_link_config
+            .request_window_seconds
+            .max(self.magic_link_config.token_expiration_seconds);
+        let stale_before = chrono::Utc::now() - chrono::Duration::seconds(retention_seconds as i64);
+
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE created_at < $stale_before")
+            .bind(("table", MagicLinkTokenModel::table_name()))
+            .bind(("stale_before", BaseDateTime::from(stale_before)))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    async fn count_recent_requests(
+        &self,
+        condition: &str,
+        value: impl serde::Serialize + 'static,
+    ) -> Result<usize, AuthenticationServiceError> {
+        let count: Option<usize> = self
+            .database_connection
+            .query(format!(
+                "SELECT count() AS count FROM type::table($table) WHERE {} = $value AND created_at > $window_start GROUP ALL",
+                condition
+            ))
+            .bind(("table", MagicLinkTokenModel::table_name()))
+            .bind(("value", value))
+            .bind(("window_start", self.window_start()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take((0, "count"))
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(count.unwrap_or_default())
+    }
+
+    async fn invalidate_tokens_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("UPDATE type::table($table) SET expires_at = time::now() WHERE account_id = $account_id AND expires_at > time::now()")
+            .bind(("table", MagicLinkTokenModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    /// Mails a sign-in link and code to a verified address. Like password resets this never
+    /// reveals whether the account exists, only the per IP limit is reported.
+    pub async fn request_sign_in(
+        &self,
+        account_service: &AccountService,
+        mailer: &dyn Mailer,
+        ip_address: &str,
+        email: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.ensure_enabled()?;
+        self.delete_stale_tokens().await?;
+
+        let requests_for_ip = self
+            .count_recent_requests("ip_address", ip_address.to_string())
+            .await?;
+        if requests_for_ip >= self.magic_link_config.max_requests_per_ip {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::TooManyMagicLinkRequests,
+            ));
+        }
+
+        let email = account_service.normalize_email(email)?;
+        let account = match account_service.get_account_by_email(&email).await {
+            Ok(account) => account,
+            Err(AuthenticationServiceError::ClientError(
+                AuthenticationClientError::AccountNotFound,
+            )) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        if !account.email_verified || account.is_locked() {
+            tracing::warn!(
+                "Sign-in link requested for account {} that is locked or not verified",
+                AccountModel::to_named_format(&account.id)
+            );
+            return Ok(());
+        }
+
+        let requests_for_account = self
+            .count_recent_requests("account_id", account.id.clone())
+            .await?;
+        if requests_for_account >= self.magic_link_config.max_requests_per_account {
+            tracing::debug!(
+                "Sign-in link request limit reached for account {}",
+                AccountModel::to_named_format(&account.id)
+            );
+            return Ok(());
+        }
+
+        // Only the latest link and code are valid
+        self.invalidate_tokens_for_account(&account.id).await?;
+
+        let token = self.generate_token();
+        let code = self.generate_code();
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::seconds(self.magic_link_config.token_expiration_seconds as i64);
+
+        let _: Vec<MagicLinkTokenModel> = self
+            .database_connection
+            .insert(MagicLinkTokenModel::table_name())
+            .content(CreateMagicLinkTokenOptions {
+                account_id: account.id.clone(),
+                token_hash: self.hash_token(&token),
+                code_hash: self.hash_token(&code),
+                ip_address: ip_address.to_string(),
+                expires_at: BaseDateTime::from(expires_at),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let link_url = self.magic_link_config.link_url.replace("{token}", &token);
+        let message = MailMessage {
+            to: email,
+            subject: "Your sign-in link".to_string(),
+            body: format!(
+                "Use the following link to sign in:\n{}\n\nOr enter this code: {}\n\nThe link and code can be used once and expire in {} minutes. If you did not try to sign in you can ignore this mail.",
+                link_url,
+                code,
+                self.magic_link_config.token_expiration_seconds / 60
+            ),
+        };
+
+        if let Err(e) = mailer.send(message).await {
+            tracing::error!("Failed to send sign-in link mail: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Marks a live token as used, returns `None` if it was used or expired in the meantime.
+    async fn consume_token(
+        &self,
+        condition: &str,
+        value: impl serde::Serialize + 'static,
+    ) -> Result<Option<MagicLinkTokenModel>, AuthenticationServiceError> {
+        let tokens: Vec<MagicLinkTokenModel> = self
+            .database_connection
+            .query(format!(
+                "UPDATE type::table($table) SET expires_at = time::now() WHERE {} = $value AND expires_at > time::now() RETURN BEFORE",
+                condition
+            ))
+            .bind(("table", MagicLinkTokenModel::table_name()))
+            .bind(("value", value))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(tokens.into_iter().next())
+    }
+
+    async fn redeem_code(
+        &self,
+        account_service: &AccountService,
+        email: &str,
+        code: &str,
+    ) -> Result<Option<MagicLinkTokenModel>, AuthenticationServiceError> {
+        let email = account_service.normalize_email(email)?;
+        let account = match account_service.get_account_by_email(&email).await {
+            Ok(account) => account,
+            Err(AuthenticationServiceError::ClientError(
+                AuthenticationClientError::AccountNotFound,
+            )) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let tokens: Vec<MagicLinkTokenModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE account_id = $account_id AND expires_at > time::now() ORDER BY created_at DESC LIMIT 1")
+            .bind(("table", MagicLinkTokenModel::table_name()))
+            .bind(("account_id", account.id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let Some(token) = tokens.into_iter().next() else {
+            return Ok(None);
+        };
+
+        // Six digits are guessable, so every guess takes up an attempt before it is compared.
+        // Counting in the database keeps parallel guesses from sharing one.
+        let counted: Vec<MagicLinkTokenModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET code_attempts += 1 WHERE id = $id AND code_attempts < $max_attempts AND expires_at > time::now() RETURN AFTER")
+            .bind(("table", MagicLinkTokenModel::table_name()))
+            .bind(("id", token.id.clone()))
+            .bind(("max_attempts", self.magic_link_config.max_code_attempts))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let Some(token) = counted.into_iter().next() else {
+            return Ok(None);
+        };
+
+        if token.code_hash != self.hash_token(code) {
+            if token.code_attempts >= self.magic_link_config.max_code_attempts {
+                self.database_connection
+                    .query("UPDATE type::table($table) SET expires_at = time::now() WHERE id = $id")
+                    .bind(("table", MagicLinkTokenModel::table_name()))
+                    .bind(("id", token.id.clone()))
+                    .await
+                    .map_err(AuthenticationServiceError::from_error)?;
+            }
+
+            return Ok(None);
+        }
+
+        self.consume_token("id", token.id).await
+    }
+
+    /// Redeems a link or code, every other outstanding token of the account is invalidated too.
+    pub async fn redeem(
+        &self,
+        account_service: &AccountService,
+        verify: &MagicLinkVerifyRequestDto,
+    ) -> Result<AccountModel, AuthenticationServiceError> {
+        self.ensure_enabled()?;
+
+        let token = match (
+            verify.token.as_deref(),
+            verify.email.as_deref(),
+            verify.code.as_deref(),
+        ) {
+            (Some(token), _, _) => {
+                self.consume_token("token_hash", self.hash_token(token))
+                    .await?
+            }
+            (None, Some(email), Some(code)) => {
+                self.redeem_code(account_service, email, code).await?
+            }
+            _ => None,
+        }
+        .ok_or(AuthenticationServiceError::client(
+            AuthenticationClientError::InvalidMagicLink,
+        ))?;
+
+        self.invalidate_tokens_for_account(&token.account_id)
+            .await?;
+
+        let account = account_service.get_account_by_id(&token.account_id).await?;
+        if account.is_locked() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::AccountLocked,
+            ));
+        }
+
+        Ok(account)
+    }
+}
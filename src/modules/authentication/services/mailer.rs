@@ -0,0 +1,143 @@
+use crate::modules::authentication::{
+    config::authentication::{MailConfiguration, MailTransport},
+    errors::service::AuthenticationServiceError,
+};
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor, message::Mailbox,
+    transport::smtp::authentication::Credentials,
+};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct MailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait::async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: MailMessage) -> Result<(), AuthenticationServiceError>;
+}
+
+pub fn mailer_from_config(
+    mail_config: &MailConfiguration,
+) -> Result<Box<dyn Mailer>, AuthenticationServiceError> {
+    match mail_config.transport {
+        MailTransport::Smtp => Ok(Box::new(SmtpMailer::new(mail_config)?)),
+        MailTransport::File => Ok(Box::new(FileMailer::new(
+            &mail_config.from,
+            Some(PathBuf::from(&mail_config.file_directory)),
+        ))),
+        MailTransport::Stdout => Ok(Box::new(FileMailer::new(&mail_config.from, None))),
+    }
+}
+
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    pub fn new(mail_config: &MailConfiguration) -> Result<Self, AuthenticationServiceError> {
+        let from = mail_config
+            .from
+            .parse::<Mailbox>()
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let mut builder = if mail_config.smtp_starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&mail_config.smtp_host)
+                .map_err(AuthenticationServiceError::from_error)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&mail_config.smtp_host)
+        }
+        .port(mail_config.smtp_port);
+
+        if let (Some(username), Some(password)) = (
+            mail_config.smtp_username.as_ref(),
+            mail_config.smtp_password.as_ref(),
+        ) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            from,
+            transport: builder.build(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: MailMessage) -> Result<(), AuthenticationServiceError> {
+        let to = message
+            .to
+            .parse::<Mailbox>()
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(message.subject)
+            .body(message.body)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        self.transport
+            .send(email)
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+}
+
+/// Development transport, writes mails to a directory or to stdout when no directory is given.
+pub struct FileMailer {
+    from: String,
+    directory: Option<PathBuf>,
+}
+
+impl FileMailer {
+    pub fn new(from: &str, directory: Option<PathBuf>) -> Self {
+        Self {
+            from: from.to_string(),
+            directory,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, message: MailMessage) -> Result<(), AuthenticationServiceError> {
+        let content = format!(
+            "From: {}\nTo: {}\nSubject: {}\n\n{}\n",
+            self.from, message.to, message.subject, message.body
+        );
+
+        let Some(directory) = self.directory.as_ref() else {
+            tracing::warn!(
+                "Mail to {} is written to stdout, configure the smtp transport outside of development",
+                message.to
+            );
+            println!("{}", content);
+            return Ok(());
+        };
+
+        tokio::fs::create_dir_all(directory)
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let file_name = format!(
+            "{}-{}.eml",
+            chrono::Utc::now().format("%Y%m%d%H%M%S%3f"),
+            message
+                .to
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+        );
+        tokio::fs::write(directory.join(file_name), content)
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use crate::modules::authentication::{
+    config::authentication::MfaConfiguration,
+    dtos::mfa::{
+        MfaChallengeResponseDto, MfaVerifyRequestDto, RecoveryCodesResponseDto,
+        TotpEnrollmentResponseDto,
+    },
+    errors::service::*,
+    models::account::AccountModel,
+    services::{
+        account::AccountService, encryption::EncryptionService, token::TokenService,
+        totp::TotpService,
+    },
+};
+
+const TOTP_METHOD: &str = "totp";
+const RECOVERY_CODE_METHOD: &str = "recovery_code";
+
+pub struct MfaService {
+    mfa_config: MfaConfiguration,
+    totp_service: TotpService,
+}
+
+impl MfaService {
+    pub fn new(mfa_config: MfaConfiguration) -> Self {
+        let totp_service = TotpService::new(mfa_config.clone());
+
+        Self {
+            mfa_config,
+            totp_service,
+        }
+    }
+
+    fn encryption_service(&self) -> Result<EncryptionService, AuthenticationServiceError> {
+        if self.mfa_config.encryption_key.is_empty() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::TotpNotConfigured,
+            ));
+        }
+
+        EncryptionService::new(&self.mfa_config.encryption_key)
+    }
+
+    pub async fn begin_totp_enrollment(
+        &self,
+        account_service: &AccountService,
+        account: &AccountModel,
+    ) -> Result<TotpEnrollmentResponseDto, AuthenticationServiceError> {
+        if account.totp_enabled {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::MfaAlreadyEnabled,
+            ));
+        }
+
+        let secret = self.totp_service.generate_secret();
+        let provisioning_uri = self
+            .totp_service
+            .provisioning_uri(&secret, &account.username)?;
+
+        account_service
+            .set_totp_pending_secret(&account.id, self.encryption_service()?.encrypt(&secret)?)
+            .await?;
+
+        Ok(TotpEnrollmentResponseDto {
+            secret,
+            provisioning_uri,
+        })
+    }
+
+    pub async fn confirm_totp_enrollment(
+        &self,
+        account_service: &AccountService,
+        account: &AccountModel,
+        code: &str,
+    ) -> Result<RecoveryCodesResponseDto, AuthenticationServiceError> {
+        if account.totp_enabled {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::MfaAlreadyEnabled,
+            ));
+        }
+
+        let encrypted_secret =
+            account
+                .totp_pending_secret
+                .as_ref()
+                .ok_or(AuthenticationServiceError::client(
+                    AuthenticationClientError::MfaEnrollmentNotStarted,
+                ))?;
+        let secret = self.encryption_service()?.decrypt(encrypted_secret)?;
+
+        let step = self.totp_service.verify_code(&secret, code, None)?.ok_or(
+            AuthenticationServiceError::client(AuthenticationClientError::InvalidMfaCode),
+        )?;
+
+        let recovery_codes = self.totp_service.generate_recovery_codes();
+        let recovery_code_hashes = recovery_codes
+            .iter()
+            .map(|recovery_code| self.totp_service.hash_recovery_code(recovery_code))
+            .collect();
+
+        account_service
+            .enable_totp(
+                &account.id,
+                encrypted_secret.clone(),
+                recovery_code_hashes,
+                step,
+            )
+            .await?;
+
+        Ok(RecoveryCodesResponseDto { recovery_codes })
+    }
+
+    pub async fn disable_totp(
+        &self,
+        account_service: &AccountService,
+        account: &AccountModel,
+        code: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        if !account.totp_enabled {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::MfaNotEnabled,
+            ));
+        }
+
+        let is_valid = self
+            .verify_second_factor(account_service, account, Some(code), Some(code))
+            .await?;
+        if !is_valid {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidMfaCode,
+            ));
+        }
+
+        account_service.disable_totp(&account.id).await
+    }
+
+    pub fn create_challenge(
+        &self,
+        token_service: &TokenService,
+        account: &AccountModel,
+    ) -> Result<MfaChallengeResponseDto, AuthenticationServiceError> {
+        let (challenge_token, challenge_expires_at) = token_service
+            .generate_mfa_challenge(&account.id, self.mfa_config.challenge_expiration_seconds)?;
+
+        Ok(MfaChallengeResponseDto {
+            mfa_required: true,
+            challenge_token,
+            challenge_expires_at,
+            methods: vec![TOTP_METHOD.to_string(), RECOVERY_CODE_METHOD.to_string()],
+        })
+    }
+
+    /// Verifies the second factor of a sign-in challenge, the sign-in is finished by the caller.
+    pub async fn verify_challenge(
+        &self,
+        account_service: &AccountService,
+        token_service: &TokenService,
+        verify: MfaVerifyRequestDto,
+    ) -> Result<AccountModel, AuthenticationServiceError> {
+        let account_id = token_service.verify_mfa_challenge(&verify.challenge_token)?;
+        let account = account_service.get_account_by_id(&account_id).await?;
+
+        if account.is_locked() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::AccountLocked,
+            ));
+        }
+
+        account_service.ensure_email_verified(&account)?;
+
+        if !account.totp_enabled {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::MfaNotEnabled,
+            ));
+        }
+
+        let is_valid = self
+            .verify_second_factor(
+                account_service,
+                &account,
+                verify.code.as_deref(),
+                verify.recovery_code.as_deref(),
+            )
+            .await?;
+
+        if !is_valid {
+            account_service
+                .register_failed_sign_in_attempt(&account.id)
+                .await?;
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidMfaCode,
+            ));
+        }
+
+        Ok(account)
+    }
+
+    async fn verify_second_factor(
+        &self,
+        account_service: &AccountService,
+        account: &AccountModel,
+        code: Option<&str>,
+        recovery_code: Option<&str>,
+    ) -> Result<bool, AuthenticationServiceError> {
+        if let (Some(code), Some(encrypted_secret)) = (code, account.totp_secret.as_ref()) {
+            let secret = self.encryption_service()?.decrypt(encrypted_secret)?;
+            let step = self
+                .totp_service
+                .verify_code(&secret, code, account.totp_last_used_step)?;
+
+            if let Some(step) = step {
+                return account_service.mark_totp_step_used(&account.id, step).await;
+            }
+        }
+
+        if let Some(recovery_code) = recovery_code {
+            let recovery_code_hash = self.totp_service.hash_recovery_code(recovery_code);
+            return account_service
+                .consume_recovery_code(&account.id, recovery_code_hash)
+                .await;
+        }
+
+        Ok(false)
+    }
+}
@@ -1,5 +1,28 @@
 pub mod account;
+pub mod api_key;
 pub mod authentication;
+pub mod claims;
+pub mod cookie;
+pub mod email_verification;
+pub mod encryption;
+pub mod external_login;
+pub mod impersonation;
+pub mod keyring;
+pub mod magic_link;
+pub mod mailer;
+pub mod mfa;
+pub mod oauth;
+pub mod oidc;
 pub mod password;
+pub mod password_reset;
+pub mod password_strength;
+pub mod revocation;
+pub mod role;
+pub mod service_account;
 pub mod session;
+pub mod session_cache;
+pub mod signing_key;
 pub mod token;
+pub mod totp;
+pub mod user_agent;
+pub mod webauthn;
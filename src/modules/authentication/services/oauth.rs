@@ -0,0 +1,876 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            config::authentication::OAuthConfiguration,
+            dtos::{
+                authentication::AuthenticationResponseDto,
+                oauth::{
+                    AuthorizationDetailsDto, AuthorizeRequestDto, CreateAuthorizationCodeOptions,
+                    CreateOAuthClientOptions, CreateOAuthClientRequestDto,
+                    IntrospectionResponseDto, TokenResponseDto, UpdateOAuthClientRequestDto,
+                },
+            },
+            errors::service::*,
+            models::{
+                account::AccountModel, authorization_code::AuthorizationCodeModel,
+                oauth_client::OAuthClientModel, service_account::ServiceAccountModel,
+                session::SessionModel,
+            },
+            services::{
+                account::AccountService,
+                oidc::OidcService,
+                revocation::RevocationService,
+                service_account::ServiceAccountService,
+                session::SessionService,
+                token::{FIRST_PARTY_AUDIENCE, TokenService},
+            },
+        },
+        base::exports::{
+            BaseDateTime, BaseId, DatabaseConnection, request_info::RequestInfoExtractor,
+        },
+    },
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sha2::{Digest, Sha256};
+
+const RESPONSE_TYPE_CODE: &str = "code";
+const CODE_CHALLENGE_METHOD_S256: &str = "S256";
+const TOKEN_TYPE_BEARER: &str = "Bearer";
+const TOKEN_TYPE_HINT_REFRESH_TOKEN: &str = "refresh_token";
+
+pub struct OAuthService {
+    database_connection: DatabaseConnection,
+    oauth_config: OAuthConfiguration,
+}
+
+impl OAuthService {
+    pub fn new(oauth_config: OAuthConfiguration, database_connection: DatabaseConnection) -> Self {
+        Self {
+            database_connection,
+            oauth_config,
+        }
+    }
+
+    fn generate_secret(&self) -> String {
+        use rand::Rng;
+        let secret: [u8; 32] = rand::rng().random();
+        URL_SAFE_NO_PAD.encode(secret)
+    }
+
+    fn hash_secret(&self, secret: &str) -> String {
+        Sha256::digest(secret.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    fn validate_redirect_uris(
+        &self,
+        redirect_uris: &[String],
+    ) -> Result<(), AuthenticationServiceError> {
+        let all_valid = !redirect_uris.is_empty()
+            && redirect_uris.iter().all(|redirect_uri| {
+                url::Url::parse(redirect_uri)
+                    .is_ok_and(|url| url.fragment().is_none() && url.has_host())
+            });
+
+        if !all_valid {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidRedirectUri,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_all_clients(
+        &self,
+    ) -> Result<Vec<OAuthClientModel>, AuthenticationServiceError> {
+        let clients: Vec<OAuthClientModel> = self
+            .database_connection
+            .select(OAuthClientModel::table_name())
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(clients)
+    }
+
+    pub async fn get_client_by_id(
+        &self,
+        client_id: &BaseId,
+    ) -> Result<OAuthClientModel, AuthenticationServiceError> {
+        let client: Option<OAuthClientModel> = self
+            .database_connection
+            .select(client_id)
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        client.ok_or(AuthenticationServiceError::client(
+            AuthenticationClientError::OAuthClientNotFound,
+        ))
+    }
+
+    pub async fn get_client_by_client_id(
+        &self,
+        client_id: &str,
+    ) -> Result<OAuthClientModel, AuthenticationServiceError> {
+        let client_id = OAuthClientModel::from_named_format(client_id).ok_or(
+            AuthenticationServiceError::client(AuthenticationClientError::InvalidOAuthClientId),
+        )?;
+
+        self.get_client_by_id(&client_id).await
+    }
+
+    pub async fn create_client(
+        &self,
+        create_client: CreateOAuthClientRequestDto,
+    ) -> Result<(OAuthClientModel, Option<String>), AuthenticationServiceError> {
+        self.validate_redirect_uris(&create_client.redirect_uris)?;
+
+        let client_secret = create_client.confidential.then(|| self.generate_secret());
+
+        let created_clients: Vec<OAuthClientModel> = self
+            .database_connection
+            .insert(OAuthClientModel::table_name())
+            .content(CreateOAuthClientOptions {
+                name: create_client.name,
+                client_secret_hash: client_secret
+                    .as_deref()
+                    .map(|secret| self.hash_secret(secret)),
+                redirect_uris: create_client.redirect_uris,
+                allowed_scopes: create_client.allowed_scopes,
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let client =
+            created_clients
+                .into_iter()
+                .next()
+                .ok_or(AuthenticationServiceError::ServerError(anyhow::anyhow!(
+                    "OAuth client creation failed without a specific error."
+                )))?;
+
+        Ok((client, client_secret))
+    }
+
+    pub async fn update_client(
+        &self,
+        client_id: &BaseId,
+        update_client: UpdateOAuthClientRequestDto,
+    ) -> Result<OAuthClientModel, AuthenticationServiceError> {
+        let mut client = self.get_client_by_id(client_id).await?;
+
+        if let Some(name) = update_client.name {
+            client.name = name;
+        }
+
+        if let Some(redirect_uris) = update_client.redirect_uris {
+            self.validate_redirect_uris(&redirect_uris)?;
+            client.redirect_uris = redirect_uris;
+        }
+
+        if let Some(allowed_scopes) = update_client.allowed_scopes {
+            client.allowed_scopes = allowed_scopes;
+        }
+
+        let clients: Vec<OAuthClientModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET name = $name, redirect_uris = $redirect_uris, allowed_scopes = $allowed_scopes WHERE id = $id RETURN AFTER")
+            .bind(("table", OAuthClientModel::table_name()))
+            .bind(("id", client_id.clone()))
+            .bind(("name", client.name))
+            .bind(("redirect_uris", client.redirect_uris))
+            .bind(("allowed_scopes", client.allowed_scopes))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        clients
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::OAuthClientNotFound,
+            ))
+    }
+
+    pub async fn rotate_client_secret(
+        &self,
+        client_id: &BaseId,
+    ) -> Result<(OAuthClientModel, String), AuthenticationServiceError> {
+        let client_secret = self.generate_secret();
+
+        let clients: Vec<OAuthClientModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET client_secret_hash = $client_secret_hash WHERE id = $id RETURN AFTER")
+            .bind(("table", OAuthClientModel::table_name()))
+            .bind(("id", client_id.clone()))
+            .bind(("client_secret_hash", self.hash_secret(&client_secret)))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let client = clients
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::OAuthClientNotFound,
+            ))?;
+
+        Ok((client, client_secret))
+    }
+
+    pub async fn delete_client(
+        &self,
+        client_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.get_client_by_id(client_id).await?;
+
+        self.database_connection
+            .query("DELETE FROM type::table($code_table) WHERE client_id = $id")
+            .query("UPDATE type::table($session_table) SET is_active = false WHERE audience = $audience")
+            .query("DELETE $id")
+            .bind(("code_table", AuthorizationCodeModel::table_name()))
+            .bind(("session_table", SessionModel::table_name()))
+            .bind(("audience", OAuthClientModel::to_named_format(client_id)))
+            .bind(("id", client_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    /// Checks the client credentials, public clients must not send a secret.
+    pub async fn authenticate_client(
+        &self,
+        client_id: &str,
+        client_secret: Option<&str>,
+    ) -> Result<OAuthClientModel, AuthenticationServiceError> {
+        let invalid_client =
+            || AuthenticationServiceError::client(AuthenticationClientError::InvalidClient);
+
+        let client = match self.get_client_by_client_id(client_id).await {
+            Err(AuthenticationServiceError::ClientError(_)) => return Err(invalid_client()),
+            other => other?,
+        };
+
+        match (client.client_secret_hash.as_deref(), client_secret) {
+            (Some(secret_hash), Some(secret)) if secret_hash == self.hash_secret(secret) => {
+                Ok(client)
+            }
+            (None, None) => Ok(client),
+            _ => Err(invalid_client()),
+        }
+    }
+
+    fn resolve_scopes(
+        &self,
+        client: &OAuthClientModel,
+        scope: Option<&str>,
+    ) -> Result<Vec<String>, AuthenticationServiceError> {
+        let requested: Vec<String> = scope
+            .map(|scope| scope.split_whitespace().map(String::from).collect())
+            .unwrap_or_default();
+
+        if requested.is_empty() {
+            return Ok(client.allowed_scopes.clone());
+        }
+
+        if requested
+            .iter()
+            .any(|scope| !client.allowed_scopes.contains(scope))
+        {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidScope,
+            ));
+        }
+
+        let mut scopes = Vec::with_capacity(requested.len());
+        for scope in requested {
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+
+        Ok(scopes)
+    }
+
+    pub async fn validate_authorization_request(
+        &self,
+        authorize: &AuthorizeRequestDto,
+    ) -> Result<(OAuthClientModel, Vec<String>), AuthenticationServiceError> {
+        let client = self.get_client_by_client_id(&authorize.client_id).await?;
+
+        if !client.redirect_uris.contains(&authorize.redirect_uri) {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidRedirectUri,
+            ));
+        }
+
+        if authorize.response_type != RESPONSE_TYPE_CODE {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::UnsupportedResponseType,
+            ));
+        }
+
+        match (
+            authorize.code_challenge.as_deref(),
+            authorize.code_challenge_method.as_deref(),
+        ) {
+            (Some(challenge), Some(CODE_CHALLENGE_METHOD_S256)) if !challenge.is_empty() => {}
+            (Some(_), _) => {
+                return Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::InvalidOAuthRequest(
+                        "only the S256 code challenge method is supported".to_string(),
+                    ),
+                ));
+            }
+            (None, _) if self.oauth_config.require_pkce || !client.is_confidential() => {
+                return Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::InvalidOAuthRequest(
+                        "code_challenge is required".to_string(),
+                    ),
+                ));
+            }
+            (None, _) => {}
+        }
+
+        let scopes = self.resolve_scopes(&client, authorize.scope.as_deref())?;
+
+        Ok((client, scopes))
+    }
+
+    pub async fn get_authorization_details(
+        &self,
+        authorize: &AuthorizeRequestDto,
+    ) -> Result<AuthorizationDetailsDto, AuthenticationServiceError> {
+        let (client, scopes) = self.validate_authorization_request(authorize).await?;
+
+        Ok(AuthorizationDetailsDto {
+            client_id: OAuthClientModel::to_named_format(&client.id),
+            client_name: client.name,
+            redirect_uri: authorize.redirect_uri.clone(),
+            scopes,
+        })
+    }
+
+    /// Issues an authorization code and returns the URI the user agent has to be redirected to.
+    pub async fn authorize(
+        &self,
+        account: &AccountModel,
+        authorize: AuthorizeRequestDto,
+    ) -> Result<String, AuthenticationServiceError> {
+        let (client, scopes) = self.validate_authorization_request(&authorize).await?;
+
+        let code = self.generate_secret();
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::seconds(
+                self.oauth_config.authorization_code_expiration_seconds as i64,
+            );
+
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE expires_at < time::now()")
+            .bind(("table", AuthorizationCodeModel::table_name()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let _: Vec<AuthorizationCodeModel> = self
+            .database_connection
+            .insert(AuthorizationCodeModel::table_name())
+            .content(CreateAuthorizationCodeOptions {
+                code_hash: self.hash_secret(&code),
+                client_id: client.id,
+                account_id: account.id.clone(),
+                redirect_uri: authorize.redirect_uri.clone(),
+                scopes,
+                code_challenge: authorize.code_challenge,
+                nonce: authorize.nonce,
+                expires_at: BaseDateTime::from(expires_at),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let mut redirect_to = url::Url::parse(&authorize.redirect_uri)
+            .map_err(AuthenticationServiceError::from_error)?;
+        redirect_to.query_pairs_mut().append_pair("code", &code);
+        if let Some(state) = authorize.state.as_deref() {
+            redirect_to.query_pairs_mut().append_pair("state", state);
+        }
+
+        Ok(redirect_to.to_string())
+    }
+
+    async fn consume_authorization_code(
+        &self,
+        code: &str,
+    ) -> Result<AuthorizationCodeModel, AuthenticationServiceError> {
+        let codes: Vec<AuthorizationCodeModel> = self
+            .database_connection
+            .query("DELETE FROM type::table($table) WHERE code_hash = $code_hash RETURN BEFORE")
+            .bind(("table", AuthorizationCodeModel::table_name()))
+            .bind(("code_hash", self.hash_secret(code)))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        codes
+            .into_iter()
+            .next()
+            .filter(|code| code.expires_at > BaseDateTime::from(chrono::Utc::now()))
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidGrant,
+            ))
+    }
+
+    fn verify_code_verifier(
+        &self,
+        code_challenge: Option<&str>,
+        code_verifier: Option<&str>,
+    ) -> Result<(), AuthenticationServiceError> {
+        match (code_challenge, code_verifier) {
+            (None, None) => Ok(()),
+            (Some(challenge), Some(verifier))
+                if URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())) == challenge =>
+            {
+                Ok(())
+            }
+            _ => Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidGrant,
+            )),
+        }
+    }
+
+    fn token_response(
+        &self,
+        auth_response: AuthenticationResponseDto,
+        scopes: &[String],
+        id_token: Option<String>,
+    ) -> TokenResponseDto {
+        TokenResponseDto {
+            id_token,
+            expires_in: (auth_response.access_token_expires_at - chrono::Utc::now())
+                .num_seconds()
+                .max(0),
+            access_token: auth_response.access_token,
+            token_type: TOKEN_TYPE_BEARER.to_string(),
+            refresh_token: Some(auth_response.refresh_token),
+            scope: scopes.join(" "),
+        }
+    }
+
+    /// Issues an id token for the granted scopes when the client asked for `openid`.
+    #[allow(clippy::too_many_arguments)]
+    async fn id_token(
+        &self,
+        oidc_service: &OidcService,
+        account_service: &AccountService,
+        token_service: &TokenService,
+        client: &OAuthClientModel,
+        account_id: &BaseId,
+        scopes: &[String],
+        nonce: Option<&str>,
+    ) -> Result<Option<String>, AuthenticationServiceError> {
+        if !oidc_service.is_openid_request(scopes) {
+            return Ok(None);
+        }
+
+        let account = account_service.get_account_by_id(account_id).await?;
+        let id_token = oidc_service
+            .generate_id_token(
+                token_service,
+                &account,
+                &OAuthClientModel::to_named_format(&client.id),
+                scopes,
+                nonce,
+            )
+            .await?;
+
+        Ok(Some(id_token))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn exchange_authorization_code(
+        &self,
+        account_service: &AccountService,
+        oidc_service: &OidcService,
+        session_service: &SessionService,
+        token_service: &TokenService,
+        request_info: RequestInfoExtractor,
+        client: &OAuthClientModel,
+        code: &str,
+        redirect_uri: Option<&str>,
+        code_verifier: Option<&str>,
+    ) -> Result<TokenResponseDto, AuthenticationServiceError> {
+        let authorization_code = self.consume_authorization_code(code).await?;
+
+        if authorization_code.client_id != client.id
+            || redirect_uri != Some(authorization_code.redirect_uri.as_str())
+        {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidGrant,
+            ));
+        }
+
+        self.verify_code_verifier(authorization_code.code_challenge.as_deref(), code_verifier)?;
+
+        let auth_response = session_service
+            .create_session_with_scopes(
+                token_service,
+                &authorization_code.account_id,
+                request_info,
+                OAuthClientModel::to_named_format(&client.id),
+                authorization_code.scopes.clone(),
+            )
+            .await?;
+
+        let id_token = self
+            .id_token(
+                oidc_service,
+                account_service,
+                token_service,
+                client,
+                &authorization_code.account_id,
+                &authorization_code.scopes,
+                authorization_code.nonce.as_deref(),
+            )
+            .await?;
+
+        Ok(self.token_response(auth_response, &authorization_code.scopes, id_token))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn refresh(
+        &self,
+        account_service: &AccountService,
+        oidc_service: &OidcService,
+        session_service: &SessionService,
+        token_service: &TokenService,
+        request_info: RequestInfoExtractor,
+        client: &OAuthClientModel,
+        refresh_token: &str,
+    ) -> Result<TokenResponseDto, AuthenticationServiceError> {
+        let refresh_token_hash = token_service.hash_refresh_token(refresh_token);
+        let auth_response = session_service
+            .refresh_session(
+                token_service,
+                refresh_token_hash,
+                OAuthClientModel::to_named_format(&client.id),
+                &request_info,
+            )
+            .await?;
+
+        let session_id = SessionModel::from_named_format(&auth_response.session_id).ok_or(
+            AuthenticationServiceError::client(AuthenticationClientError::InvalidSessionId),
+        )?;
+        let session = session_service.get_session_by_id(&session_id).await?;
+
+        let id_token = self
+            .id_token(
+                oidc_service,
+                account_service,
+                token_service,
+                client,
+                &session.account_id,
+                &session.scopes,
+                None,
+            )
+            .await?;
+
+        Ok(self.token_response(auth_response, &session.scopes, id_token))
+    }
+
+    /// Access tokens are only active while their session is, so revoked sessions are reported
+    /// as inactive before the token expires.
+    async fn introspect_access_token(
+        &self,
+        session_service: &SessionService,
+        service_account_service: &ServiceAccountService,
+        revocation_service: &RevocationService,
+        token_service: &TokenService,
+        token: &str,
+    ) -> Result<IntrospectionResponseDto, AuthenticationServiceError> {
+        let claims = match token_service.verify_access_token(token) {
+            Ok(claims) => claims,
+            Err(AuthenticationServiceError::ClientError(_)) => {
+                return self
+                    .introspect_service_token(
+                        service_account_service,
+                        revocation_service,
+                        token_service,
+                        token,
+                    )
+                    .await;
+            }
+            Err(e) => return Err(e),
+        };
+
+        revocation_service
+            .ensure_not_revoked(&claims.token_id)
+            .await?;
+        let session = session_service.get_live_session(&claims.session_id).await?;
+        if session.account_id != claims.account_id {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidAccessToken,
+            ));
+        }
+
+        Ok(IntrospectionResponseDto {
+            active: true,
+            scope: Some(claims.scopes.join(" ")).filter(|scope| !scope.is_empty()),
+            client_id: Some(claims.audience).filter(|audience| audience != FIRST_PARTY_AUDIENCE),
+            sub: Some(AccountModel::to_named_format(&claims.account_id)),
+            token_type: Some(TOKEN_TYPE_BEARER.to_string()),
+            exp: Some(claims.expires_at.timestamp()),
+            iat: Some(claims.issued_at.timestamp()),
+            jti: Some(claims.token_id),
+        })
+    }
+
+    async fn introspect_service_token(
+        &self,
+        service_account_service: &ServiceAccountService,
+        revocation_service: &RevocationService,
+        token_service: &TokenService,
+        token: &str,
+    ) -> Result<IntrospectionResponseDto, AuthenticationServiceError> {
+        let claims = token_service.verify_service_token(token)?;
+        revocation_service
+            .ensure_not_revoked(&claims.token_id)
+            .await?;
+        let service_account = service_account_service
+            .get_service_account_by_id(&claims.service_account_id)
+            .await?;
+        if !service_account.is_active {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidAccessToken,
+            ));
+        }
+
+        let service_account_id = ServiceAccountModel::to_named_format(&service_account.id);
+        Ok(IntrospectionResponseDto {
+            active: true,
+            scope: Some(claims.scopes.join(" ")).filter(|scope| !scope.is_empty()),
+            client_id: Some(service_account_id.clone()),
+            sub: Some(service_account_id),
+            token_type: Some(TOKEN_TYPE_BEARER.to_string()),
+            exp: Some(claims.expires_at.timestamp()),
+            iat: Some(claims.issued_at.timestamp()),
+            jti: Some(claims.token_id),
+        })
+    }
+
+    async fn introspect_refresh_token(
+        &self,
+        session_service: &SessionService,
+        token_service: &TokenService,
+        token: &str,
+    ) -> Result<IntrospectionResponseDto, AuthenticationServiceError> {
+        let session = session_service
+            .get_session_by_refresh_token_hash(token_service.hash_refresh_token(token))
+            .await?;
+        session_service.validate_session(&session)?;
+
+        Ok(IntrospectionResponseDto {
+            active: true,
+            scope: Some(session.scopes.join(" ")).filter(|scope| !scope.is_empty()),
+            client_id: Some(session.audience).filter(|audience| audience != FIRST_PARTY_AUDIENCE),
+            sub: Some(AccountModel::to_named_format(&session.account_id)),
+            token_type: Some(TOKEN_TYPE_HINT_REFRESH_TOKEN.to_string()),
+            exp: Some(session.expires_at.into_inner().0.timestamp()),
+            iat: Some(session.created_at.into_inner().0.timestamp()),
+            jti: None,
+        })
+    }
+
+    /// Token introspection as described in RFC 7662. Unknown, expired or revoked tokens are
+    /// reported as inactive, the hint only decides which token type is tried first.
+    pub async fn introspect(
+        &self,
+        session_service: &SessionService,
+        service_account_service: &ServiceAccountService,
+        revocation_service: &RevocationService,
+        token_service: &TokenService,
+        token: &str,
+        token_type_hint: Option<&str>,
+    ) -> Result<IntrospectionResponseDto, AuthenticationServiceError> {
+        let token = token.trim();
+        let prefer_refresh_token = token_type_hint == Some(TOKEN_TYPE_HINT_REFRESH_TOKEN);
+
+        for refresh_token in [prefer_refresh_token, !prefer_refresh_token] {
+            let introspection = if refresh_token {
+                self.introspect_refresh_token(session_service, token_service, token)
+                    .await
+            } else {
+                self.introspect_access_token(
+                    session_service,
+                    service_account_service,
+                    revocation_service,
+                    token_service,
+                    token,
+                )
+                .await
+            };
+
+            match introspection {
+                Ok(response) => return Ok(response),
+                Err(AuthenticationServiceError::ClientError(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(IntrospectionResponseDto::inactive())
+    }
+
+    /// Revokes an access token by its id and ends its session, a token issued to another
+    /// client is left untouched.
+    async fn revoke_access_token(
+        &self,
+        session_service: &SessionService,
+        revocation_service: &RevocationService,
+        token_service: &TokenService,
+        client_id: &str,
+        token: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        let claims = match token_service.verify_access_token(token) {
+            Ok(claims) => claims,
+            Err(AuthenticationServiceError::ClientError(_)) => {
+                let claims = token_service.verify_service_token(token)?;
+                if ServiceAccountModel::to_named_format(&claims.service_account_id) != client_id {
+                    return Ok(());
+                }
+
+                return revocation_service
+                    .revoke_token_id(&claims.token_id, claims.expires_at)
+                    .await;
+            }
+            Err(e) => return Err(e),
+        };
+
+        if claims.audience != client_id {
+            return Ok(());
+        }
+
+        revocation_service
+            .revoke_token_id(&claims.token_id, claims.expires_at)
+            .await?;
+        session_service
+            .deactivate_session_for_account(&claims.session_id, &claims.account_id)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_refresh_token(
+        &self,
+        session_service: &SessionService,
+        token_service: &TokenService,
+        client_id: &str,
+        token: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        let session = session_service
+            .get_session_by_refresh_token_hash(token_service.hash_refresh_token(token))
+            .await?;
+        if session.audience != client_id {
+            return Ok(());
+        }
+
+        session_service.deactivate_session(&session.id).await?;
+
+        Ok(())
+    }
+
+    /// Token revocation as described in RFC 7009. `client_id` is the authenticated caller, first
+    /// party callers pass the first party audience. Unknown tokens are not an error.
+    pub async fn revoke(
+        &self,
+        session_service: &SessionService,
+        revocation_service: &RevocationService,
+        token_service: &TokenService,
+        client_id: &str,
+        token: &str,
+        token_type_hint: Option<&str>,
+    ) -> Result<(), AuthenticationServiceError> {
+        let token = token.trim();
+        let prefer_refresh_token = token_type_hint == Some(TOKEN_TYPE_HINT_REFRESH_TOKEN);
+
+        for refresh_token in [prefer_refresh_token, !prefer_refresh_token] {
+            let revocation = if refresh_token {
+                self.revoke_refresh_token(session_service, token_service, client_id, token)
+                    .await
+            } else {
+                self.revoke_access_token(
+                    session_service,
+                    revocation_service,
+                    token_service,
+                    client_id,
+                    token,
+                )
+                .await
+            };
+
+            match revocation {
+                Ok(()) => return Ok(()),
+                Err(AuthenticationServiceError::ClientError(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 7636, Appendix B
+    const RFC_VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const RFC_CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+    fn service() -> OAuthService {
+        OAuthService::new(OAuthConfiguration::default(), DatabaseConnection::init())
+    }
+
+    #[test]
+    fn accepts_the_rfc_7636_example() {
+        assert!(
+            service()
+                .verify_code_verifier(Some(RFC_CHALLENGE), Some(RFC_VERIFIER))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_or_missing_verifiers() {
+        let service = service();
+        let cases = [
+            (
+                Some(RFC_CHALLENGE),
+                Some("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXl"),
+            ),
+            (Some(RFC_CHALLENGE), Some("")),
+            // The plain method is not supported, the challenge itself is no verifier
+            (Some(RFC_CHALLENGE), Some(RFC_CHALLENGE)),
+            (Some(RFC_CHALLENGE), None),
+            (None, Some(RFC_VERIFIER)),
+        ];
+
+        for (challenge, verifier) in cases {
+            assert!(matches!(
+                service.verify_code_verifier(challenge, verifier),
+                Err(AuthenticationServiceError::ClientError(
+                    AuthenticationClientError::InvalidGrant
+                ))
+            ));
+        }
+    }
+
+    #[test]
+    fn allows_codes_issued_without_pkce() {
+        assert!(service().verify_code_verifier(None, None).is_ok());
+    }
+}
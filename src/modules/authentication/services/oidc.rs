@@ -0,0 +1,174 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::authentication::{
+        config::authentication::OidcConfiguration,
+        dtos::oidc::OpenIdConfigurationDto,
+        errors::service::*,
+        models::account::AccountModel,
+        services::{
+            account::AccountService,
+            claims::{Claims, OPENID_SCOPE, claim_providers},
+            session::SessionService,
+            token::TokenService,
+        },
+    },
+};
+use serde_json::json;
+
+const STANDARD_CLAIMS: [&str; 6] = ["iss", "sub", "aud", "exp", "iat", "nonce"];
+
+pub struct OidcService {
+    oidc_config: OidcConfiguration,
+}
+
+impl OidcService {
+    pub fn new(oidc_config: OidcConfiguration) -> Self {
+        Self { oidc_config }
+    }
+
+    fn issuer(&self) -> &str {
+        self.oidc_config.issuer.trim_end_matches('/')
+    }
+
+    pub fn is_openid_request(&self, scopes: &[String]) -> bool {
+        scopes.iter().any(|scope| scope == OPENID_SCOPE)
+    }
+
+    /// Collects the subject and the claims of every provider whose scope was granted.
+    pub async fn collect_claims(
+        &self,
+        account: &AccountModel,
+        scopes: &[String],
+    ) -> Result<Claims, AuthenticationServiceError> {
+        let mut claims = Claims::new();
+
+        for provider in claim_providers() {
+            if !scopes.iter().any(|scope| scope == provider.scope()) {
+                continue;
+            }
+
+            claims.extend(provider.claims(account).await?);
+        }
+
+        claims.insert(
+            "sub".to_string(),
+            json!(AccountModel::to_named_format(&account.id)),
+        );
+
+        Ok(claims)
+    }
+
+    pub async fn generate_id_token(
+        &self,
+        token_service: &TokenService,
+        account: &AccountModel,
+        audience: &str,
+        scopes: &[String],
+        nonce: Option<&str>,
+    ) -> Result<String, AuthenticationServiceError> {
+        let issued_at = chrono::Utc::now();
+        let expires_at = issued_at
+            + chrono::Duration::seconds(self.oidc_config.id_token_expiration_seconds as i64);
+
+        let mut claims = self.collect_claims(account, scopes).await?;
+        claims.insert("iss".to_string(), json!(self.issuer()));
+        claims.insert("aud".to_string(), json!(audience));
+        claims.insert("iat".to_string(), json!(issued_at.timestamp()));
+        claims.insert("exp".to_string(), json!(expires_at.timestamp()));
+        if let Some(nonce) = nonce {
+            claims.insert("nonce".to_string(), json!(nonce));
+        }
+
+        token_service.sign_claims(&claims)
+    }
+
+    pub async fn userinfo(
+        &self,
+        account_service: &AccountService,
+        session_service: &SessionService,
+        token_service: &TokenService,
+        access_token: &str,
+    ) -> Result<Claims, AuthenticationServiceError> {
+        let access_token_claims = token_service
+            .verify_access_token(access_token)
+            .map_err(|e| match e {
+                AuthenticationServiceError::ServerError(_) => AuthenticationServiceError::client(
+                    AuthenticationClientError::InvalidAccessToken,
+                ),
+                client_error => client_error,
+            })?;
+
+        if !self.is_openid_request(&access_token_claims.scopes) {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidScope,
+            ));
+        }
+
+        let session = session_service
+            .get_live_session(&access_token_claims.session_id)
+            .await?;
+        if session.account_id != access_token_claims.account_id {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidAccessToken,
+            ));
+        }
+
+        let account = account_service
+            .get_account_by_id(&access_token_claims.account_id)
+            .await?;
+
+        self.collect_claims(&account, &access_token_claims.scopes)
+            .await
+    }
+
+    pub fn discovery_document(&self, token_service: &TokenService) -> OpenIdConfigurationDto {
+        let issuer = self.issuer();
+        let providers = claim_providers();
+
+        let mut scopes_supported = vec![OPENID_SCOPE.to_string()];
+        let mut claims_supported: Vec<String> = STANDARD_CLAIMS
+            .iter()
+            .map(|claim| claim.to_string())
+            .collect();
+        for provider in providers {
+            if !scopes_supported
+                .iter()
+                .any(|scope| scope == provider.scope())
+            {
+                scopes_supported.push(provider.scope().to_string());
+            }
+
+            for claim in provider.claim_names() {
+                if !claims_supported.iter().any(|supported| supported == claim) {
+                    claims_supported.push(claim.to_string());
+                }
+            }
+        }
+
+        OpenIdConfigurationDto {
+            issuer: issuer.to_string(),
+            authorization_endpoint: format!("{}/oauth/authorize", issuer),
+            token_endpoint: format!("{}/oauth/token", issuer),
+            userinfo_endpoint: format!("{}/oauth/userinfo", issuer),
+            introspection_endpoint: format!("{}/oauth/introspect", issuer),
+            revocation_endpoint: format!("{}/oauth/revoke", issuer),
+            jwks_uri: format!("{}/.well-known/jwks.json", issuer),
+            response_types_supported: vec!["code".to_string()],
+            grant_types_supported: vec![
+                "authorization_code".to_string(),
+                "refresh_token".to_string(),
+                "client_credentials".to_string(),
+            ],
+            subject_types_supported: vec!["public".to_string()],
+            id_token_signing_alg_values_supported: token_service.signing_algorithms(),
+            token_endpoint_auth_methods_supported: vec![
+                "client_secret_basic".to_string(),
+                "client_secret_post".to_string(),
+                "none".to_string(),
+            ],
+            code_challenge_methods_supported: vec!["S256".to_string()],
+            scopes_supported,
+            claims_supported,
+        }
+    }
+}
@@ -1,15 +1,87 @@
 use argon2::{
-    Argon2,
+    Algorithm, Argon2, KeyId, Params, ParamsBuilder, Version,
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
 };
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::{
+    cmp::Ordering,
+    fs::File,
+    io::{BufRead, BufReader, Seek, SeekFrom},
+};
+
+use crate::modules::authentication::{
+    config::authentication::{PasswordHashingConfiguration, PasswordPolicyConfiguration},
+    errors::service::{AuthenticationClientError, AuthenticationServiceError},
+    services::password_strength,
+};
 
-use crate::modules::authentication::errors::service::AuthenticationServiceError;
+const SHA1_HEX_LENGTH: usize = 40;
+const SERVICE_NAME: &str = "PasswordService";
+
+pub struct PasswordService {
+    password_policy: PasswordPolicyConfiguration,
+    password_hashing: PasswordHashingConfiguration,
+}
 
-pub struct PasswordService;
 impl PasswordService {
+    pub fn new(
+        password_policy: PasswordPolicyConfiguration,
+        password_hashing: PasswordHashingConfiguration,
+    ) -> Self {
+        Self {
+            password_policy,
+            password_hashing,
+        }
+    }
+
+    fn argon2_error(e: argon2::Error) -> AuthenticationServiceError {
+        AuthenticationServiceError::ServerError(anyhow::anyhow!(
+            "Invalid password hashing configuration: {}",
+            e
+        ))
+    }
+
+    fn pepper(&self) -> Option<&[u8]> {
+        self.password_hashing
+            .pepper
+            .as_deref()
+            .filter(|pepper| !pepper.is_empty())
+            .map(str::as_bytes)
+    }
+
+    /// Peppered hashes carry a fingerprint of the pepper as PHC `keyid`, so hashes from before
+    /// the pepper was introduced can still be told apart.
+    fn pepper_key_id(&self) -> Vec<u8> {
+        self.pepper()
+            .map(|pepper| Sha256::digest(pepper)[..Params::MAX_KEYID_LEN].to_vec())
+            .unwrap_or_default()
+    }
+
+    fn params(&self) -> Result<Params, AuthenticationServiceError> {
+        let key_id = self.pepper_key_id();
+        ParamsBuilder::new()
+            .m_cost(self.password_hashing.memory_cost_kib)
+            .t_cost(self.password_hashing.iterations)
+            .p_cost(self.password_hashing.parallelism)
+            .keyid(KeyId::new(&key_id).map_err(Self::argon2_error)?)
+            .build()
+            .map_err(Self::argon2_error)
+    }
+
+    fn argon2(&self, params: Params) -> Result<Argon2<'_>, AuthenticationServiceError> {
+        match self.pepper() {
+            Some(pepper) if !params.keyid().is_empty() => {
+                Argon2::new_with_secret(pepper, Algorithm::Argon2id, Version::V0x13, params)
+                    .map_err(Self::argon2_error)
+            }
+            _ => Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params)),
+        }
+    }
+
     pub fn hash_password(&self, password: &str) -> Result<String, AuthenticationServiceError> {
         let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
+        let argon2 = self.argon2(self.params()?)?;
         let password_hash = argon2
             .hash_password(password.as_bytes(), &salt)
             .map_err(AuthenticationServiceError::from_error)?
@@ -18,6 +90,7 @@ impl PasswordService {
         Ok(password_hash)
     }
 
+    /// Verifies with the parameters stored in the hash, the configured ones only apply to new hashes.
     pub fn verify_password(
         &self,
         hash: &str,
@@ -25,12 +98,303 @@ impl PasswordService {
     ) -> Result<bool, AuthenticationServiceError> {
         let parsed_hash =
             PasswordHash::new(hash).map_err(AuthenticationServiceError::from_error)?;
-        let argon2 = Argon2::default();
+        let params =
+            Params::try_from(&parsed_hash).map_err(AuthenticationServiceError::from_error)?;
 
+        if !params.keyid().is_empty() && params.keyid() != self.pepper_key_id() {
+            tracing::error!(
+                "{} Password hash was created with a different pepper",
+                SERVICE_NAME
+            );
+            return Ok(false);
+        }
+
+        let argon2 = self.argon2(params)?;
         match argon2.verify_password(password.as_bytes(), &parsed_hash) {
             Ok(_) => Ok(true),
             Err(argon2::password_hash::Error::Password) => Ok(false),
             Err(e) => Err(AuthenticationServiceError::from_error(e)),
         }
     }
+
+    /// Whether a hash was created with other parameters or pepper than currently configured.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(parsed_hash) = PasswordHash::new(hash) else {
+            return false;
+        };
+        let (Ok(params), Ok(expected)) = (Params::try_from(&parsed_hash), self.params()) else {
+            return false;
+        };
+
+        parsed_hash.algorithm != Algorithm::Argon2id.ident()
+            || parsed_hash.version != Some(Version::V0x13.into())
+            || params.m_cost() != expected.m_cost()
+            || params.t_cost() != expected.t_cost()
+            || params.p_cost() != expected.p_cost()
+            || params.keyid() != expected.keyid()
+    }
+
+    /// Checks a new password against the policy, the error names the first rule that failed.
+    pub fn validate_password(
+        &self,
+        password: &str,
+        username: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        let policy = &self.password_policy;
+        let length = password.chars().count();
+        let rules = [
+            (
+                length < policy.min_length,
+                AuthenticationClientError::PasswordTooShort(policy.min_length),
+            ),
+            (
+                length > policy.max_length,
+                AuthenticationClientError::PasswordTooLong(policy.max_length),
+            ),
+            (
+                policy.require_lowercase && !password.chars().any(char::is_lowercase),
+                AuthenticationClientError::PasswordMissingLowercase,
+            ),
+            (
+                policy.require_uppercase && !password.chars().any(char::is_uppercase),
+                AuthenticationClientError::PasswordMissingUppercase,
+            ),
+            (
+                policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()),
+                AuthenticationClientError::PasswordMissingDigit,
+            ),
+            (
+                policy.require_symbol && password.chars().all(char::is_alphanumeric),
+                AuthenticationClientError::PasswordMissingSymbol,
+            ),
+            (
+                policy.disallow_username
+                    && !username.trim().is_empty()
+                    && password
+                        .to_lowercase()
+                        .contains(&username.trim().to_lowercase()),
+                AuthenticationClientError::PasswordContainsUsername,
+            ),
+            (
+                password_strength::score(password, &[username]) < policy.min_strength_score,
+                AuthenticationClientError::PasswordTooWeak,
+            ),
+        ];
+
+        if let Some((_, error)) = rules.into_iter().find(|(failed, _)| *failed) {
+            return Err(AuthenticationServiceError::client(error));
+        }
+
+        if let Some(path) = policy.breached_passwords_path.as_deref()
+            && self.breached_count(path, password)? >= policy.breached_min_count.max(1)
+        {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::PasswordBreached,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Looks the password up in a Pwned Passwords list sorted by hash, lines are `HASH:COUNT`.
+    /// The list is binary searched on disk, so multi gigabyte lists need no loading.
+    fn breached_count(
+        &self,
+        path: &str,
+        password: &str,
+    ) -> Result<u64, AuthenticationServiceError> {
+        let hash: String = Sha1::digest(password.as_bytes())
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect();
+
+        let file = File::open(path).map_err(AuthenticationServiceError::from_error)?;
+        let length = file
+            .metadata()
+            .map_err(AuthenticationServiceError::from_error)?
+            .len();
+        let mut reader = BufReader::new(file);
+
+        // The line holding the hash, if any, starts within [low, high)
+        let (mut low, mut high) = (0, length);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let line_start = Self::next_line_start(&mut reader, middle)?;
+            if line_start >= high {
+                high = middle;
+                continue;
+            }
+
+            let (line, line_end) = Self::read_line(&mut reader, line_start)?;
+            let (line_hash, count) = line.trim().split_once(':').unwrap_or((line.trim(), "1"));
+            if line_hash.len() != SHA1_HEX_LENGTH {
+                return Err(AuthenticationServiceError::ServerError(anyhow::anyhow!(
+                    "Malformed line in breached password list {}",
+                    path
+                )));
+            }
+
+            match line_hash.to_ascii_uppercase().as_str().cmp(hash.as_str()) {
+                Ordering::Equal => return Ok(count.trim().parse().unwrap_or(1)),
+                Ordering::Less => low = line_end,
+                Ordering::Greater => high = middle,
+            }
+        }
+
+        Ok(0)
+    }
+
+    fn next_line_start(
+        reader: &mut BufReader<File>,
+        position: u64,
+    ) -> Result<u64, AuthenticationServiceError> {
+        if position == 0 {
+            return Ok(0);
+        }
+
+        let mut skipped = Vec::new();
+        reader
+            .seek(SeekFrom::Start(position - 1))
+            .map_err(AuthenticationServiceError::from_error)?;
+        let read = reader
+            .read_until(b'\n', &mut skipped)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(position - 1 + read as u64)
+    }
+
+    fn read_line(
+        reader: &mut BufReader<File>,
+        position: u64,
+    ) -> Result<(String, u64), AuthenticationServiceError> {
+        let mut line = Vec::new();
+        reader
+            .seek(SeekFrom::Start(position))
+            .map_err(AuthenticationServiceError::from_error)?;
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok((
+            String::from_utf8_lossy(&line).into_owned(),
+            position + read as u64,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sha1_hex(password: &str) -> String {
+        Sha1::digest(password.as_bytes())
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect()
+    }
+
+    /// Writes a sorted `HASH:COUNT` list of the passwords plus filler hashes in between.
+    fn breached_list(name: &str, passwords: &[(&str, u64)], line_ending: &str) -> PathBuf {
+        let mut lines: Vec<String> = passwords
+            .iter()
+            .map(|(password, count)| format!("{}:{}", sha1_hex(password), count))
+            .chain((0..200).map(|i| format!("{}:1", sha1_hex(&format!("filler-{}", i)))))
+            .collect();
+        lines.sort();
+
+        let path =
+            std::env::temp_dir().join(format!("core-breached-{}-{}.txt", name, std::process::id()));
+        std::fs::write(&path, lines.join(line_ending) + line_ending).unwrap();
+        path
+    }
+
+    fn service() -> PasswordService {
+        PasswordService::new(
+            PasswordPolicyConfiguration::default(),
+            PasswordHashingConfiguration::default(),
+        )
+    }
+
+    fn first_and_last(passwords: &[&str]) -> (String, String) {
+        let mut hashes: Vec<(String, String)> = passwords
+            .iter()
+            .map(|p| (sha1_hex(p), p.to_string()))
+            .chain((0..200).map(|i| {
+                let filler = format!("filler-{}", i);
+                (sha1_hex(&filler), filler)
+            }))
+            .collect();
+        hashes.sort();
+
+        (
+            hashes.first().unwrap().1.clone(),
+            hashes.last().unwrap().1.clone(),
+        )
+    }
+
+    #[test]
+    fn breached_search_finds_listed_passwords() {
+        let path = breached_list("listed", &[("hunter2", 17), ("letmein", 3)], "\n");
+        let service = service();
+        let path = path.to_str().unwrap();
+
+        assert_eq!(service.breached_count(path, "hunter2").unwrap(), 17);
+        assert_eq!(service.breached_count(path, "letmein").unwrap(), 3);
+        assert_eq!(service.breached_count(path, "not in the list").unwrap(), 0);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn breached_search_finds_first_and_last_lines() {
+        let path = breached_list("edges", &[], "\n");
+        let service = service();
+        let (first, last) = first_and_last(&[]);
+        let path = path.to_str().unwrap();
+
+        assert_eq!(service.breached_count(path, &first).unwrap(), 1);
+        assert_eq!(service.breached_count(path, &last).unwrap(), 1);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn breached_search_handles_crlf_lines() {
+        let path = breached_list("crlf", &[], "\r\n");
+        let service = service();
+        let (first, last) = first_and_last(&[]);
+        let path = path.to_str().unwrap();
+
+        assert_eq!(service.breached_count(path, &first).unwrap(), 1);
+        assert_eq!(service.breached_count(path, "filler-100").unwrap(), 1);
+        assert_eq!(service.breached_count(path, &last).unwrap(), 1);
+        assert_eq!(service.breached_count(path, "missing").unwrap(), 0);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn validate_password_rejects_breached_passwords() {
+        let path = breached_list("policy", &[("Tr0ub4dor&3xyz", 42)], "\n");
+        let service = PasswordService::new(
+            PasswordPolicyConfiguration {
+                min_strength_score: 0,
+                breached_passwords_path: Some(path.to_str().unwrap().to_string()),
+                breached_min_count: 10,
+                ..Default::default()
+            },
+            PasswordHashingConfiguration::default(),
+        );
+
+        assert!(matches!(
+            service.validate_password("Tr0ub4dor&3xyz", "someone"),
+            Err(AuthenticationServiceError::ClientError(
+                AuthenticationClientError::PasswordBreached
+            ))
+        ));
+        assert!(
+            service
+                .validate_password("Tr0ub4dor&3abc", "someone")
+                .is_ok()
+        );
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -0,0 +1,188 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            config::authentication::PasswordResetConfiguration,
+            dtos::password_reset::{CreatePasswordResetTokenOptions, ResetPasswordRequestDto},
+            errors::service::*,
+            models::{account::AccountModel, password_reset_token::PasswordResetTokenModel},
+            services::{
+                account::AccountService,
+                mailer::{MailMessage, Mailer},
+                password::PasswordService,
+                session::SessionService,
+            },
+        },
+        base::exports::{BaseDateTime, BaseId, DatabaseConnection},
+    },
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sha2::{Digest, Sha256};
+
+pub struct PasswordResetService {
+    database_connection: DatabaseConnection,
+    password_reset_config: PasswordResetConfiguration,
+}
+
+impl PasswordResetService {
+    pub fn new(
+        password_reset_config: PasswordResetConfiguration,
+        database_connection: DatabaseConnection,
+    ) -> Self {
+        Self {
+            database_connection,
+            password_reset_config,
+        }
+    }
+
+    fn generate_token(&self) -> String {
+        use rand::Rng;
+        let token: [u8; 32] = rand::rng().random();
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    fn hash_token(&self, token: &str) -> String {
+        Sha256::digest(token.trim().as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    fn recipient_for_account(&self, account: &AccountModel) -> Option<String> {
+        account.email.clone().filter(|_| account.email_verified)
+    }
+
+    async fn delete_tokens_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE account_id = $account_id OR expires_at < time::now()")
+            .bind(("table", PasswordResetTokenModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    /// Never reveals whether the account exists, unknown usernames are silently ignored.
+    pub async fn request_reset(
+        &self,
+        account_service: &AccountService,
+        mailer: &dyn Mailer,
+        username: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        let account = match account_service.get_account_by_username(username).await {
+            Ok(account) => account,
+            Err(AuthenticationServiceError::ClientError(
+                AuthenticationClientError::AccountNotFound,
+            )) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let Some(recipient) = self.recipient_for_account(&account) else {
+            tracing::warn!(
+                "Password reset requested for account {} without a verified email address",
+                AccountModel::to_named_format(&account.id)
+            );
+            return Ok(());
+        };
+
+        self.delete_tokens_for_account(&account.id).await?;
+
+        let token = self.generate_token();
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::seconds(self.password_reset_config.token_expiration_seconds as i64);
+
+        let _: Vec<PasswordResetTokenModel> = self
+            .database_connection
+            .insert(PasswordResetTokenModel::table_name())
+            .content(CreatePasswordResetTokenOptions {
+                account_id: account.id.clone(),
+                token_hash: self.hash_token(&token),
+                expires_at: BaseDateTime::from(expires_at),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let reset_url = self
+            .password_reset_config
+            .reset_url
+            .replace("{token}", &token);
+        let message = MailMessage {
+            to: recipient,
+            subject: "Reset your password".to_string(),
+            body: format!(
+                "A password reset was requested for your account.\n\nUse the following link to choose a new password:\n{}\n\nThe link expires in {} minutes. If you did not request a reset you can ignore this mail.",
+                reset_url,
+                self.password_reset_config.token_expiration_seconds / 60
+            ),
+        };
+
+        if let Err(e) = mailer.send(message).await {
+            tracing::error!("Failed to send password reset mail: {}", e);
+        }
+
+        Ok(())
+    }
+
+    pub async fn reset_password(
+        &self,
+        account_service: &AccountService,
+        password_service: &PasswordService,
+        session_service: &SessionService,
+        reset: ResetPasswordRequestDto,
+    ) -> Result<(), AuthenticationServiceError> {
+        let token_hash = self.hash_token(&reset.token);
+        let tokens: Vec<PasswordResetTokenModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE token_hash = $token_hash AND expires_at > time::now()")
+            .bind(("table", PasswordResetTokenModel::table_name()))
+            .bind(("token_hash", token_hash.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+        let token = tokens
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidPasswordResetToken,
+            ))?;
+
+        // A rejected password leaves the link usable, so the user can pick another one
+        let account = account_service.get_account_by_id(&token.account_id).await?;
+        account_service
+            .validate_new_password(password_service, &account, &reset.new_password)
+            .await?;
+
+        // Only one of several concurrent resets with the same token gets past this
+        let consumed: Vec<PasswordResetTokenModel> = self
+            .database_connection
+            .query("DELETE FROM type::table($table) WHERE token_hash = $token_hash AND expires_at > time::now() RETURN BEFORE")
+            .bind(("table", PasswordResetTokenModel::table_name()))
+            .bind(("token_hash", token_hash))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+        if consumed.is_empty() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidPasswordResetToken,
+            ));
+        }
+
+        account_service
+            .store_password(password_service, account, &reset.new_password)
+            .await?;
+        account_service.unlock_account(&token.account_id).await?;
+        self.delete_tokens_for_account(&token.account_id).await?;
+
+        session_service
+            .deactivate_all_sessions_for_account(&token.account_id)
+            .await?;
+
+        Ok(())
+    }
+}
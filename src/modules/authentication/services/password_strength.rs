@@ -0,0 +1,257 @@
+const COMMON_PASSWORDS: &[&str] = &[
+    "password",
+    "qwerty",
+    "letmein",
+    "welcome",
+    "admin",
+    "iloveyou",
+    "monkey",
+    "dragon",
+    "football",
+    "baseball",
+    "sunshine",
+    "princess",
+    "master",
+    "shadow",
+    "login",
+    "abc123",
+    "trustno",
+    "starwars",
+    "whatever",
+    "superman",
+    "batman",
+    "michael",
+    "jennifer",
+    "hello",
+    "freedom",
+    "secret",
+    "summer",
+    "winter",
+    "spring",
+    "autumn",
+    "computer",
+    "internet",
+    "changeme",
+    "default",
+    "guest",
+    "root",
+    "pass",
+    "love",
+    "money",
+    "charlie",
+    "ninja",
+    "mustang",
+    "access",
+    "flower",
+    "hunter",
+    "killer",
+    "soccer",
+    "hockey",
+    "ranger",
+    "buster",
+    "thomas",
+    "jordan",
+    "pepper",
+    "ginger",
+    "cookie",
+    "cheese",
+    "matrix",
+    "orange",
+    "banana",
+    "apple",
+    "chocolate",
+    "samsung",
+    "google",
+    "qazwsx",
+    "zaq12wsx",
+    "asdf",
+    "test",
+];
+
+const KEYBOARD_ROWS: &[&str] = &[
+    "1234567890",
+    "qwertyuiop",
+    "asdfghjkl",
+    "zxcvbnm",
+    "qwertzuiop",
+    "yxcvbnm",
+    "azertyuiop",
+    "qsdfghjklm",
+    "wxcvbn",
+];
+
+/// Guesses contributed by a character that continues a repeat, sequence or keyboard walk.
+const PATTERN_GUESSES_LOG10: f64 = 0.3;
+const MIN_USER_INPUT_LENGTH: usize = 3;
+
+/// Offline strength estimate in the spirit of zxcvbn, from 0 to 4 with its thresholds of
+/// 10^3, 10^6, 10^8 and 10^10 guesses. Dictionary words, repeats, sequences and keyboard walks
+/// are cheap to guess, the remaining characters count as brute force over the classes in use.
+pub fn score(password: &str, user_inputs: &[&str]) -> u8 {
+    score_for_guesses(estimate_guesses_log10(password, user_inputs))
+}
+
+fn score_for_guesses(guesses_log10: f64) -> u8 {
+    match guesses_log10 {
+        guesses if guesses < 3.0 => 0,
+        guesses if guesses < 6.0 => 1,
+        guesses if guesses < 8.0 => 2,
+        guesses if guesses < 10.0 => 3,
+        _ => 4,
+    }
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' | '!' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        other => other,
+    }
+}
+
+fn cardinality(password: &str) -> f64 {
+    let mut cardinality = 0;
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        cardinality += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        cardinality += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        cardinality += 10;
+    }
+    if password
+        .chars()
+        .any(|c| c.is_ascii_punctuation() || c == ' ')
+    {
+        cardinality += 33;
+    }
+    if !password.is_ascii() {
+        cardinality += 100;
+    }
+
+    cardinality.max(1) as f64
+}
+
+fn continues_pattern(previous: char, current: char) -> bool {
+    let (previous, current) = (previous.to_ascii_lowercase(), current.to_ascii_lowercase());
+    if previous == current || (previous as i64 - current as i64).abs() == 1 {
+        return true;
+    }
+
+    KEYBOARD_ROWS.iter().any(|row| {
+        row.find(previous).is_some_and(|index| {
+            row[index + 1..].starts_with(current) || row[..index].ends_with(current)
+        })
+    })
+}
+
+fn estimate_guesses_log10(password: &str, user_inputs: &[&str]) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    let normalized: Vec<char> = chars
+        .iter()
+        .map(|c| unleet(c.to_lowercase().next().unwrap_or(*c)))
+        .collect();
+    let mut covered = vec![false; chars.len()];
+    let mut guesses_log10 = 0.0;
+
+    // User inputs are weighted like the most common passwords
+    let user_inputs: Vec<Vec<char>> = user_inputs
+        .iter()
+        .map(|input| {
+            input
+                .to_lowercase()
+                .chars()
+                .map(unleet)
+                .collect::<Vec<char>>()
+        })
+        .filter(|input| input.len() >= MIN_USER_INPUT_LENGTH)
+        .collect();
+    let common_passwords: Vec<Vec<char>> = COMMON_PASSWORDS
+        .iter()
+        .map(|word| word.chars().map(unleet).collect())
+        .collect();
+
+    for (rank, word) in user_inputs
+        .iter()
+        .map(|word| (0, word))
+        .chain(common_passwords.iter().enumerate().map(|(i, w)| (i + 1, w)))
+    {
+        let mut start = 0;
+        while start + word.len() <= normalized.len() {
+            let end = start + word.len();
+            if normalized[start..end] == word[..] && !covered[start..end].iter().any(|c| *c) {
+                covered[start..end].iter_mut().for_each(|c| *c = true);
+                guesses_log10 += ((rank + 1) as f64).log10() + 1.0;
+                if chars[start..end].iter().any(|c| c.is_uppercase()) {
+                    guesses_log10 += PATTERN_GUESSES_LOG10;
+                }
+                start = end;
+            } else {
+                start += 1;
+            }
+        }
+    }
+
+    let brute_force_log10 = cardinality(password).log10();
+    for (i, c) in chars.iter().enumerate() {
+        if covered[i] {
+            continue;
+        }
+
+        guesses_log10 += if i > 0 && !covered[i - 1] && continues_pattern(chars[i - 1], *c) {
+            PATTERN_GUESSES_LOG10
+        } else {
+            brute_force_log10
+        };
+    }
+
+    guesses_log10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_thresholds() {
+        assert_eq!(score_for_guesses(0.0), 0);
+        assert_eq!(score_for_guesses(2.99), 0);
+        assert_eq!(score_for_guesses(3.0), 1);
+        assert_eq!(score_for_guesses(5.99), 1);
+        assert_eq!(score_for_guesses(6.0), 2);
+        assert_eq!(score_for_guesses(7.99), 2);
+        assert_eq!(score_for_guesses(8.0), 3);
+        assert_eq!(score_for_guesses(9.99), 3);
+        assert_eq!(score_for_guesses(10.0), 4);
+    }
+
+    #[test]
+    fn common_passwords_and_patterns_score_low() {
+        assert_eq!(score("", &[]), 0);
+        assert_eq!(score("password", &[]), 0);
+        assert_eq!(score("P@ssw0rd", &[]), 0);
+        assert!(score("aaaaaaaaaaaa", &[]) <= 1);
+        assert!(score("123456789012", &[]) <= 1);
+        assert!(score("qwertyuiop", &[]) <= 1);
+    }
+
+    #[test]
+    fn user_inputs_count_as_dictionary_words() {
+        let with_username = score("jdoe_gardener", &["jdoe_gardener"]);
+        let without_username = score("jdoe_gardener", &[]);
+
+        assert_eq!(with_username, 0);
+        assert!(without_username > with_username);
+    }
+
+    #[test]
+    fn random_passwords_score_high() {
+        assert_eq!(score("x7#Qp9!vLm2$Rt8&", &[]), 4);
+        assert!(score("tPq8Zw3k", &[]) >= 2);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            config::authentication::JwtConfiguration, dtos::oauth::CreateRevokedTokenOptions,
+            errors::service::*, models::revoked_token::RevokedTokenModel,
+        },
+        base::exports::{BaseDateTime, DatabaseConnection},
+    },
+};
+use chrono::{DateTime, Utc};
+
+pub struct RevocationService {
+    database_connection: DatabaseConnection,
+    jwt_config: JwtConfiguration,
+}
+
+impl RevocationService {
+    pub fn new(jwt_config: JwtConfiguration, database_connection: DatabaseConnection) -> Self {
+        Self {
+            database_connection,
+            jwt_config,
+        }
+    }
+
+    /// Denies a token id until the token expires, entries are kept for the verification leeway
+    /// so a token cannot come back while it is still accepted.
+    pub async fn revoke_token_id(
+        &self,
+        token_id: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AuthenticationServiceError> {
+        if self.is_token_id_revoked(token_id).await? {
+            return Ok(());
+        }
+
+        self.delete_expired().await?;
+
+        let _: Vec<RevokedTokenModel> = self
+            .database_connection
+            .insert(RevokedTokenModel::table_name())
+            .content(CreateRevokedTokenOptions {
+                token_id: token_id.to_string(),
+                expires_at: BaseDateTime::from(
+                    expires_at + chrono::Duration::seconds(self.jwt_config.leeway_seconds as i64),
+                ),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    pub async fn is_token_id_revoked(
+        &self,
+        token_id: &str,
+    ) -> Result<bool, AuthenticationServiceError> {
+        let revoked_tokens: Vec<RevokedTokenModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE token_id = $token_id LIMIT 1")
+            .bind(("table", RevokedTokenModel::table_name()))
+            .bind(("token_id", token_id.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(!revoked_tokens.is_empty())
+    }
+
+    /// Rejects revoked token ids with the same error as an invalid token.
+    pub async fn ensure_not_revoked(
+        &self,
+        token_id: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        if self.is_token_id_revoked(token_id).await? {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidAccessToken,
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn delete_expired(&self) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE expires_at < time::now()")
+            .bind(("table", RevokedTokenModel::table_name()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,398 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            dtos::{
+                account::CreateAccountRequestDTO,
+                role::{CreateAccountRoleOptions, CreateRoleRequestDTO, UpdateRoleRequestDTO},
+            },
+            errors::service::*,
+            models::{account_role::AccountRoleModel, role::RoleModel},
+            services::{account::AccountService, password::PasswordService},
+        },
+        base::exports::{BaseId, DatabaseConnection},
+    },
+};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+pub const WILDCARD_PERMISSION: &str = "*";
+pub const ADMIN_ROLE_KEY: &str = "admin";
+
+#[derive(Debug, Clone)]
+pub struct RoleService {
+    database_connection: DatabaseConnection,
+}
+
+impl RoleService {
+    pub fn new(database_connection: DatabaseConnection) -> Self {
+        Self {
+            database_connection,
+        }
+    }
+
+    pub fn admin_role_id() -> BaseId {
+        BaseId::from((RoleModel::table_name(), ADMIN_ROLE_KEY))
+    }
+
+    pub async fn get_all_roles(&self) -> Result<Vec<RoleModel>, AuthenticationServiceError> {
+        let roles: Vec<RoleModel> = self
+            .database_connection
+            .select(RoleModel::table_name())
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(roles)
+    }
+
+    pub async fn get_role_by_id(
+        &self,
+        role_id: &BaseId,
+    ) -> Result<RoleModel, AuthenticationServiceError> {
+        let role: Option<RoleModel> = self
+            .database_connection
+            .select(role_id)
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        role.ok_or(AuthenticationServiceError::client(
+            AuthenticationClientError::RoleNotFound,
+        ))
+    }
+
+    pub async fn get_role_by_name(
+        &self,
+        name: &str,
+    ) -> Result<RoleModel, AuthenticationServiceError> {
+        let roles: Vec<RoleModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE name = $name LIMIT 1")
+            .bind(("table", RoleModel::table_name()))
+            .bind(("name", name.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(|_| {
+                AuthenticationServiceError::client(AuthenticationClientError::RoleNotFound)
+            })?;
+
+        roles
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::RoleNotFound,
+            ))
+    }
+
+    pub async fn exists_role_name(&self, name: &str) -> Result<bool, AuthenticationServiceError> {
+        match self.get_role_by_name(name).await {
+            Err(AuthenticationServiceError::ClientError(
+                AuthenticationClientError::RoleNotFound,
+            )) => Ok(false),
+            Err(e) => Err(e),
+            _ => Ok(true),
+        }
+    }
+
+    pub async fn create_role(
+        &self,
+        create_role: CreateRoleRequestDTO,
+    ) -> Result<RoleModel, AuthenticationServiceError> {
+        if self.exists_role_name(&create_role.name).await? {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::RoleAlreadyExists,
+            ));
+        }
+
+        let created_roles: Vec<RoleModel> = self
+            .database_connection
+            .insert(RoleModel::table_name())
+            .content(create_role)
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        created_roles
+            .first()
+            .cloned()
+            .ok_or(AuthenticationServiceError::ServerError(anyhow::anyhow!(
+                "Role creation failed without a specific error."
+            )))
+    }
+
+    pub async fn update_role(
+        &self,
+        role_id: &BaseId,
+        update_role: UpdateRoleRequestDTO,
+    ) -> Result<RoleModel, AuthenticationServiceError> {
+        let mut role = self.get_role_by_id(role_id).await?;
+
+        if let Some(name) = update_role.name {
+            if name != role.name && self.exists_role_name(&name).await? {
+                return Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::RoleAlreadyExists,
+                ));
+            }
+            role.name = name;
+        }
+
+        if let Some(description) = update_role.description {
+            role.description = description;
+        }
+
+        if let Some(permissions) = update_role.permissions {
+            role.permissions = permissions;
+        }
+
+        let roles: Vec<RoleModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET name = $name, description = $description, permissions = $permissions WHERE id = $id RETURN AFTER")
+            .bind(("table", RoleModel::table_name()))
+            .bind(("id", role_id.clone()))
+            .bind(("name", role.name))
+            .bind(("description", role.description))
+            .bind(("permissions", role.permissions))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        roles
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::RoleNotFound,
+            ))
+    }
+
+    pub async fn delete_role(&self, role_id: &BaseId) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE role_id = $role_id")
+            .bind(("table", AccountRoleModel::table_name()))
+            .bind(("role_id", role_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let _: Option<RoleModel> = self
+            .database_connection
+            .delete(role_id)
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    pub async fn get_roles_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<Vec<RoleModel>, AuthenticationServiceError> {
+        let roles: Vec<RoleModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE id IN (SELECT VALUE role_id FROM type::table($account_roles_table) WHERE account_id = $account_id)")
+            .bind(("table", RoleModel::table_name()))
+            .bind(("account_roles_table", AccountRoleModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(roles)
+    }
+
+    pub async fn get_permissions_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<Vec<String>, AuthenticationServiceError> {
+        let mut permissions: Vec<String> = self
+            .get_roles_for_account(account_id)
+            .await?
+            .into_iter()
+            .flat_map(|role| role.permissions)
+            .collect();
+
+        permissions.sort();
+        permissions.dedup();
+
+        Ok(permissions)
+    }
+
+    pub fn permission_matches(granted: &str, required: &str) -> bool {
+        if granted == WILDCARD_PERMISSION || granted == required {
+            return true;
+        }
+
+        granted
+            .strip_suffix(":*")
+            .is_some_and(|scope| required.split(':').next() == Some(scope))
+    }
+
+    pub async fn account_has_permission(
+        &self,
+        account_id: &BaseId,
+        permission: &str,
+    ) -> Result<bool, AuthenticationServiceError> {
+        let permissions = self.get_permissions_for_account(account_id).await?;
+
+        Ok(permissions
+            .iter()
+            .any(|granted| Self::permission_matches(granted, permission)))
+    }
+
+    pub async fn assign_role(
+        &self,
+        account_id: &BaseId,
+        role_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.get_role_by_id(role_id).await?;
+
+        let existing: Vec<AccountRoleModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE account_id = $account_id AND role_id = $role_id LIMIT 1")
+            .bind(("table", AccountRoleModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .bind(("role_id", role_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        if !existing.is_empty() {
+            return Ok(());
+        }
+
+        let _: Vec<AccountRoleModel> = self
+            .database_connection
+            .insert(AccountRoleModel::table_name())
+            .content(CreateAccountRoleOptions {
+                account_id: account_id.clone(),
+                role_id: role_id.clone(),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    pub async fn unassign_role(
+        &self,
+        account_id: &BaseId,
+        role_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE account_id = $account_id AND role_id = $role_id")
+            .bind(("table", AccountRoleModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .bind(("role_id", role_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    pub async fn unassign_all_roles_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE account_id = $account_id")
+            .bind(("table", AccountRoleModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    /// Creates the configured admin accounts that do not exist yet. Existing accounts are never
+    /// promoted, anyone could have registered a configured name through public sign-up.
+    pub async fn ensure_admin_accounts(
+        &self,
+        account_service: &AccountService,
+        password_service: &PasswordService,
+        usernames: &[String],
+    ) -> Result<(), AuthenticationServiceError> {
+        let admin_role_id = Self::admin_role_id();
+
+        for username in usernames {
+            match account_service.get_account_by_username(username).await {
+                Ok(account) => {
+                    let is_admin = self
+                        .get_roles_for_account(&account.id)
+                        .await?
+                        .iter()
+                        .any(|role| role.id == admin_role_id);
+                    if !is_admin {
+                        tracing::warn!(
+                            "Configured admin account '{}' was registered without the admin role and is not promoted, assign the role through /role if the account is yours",
+                            username
+                        );
+                    }
+                }
+                Err(AuthenticationServiceError::ClientError(
+                    AuthenticationClientError::AccountNotFound,
+                )) => {
+                    use rand::Rng;
+                    let secret: [u8; 18] = rand::rng().random();
+                    let password = URL_SAFE_NO_PAD.encode(secret);
+
+                    let account = account_service
+                        .create_account(
+                            password_service,
+                            CreateAccountRequestDTO {
+                                username: username.clone(),
+                                password: password.clone(),
+                                email: None,
+                            },
+                        )
+                        .await?;
+                    account_service.require_password_change(&account.id).await?;
+                    self.assign_role(&account.id, &admin_role_id).await?;
+
+                    tracing::warn!(
+                        "Created admin account '{}' with the one-time password {}, it has to be changed on the first sign-in",
+                        username,
+                        password
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_granted_permissions() {
+        let cases = [
+            ("*", "accounts:read", true),
+            ("*", "roles:write", true),
+            ("accounts:read", "accounts:read", true),
+            ("accounts:read", "accounts:write", false),
+            ("accounts:*", "accounts:read", true),
+            ("accounts:*", "accounts:delete", true),
+            ("accounts:*", "roles:read", false),
+            // Scope wildcards only cover their own scope, not others sharing a prefix
+            ("account:*", "accounts:read", false),
+            ("accounts:*", "accountsx:read", false),
+            ("x:*", "xy:read", false),
+            ("accounts", "accounts:read", false),
+            ("accounts:read:*", "accounts:read", false),
+            ("", "accounts:read", false),
+        ];
+
+        for (granted, required, expected) in cases {
+            assert_eq!(
+                RoleService::permission_matches(granted, required),
+                expected,
+                "{} granting {}",
+                granted,
+                required
+            );
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            dtos::{
+                oauth::TokenResponseDto,
+                service_account::{
+                    CreateServiceAccountOptions, CreateServiceAccountRequestDto,
+                    UpdateServiceAccountRequestDto,
+                },
+            },
+            errors::service::*,
+            models::service_account::ServiceAccountModel,
+            services::token::{FIRST_PARTY_AUDIENCE, TokenOpts, TokenService},
+        },
+        base::exports::{BaseId, DatabaseConnection},
+    },
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sha2::{Digest, Sha256};
+
+const TOKEN_TYPE_BEARER: &str = "Bearer";
+
+pub struct ServiceAccountService {
+    database_connection: DatabaseConnection,
+}
+
+impl ServiceAccountService {
+    pub fn new(database_connection: DatabaseConnection) -> Self {
+        Self {
+            database_connection,
+        }
+    }
+
+    fn generate_secret(&self) -> String {
+        use rand::Rng;
+        let secret: [u8; 32] = rand::rng().random();
+        URL_SAFE_NO_PAD.encode(secret)
+    }
+
+    fn hash_secret(&self, secret: &str) -> String {
+        Sha256::digest(secret.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    fn normalize_scopes(mut scopes: Vec<String>) -> Vec<String> {
+        scopes.sort();
+        scopes.dedup();
+        scopes
+    }
+
+    async fn exists_service_account_name(
+        &self,
+        name: &str,
+    ) -> Result<bool, AuthenticationServiceError> {
+        let service_accounts: Vec<ServiceAccountModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE name = $name")
+            .bind(("table", ServiceAccountModel::table_name()))
+            .bind(("name", name.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(!service_accounts.is_empty())
+    }
+
+    pub async fn get_all_service_accounts(
+        &self,
+    ) -> Result<Vec<ServiceAccountModel>, AuthenticationServiceError> {
+        let service_accounts: Vec<ServiceAccountModel> = self
+            .database_connection
+            .select(ServiceAccountModel::table_name())
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(service_accounts)
+    }
+
+    pub async fn get_service_account_by_id(
+        &self,
+        service_account_id: &BaseId,
+    ) -> Result<ServiceAccountModel, AuthenticationServiceError> {
+        let service_account: Option<ServiceAccountModel> = self
+            .database_connection
+            .select(service_account_id)
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        service_account.ok_or(AuthenticationServiceError::client(
+            AuthenticationClientError::ServiceAccountNotFound,
+        ))
+    }
+
+    /// Returns the service account together with its plain client secret, which is only shown once.
+    pub async fn create_service_account(
+        &self,
+        create_service_account: CreateServiceAccountRequestDto,
+    ) -> Result<(ServiceAccountModel, String), AuthenticationServiceError> {
+        if self
+            .exists_service_account_name(&create_service_account.name)
+            .await?
+        {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::ServiceAccountAlreadyExists,
+            ));
+        }
+
+        let client_secret = self.generate_secret();
+        let created_service_accounts: Vec<ServiceAccountModel> = self
+            .database_connection
+            .insert(ServiceAccountModel::table_name())
+            .content(CreateServiceAccountOptions {
+                name: create_service_account.name,
+                description: create_service_account.description,
+                client_secret_hash: self.hash_secret(&client_secret),
+                scopes: Self::normalize_scopes(create_service_account.scopes),
+                is_active: true,
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let service_account = created_service_accounts.into_iter().next().ok_or(
+            AuthenticationServiceError::ServerError(anyhow::anyhow!(
+                "Service account creation failed without a specific error."
+            )),
+        )?;
+
+        Ok((service_account, client_secret))
+    }
+
+    pub async fn update_service_account(
+        &self,
+        service_account_id: &BaseId,
+        update_service_account: UpdateServiceAccountRequestDto,
+    ) -> Result<ServiceAccountModel, AuthenticationServiceError> {
+        let mut service_account = self.get_service_account_by_id(service_account_id).await?;
+
+        if let Some(name) = update_service_account.name {
+            if name != service_account.name && self.exists_service_account_name(&name).await? {
+                return Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::ServiceAccountAlreadyExists,
+                ));
+            }
+            service_account.name = name;
+        }
+
+        if let Some(description) = update_service_account.description {
+            service_account.description = description;
+        }
+
+        if let Some(scopes) = update_service_account.scopes {
+            service_account.scopes = Self::normalize_scopes(scopes);
+        }
+
+        if let Some(is_active) = update_service_account.is_active {
+            service_account.is_active = is_active;
+        }
+
+        let service_accounts: Vec<ServiceAccountModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET name = $name, description = $description, scopes = $scopes, is_active = $is_active WHERE id = $id RETURN AFTER")
+            .bind(("table", ServiceAccountModel::table_name()))
+            .bind(("id", service_account_id.clone()))
+            .bind(("name", service_account.name))
+            .bind(("description", service_account.description))
+            .bind(("scopes", service_account.scopes))
+            .bind(("is_active", service_account.is_active))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        service_accounts
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::ServiceAccountNotFound,
+            ))
+    }
+
+    /// Replaces the client secret, tokens issued with the old secret stay valid until they expire.
+    pub async fn rotate_secret(
+        &self,
+        service_account_id: &BaseId,
+    ) -> Result<(ServiceAccountModel, String), AuthenticationServiceError> {
+        let client_secret = self.generate_secret();
+        let service_accounts: Vec<ServiceAccountModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET client_secret_hash = $client_secret_hash WHERE id = $id RETURN AFTER")
+            .bind(("table", ServiceAccountModel::table_name()))
+            .bind(("id", service_account_id.clone()))
+            .bind(("client_secret_hash", self.hash_secret(&client_secret)))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let service_account =
+            service_accounts
+                .into_iter()
+                .next()
+                .ok_or(AuthenticationServiceError::client(
+                    AuthenticationClientError::ServiceAccountNotFound,
+                ))?;
+
+        Ok((service_account, client_secret))
+    }
+
+    pub async fn delete_service_account(
+        &self,
+        service_account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        let deleted: Option<ServiceAccountModel> = self
+            .database_connection
+            .delete(service_account_id)
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        deleted
+            .map(|_| ())
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::ServiceAccountNotFound,
+            ))
+    }
+
+    /// Checks the client credentials of a service account, disabled accounts are rejected.
+    pub async fn authenticate(
+        &self,
+        client_id: &str,
+        client_secret: &str,
+    ) -> Result<ServiceAccountModel, AuthenticationServiceError> {
+        let invalid_client =
+            || AuthenticationServiceError::client(AuthenticationClientError::InvalidClient);
+
+        let service_account_id =
+            ServiceAccountModel::from_named_format(client_id).ok_or_else(invalid_client)?;
+        let service_account = match self.get_service_account_by_id(&service_account_id).await {
+            Err(AuthenticationServiceError::ClientError(_)) => return Err(invalid_client()),
+            other => other?,
+        };
+
+        if !service_account.is_active
+            || service_account.client_secret_hash != self.hash_secret(client_secret)
+        {
+            return Err(invalid_client());
+        }
+
+        Ok(service_account)
+    }
+
+    /// Requested scopes must be a subset of the service account's scopes, none requested grants all.
+    fn resolve_scopes(
+        &self,
+        service_account: &ServiceAccountModel,
+        scope: Option<&str>,
+    ) -> Result<Vec<String>, AuthenticationServiceError> {
+        let requested: Vec<String> = scope
+            .map(|scope| scope.split_whitespace().map(String::from).collect())
+            .unwrap_or_default();
+
+        if requested.is_empty() {
+            return Ok(service_account.scopes.clone());
+        }
+
+        if requested
+            .iter()
+            .any(|scope| !service_account.scopes.contains(scope))
+        {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidScope,
+            ));
+        }
+
+        Ok(Self::normalize_scopes(requested))
+    }
+
+    /// Client credentials grant, the access token carries the service identity and no refresh token is issued.
+    pub fn issue_token(
+        &self,
+        token_service: &TokenService,
+        service_account: &ServiceAccountModel,
+        scope: Option<&str>,
+    ) -> Result<TokenResponseDto, AuthenticationServiceError> {
+        let scopes = self.resolve_scopes(service_account, scope)?;
+        let (access_token, expires_at) = token_service.generate_jwt(
+            TokenOpts::for_service_account(
+                ServiceAccountModel::to_named_format(&service_account.id),
+                FIRST_PARTY_AUDIENCE.to_string(),
+            )
+            .with_scopes(scopes.clone()),
+        )?;
+
+        Ok(TokenResponseDto {
+            access_token,
+            token_type: TOKEN_TYPE_BEARER.to_string(),
+            expires_in: (expires_at - chrono::Utc::now()).num_seconds().max(0),
+            refresh_token: None,
+            scope: scopes.join(" "),
+            id_token: None,
+        })
+    }
+}
@@ -6,17 +6,26 @@ use crate::{
             dtos::{authentication::AuthenticationResponseDto, session::CreateSessionOptions},
             errors::service::*,
             models::{account::AccountModel, session::SessionModel},
-            services::token::{TokenOpts, TokenService},
+            services::{
+                session_cache::SessionCache,
+                token::{FIRST_PARTY_AUDIENCE, TokenOpts, TokenService},
+                user_agent,
+            },
         },
         base::exports::{
             BaseDateTime, BaseId, DatabaseConnection, request_info::RequestInfoExtractor,
         },
     },
 };
+use chrono::{DateTime, Utc};
+
+/// Retired refresh hashes kept per session for reuse detection, older ones are simply unknown.
+const MAX_PREVIOUS_REFRESH_HASHES: usize = 20;
 
 pub struct SessionService {
     database_connection: DatabaseConnection,
     authentication_config: AuthenticationConfiguration,
+    session_cache: SessionCache,
 }
 
 impl SessionService {
@@ -26,6 +35,7 @@ impl SessionService {
     ) -> Self {
         SessionService {
             database_connection,
+            session_cache: SessionCache::new(authentication_config.session_cache_seconds),
             authentication_config,
         }
     }
@@ -60,6 +70,41 @@ impl SessionService {
         ))
     }
 
+    pub fn validate_session(
+        &self,
+        session: &SessionModel,
+    ) -> Result<(), AuthenticationServiceError> {
+        if !session.is_active {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::SessionNotFound,
+            ));
+        }
+
+        if session.expires_at <= BaseDateTime::from(chrono::Utc::now()) {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::ExpiredRefreshToken,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_live_session(
+        &self,
+        session_id: &BaseId,
+    ) -> Result<SessionModel, AuthenticationServiceError> {
+        if let Some(session) = self.session_cache.get(session_id) {
+            self.validate_session(&session)?;
+            return Ok(session);
+        }
+
+        let session = self.get_session_by_id(session_id).await?;
+        self.validate_session(&session)?;
+        self.session_cache.insert(&session);
+
+        Ok(session)
+    }
+
     pub async fn get_all_sessions_for_account(
         &self,
         account_id: &BaseId,
@@ -103,30 +148,147 @@ impl SessionService {
             ))
     }
 
+    pub async fn get_session_by_previous_refresh_token_hash(
+        &self,
+        refresh_token_hash: String,
+    ) -> Result<Option<SessionModel>, AuthenticationServiceError> {
+        let session_vec: Vec<SessionModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE previous_refresh_hashes CONTAINS $hash LIMIT 1")
+            .bind(("table", SessionModel::table_name()))
+            .bind(("hash", refresh_token_hash))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(session_vec.into_iter().next())
+    }
+
+    /// Only rotates if the session still has the refresh hash it was read with, so the retired
+    /// hashes written back cannot overwrite those of a concurrent rotation.
+    pub async fn rotate_refresh_hash(
+        &self,
+        session: &SessionModel,
+        new_refresh_hash: String,
+        expires_at: BaseDateTime,
+    ) -> Result<bool, AuthenticationServiceError> {
+        let previous_hashes = retire_refresh_hash(
+            session.previous_refresh_hashes.clone(),
+            session.refresh_hash.clone(),
+        );
+        let sessions: Vec<SessionModel> = self.database_connection
+            .query("UPDATE type::table($table) SET previous_refresh_hashes = $previous_hashes, refresh_hash = $new_hash, expires_at = $expires_at WHERE id = $id AND refresh_hash = $old_hash RETURN AFTER")
+            .bind(("table", SessionModel::table_name()))
+            .bind(("id", session.id.clone()))
+            .bind(("old_hash", session.refresh_hash.clone()))
+            .bind(("new_hash", new_refresh_hash))
+            .bind(("expires_at", expires_at))
+            .bind(("previous_hashes", previous_hashes))
+            .await.map_err(AuthenticationServiceError::from_error)?
+            .take(0).map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(!sessions.is_empty())
+    }
+
     pub async fn create_session(
         &self,
         token_service: &TokenService,
         account_id: &BaseId,
         request_info: RequestInfoExtractor,
-        service: String,
+        audience: String,
+    ) -> Result<AuthenticationResponseDto, AuthenticationServiceError> {
+        self.create_session_with_scopes(
+            token_service,
+            account_id,
+            request_info,
+            audience,
+            Vec::new(),
+        )
+        .await
+    }
+
+    pub async fn create_session_with_scopes(
+        &self,
+        token_service: &TokenService,
+        account_id: &BaseId,
+        request_info: RequestInfoExtractor,
+        audience: String,
+        scopes: Vec<String>,
     ) -> Result<AuthenticationResponseDto, AuthenticationServiceError> {
-        let refresh_token = token_service.generate_refresh_token();
-        let refresh_token_hash = token_service.hash_refresh_token(&refresh_token);
         let refresh_expires_at = chrono::Utc::now()
             + chrono::Duration::days(
                 self.authentication_config.refresh_token_expiration_days as i64,
             );
 
+        self.insert_session(
+            token_service,
+            account_id,
+            request_info,
+            audience,
+            scopes,
+            None,
+            refresh_expires_at,
+        )
+        .await
+    }
+
+    /// Opens a first party session for `account_id` on behalf of an admin. The session cannot be
+    /// refreshed beyond the configured impersonation lifetime.
+    pub async fn create_impersonation_session(
+        &self,
+        token_service: &TokenService,
+        account_id: &BaseId,
+        impersonator_id: &BaseId,
+        request_info: RequestInfoExtractor,
+    ) -> Result<AuthenticationResponseDto, AuthenticationServiceError> {
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::seconds(
+                self.authentication_config
+                    .impersonation
+                    .session_expiration_seconds as i64,
+            );
+
+        self.insert_session(
+            token_service,
+            account_id,
+            request_info,
+            FIRST_PARTY_AUDIENCE.to_string(),
+            Vec::new(),
+            Some(impersonator_id.clone()),
+            expires_at,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_session(
+        &self,
+        token_service: &TokenService,
+        account_id: &BaseId,
+        request_info: RequestInfoExtractor,
+        audience: String,
+        scopes: Vec<String>,
+        impersonator_id: Option<BaseId>,
+        refresh_expires_at: DateTime<Utc>,
+    ) -> Result<AuthenticationResponseDto, AuthenticationServiceError> {
+        let refresh_token = token_service.generate_refresh_token();
+        let refresh_token_hash = token_service.hash_refresh_token(&refresh_token);
+
         let create_session: Vec<SessionModel> = self
             .database_connection
             .insert(SessionModel::table_name())
             .content(CreateSessionOptions {
                 account_id: account_id.clone(),
                 is_active: true,
+                device_label: user_agent::device_label(&request_info.user_agent),
                 ip_address: request_info.ip_address,
                 user_agent: request_info.user_agent,
                 expires_at: BaseDateTime::from(refresh_expires_at),
                 refresh_hash: refresh_token_hash,
+                audience: audience.clone(),
+                scopes: scopes.clone(),
+                impersonator_id: impersonator_id.clone(),
             })
             .await
             .map_err(AuthenticationServiceError::from_error)?;
@@ -138,12 +300,15 @@ impl SessionService {
         }
 
         let session = &create_session[0];
-        let (access_token, access_token_expires_at) =
-            token_service.generate_jwt(TokenOpts::new(
+        let (access_token, access_token_expires_at) = token_service.generate_jwt(
+            TokenOpts::new(
                 AccountModel::to_named_format(account_id),
                 SessionModel::to_named_format(&session.id),
-                service,
-            ))?;
+                audience,
+            )
+            .with_scopes(scopes)
+            .with_actor(impersonator_id.as_ref().map(AccountModel::to_named_format)),
+        )?;
 
         Ok(AuthenticationResponseDto {
             account_id: AccountModel::to_named_format(account_id),
@@ -155,6 +320,85 @@ impl SessionService {
         })
     }
 
+    async fn record_activity(
+        &self,
+        session_id: &BaseId,
+        request_info: &RequestInfoExtractor,
+    ) -> Result<Option<SessionModel>, AuthenticationServiceError> {
+        let sessions: Vec<SessionModel> = self.database_connection
+            .query("UPDATE type::table($table) SET last_used_at = time::now(), last_ip_address = $ip_address, device_label = $device_label WHERE id = $id RETURN AFTER")
+            .bind(("table", SessionModel::table_name()))
+            .bind(("id", session_id.clone()))
+            .bind(("ip_address", request_info.ip_address.clone()))
+            .bind(("device_label", user_agent::device_label(&request_info.user_agent)))
+            .await.map_err(AuthenticationServiceError::from_error)?
+            .take(0).map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(sessions.into_iter().next())
+    }
+
+    /// Records a request made with the session's access token. Writes are skipped while the last
+    /// one is recent and came from the same address, so busy clients do not write on every request.
+    pub async fn touch_session(
+        &self,
+        session: &SessionModel,
+        request_info: &RequestInfoExtractor,
+    ) -> Result<(), AuthenticationServiceError> {
+        let interval = chrono::Duration::seconds(
+            self.authentication_config
+                .session_activity
+                .update_interval_seconds as i64,
+        );
+        let last_used_at = session.last_used_at().clone().into_inner().0;
+        if last_used_at + interval > chrono::Utc::now()
+            && session.last_ip_address() == request_info.ip_address
+        {
+            return Ok(());
+        }
+
+        // The cached copy is replaced as well, otherwise it would look stale until it expires
+        if let Some(session) = self.record_activity(&session.id, request_info).await?
+            && self.session_cache.get(&session.id).is_some()
+        {
+            self.session_cache.insert(&session);
+        }
+
+        Ok(())
+    }
+
+    /// Names a session of the account, an empty name goes back to the device label.
+    pub async fn rename_session_for_account(
+        &self,
+        session_id: &BaseId,
+        account_id: &BaseId,
+        name: Option<&str>,
+    ) -> Result<SessionModel, AuthenticationServiceError> {
+        let name = name.map(str::trim).filter(|name| !name.is_empty());
+        let max_name_length = self.authentication_config.session_activity.max_name_length;
+        if name.is_some_and(|name| name.chars().count() > max_name_length) {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::SessionNameTooLong(max_name_length),
+            ));
+        }
+
+        let sessions: Vec<SessionModel> = self.database_connection
+            .query("UPDATE type::table($table) SET name = $name WHERE id = $id AND account_id = $account_id RETURN AFTER")
+            .bind(("table", SessionModel::table_name()))
+            .bind(("id", session_id.clone()))
+            .bind(("account_id", account_id.clone()))
+            .bind(("name", name.map(str::to_string)))
+            .await.map_err(AuthenticationServiceError::from_error)?
+            .take(0).map_err(AuthenticationServiceError::from_error)?;
+        self.session_cache.invalidate(session_id);
+
+        sessions
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::SessionNotFound,
+            ))
+    }
+
     pub async fn activate_session(
         &self,
         session_id: &BaseId,
@@ -200,6 +444,7 @@ impl SessionService {
             .map_err(|_| {
                 AuthenticationServiceError::client(AuthenticationClientError::SessionNotFound)
             })?;
+        self.session_cache.invalidate(session_id);
 
         Ok(!sessions.is_empty())
     }
@@ -216,6 +461,7 @@ impl SessionService {
             .bind(("account_id", account_id.clone()))
             .await.map_err(AuthenticationServiceError::from_error)?
             .take(0).map_err(|_| AuthenticationServiceError::client(AuthenticationClientError::SessionNotFound))?;
+        self.session_cache.invalidate(session_id);
 
         Ok(!sessions.is_empty())
     }
@@ -230,6 +476,7 @@ impl SessionService {
             .bind(("account_id", account_id.clone()))
             .await.map_err(AuthenticationServiceError::from_error)?
             .take(0).map_err(|_| AuthenticationServiceError::client(AuthenticationClientError::SessionNotFound))?;
+        self.session_cache.invalidate_account(account_id);
 
         Ok(!sessions.is_empty())
     }
@@ -238,24 +485,70 @@ impl SessionService {
         &self,
         token_service: &TokenService,
         refresh_token_hash: String,
-        service: String,
+        audience: String,
+        request_info: &RequestInfoExtractor,
     ) -> Result<AuthenticationResponseDto, AuthenticationServiceError> {
-        let session = self
-            .get_session_by_refresh_token_hash(refresh_token_hash)
-            .await?;
+        let session_res = self
+            .get_session_by_refresh_token_hash(refresh_token_hash.clone())
+            .await;
 
-        let refresh_token_expires_at = chrono::Utc::now()
-            + chrono::Duration::days(
-                self.authentication_config.refresh_token_expiration_days as i64,
-            );
+        let session = match session_res {
+            Err(AuthenticationServiceError::ClientError(
+                AuthenticationClientError::SessionNotFound,
+            )) => {
+                return Err(self.handle_refresh_token_reuse(refresh_token_hash).await);
+            }
+            other => other?,
+        };
+        self.validate_session(&session)?;
+
+        // Refresh tokens can only be redeemed by the client they were issued to
+        if session.audience != audience {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::SessionNotFound,
+            ));
+        }
+
+        // Impersonation sessions keep their original, short expiry
+        let refresh_token_expires_at = match session.impersonator_id {
+            Some(_) => session.expires_at.clone().into_inner().0,
+            None => {
+                chrono::Utc::now()
+                    + chrono::Duration::days(
+                        self.authentication_config.refresh_token_expiration_days as i64,
+                    )
+            }
+        };
 
         let refresh_token = token_service.generate_refresh_token();
-        let (access_token, access_token_expires_at) =
-            token_service.generate_jwt(TokenOpts::new(
+        let rotated = self
+            .rotate_refresh_hash(
+                &session,
+                token_service.hash_refresh_token(&refresh_token),
+                BaseDateTime::from(refresh_token_expires_at),
+            )
+            .await?;
+
+        if !rotated {
+            // Another request rotated this token first, so it is already retired.
+            return Err(self.handle_refresh_token_reuse(refresh_token_hash).await);
+        }
+        self.record_activity(&session.id, request_info).await?;
+
+        let (access_token, access_token_expires_at) = token_service.generate_jwt(
+            TokenOpts::new(
                 AccountModel::to_named_format(&session.account_id),
                 SessionModel::to_named_format(&session.id),
-                service,
-            ))?;
+                audience,
+            )
+            .with_scopes(session.scopes.clone())
+            .with_actor(
+                session
+                    .impersonator_id
+                    .as_ref()
+                    .map(AccountModel::to_named_format),
+            ),
+        )?;
 
         Ok(AuthenticationResponseDto {
             account_id: AccountModel::to_named_format(&session.account_id),
@@ -267,6 +560,33 @@ impl SessionService {
         })
     }
 
+    async fn handle_refresh_token_reuse(
+        &self,
+        refresh_token_hash: String,
+    ) -> AuthenticationServiceError {
+        let retired_session = match self
+            .get_session_by_previous_refresh_token_hash(refresh_token_hash)
+            .await
+        {
+            Ok(retired_session) => retired_session,
+            Err(e) => return e,
+        };
+
+        let (revoked_session_id, client_error) = refresh_token_reuse(retired_session.as_ref());
+        if let Some(session_id) = revoked_session_id {
+            tracing::warn!(
+                "Retired refresh token presented for session {}, revoking session",
+                SessionModel::to_named_format(session_id)
+            );
+
+            if let Err(e) = self.deactivate_session(session_id).await {
+                return e;
+            }
+        }
+
+        AuthenticationServiceError::client(client_error)
+    }
+
     pub async fn delete_session(
         &self,
         session_id: &BaseId,
@@ -276,6 +596,7 @@ impl SessionService {
             .delete(session_id)
             .await
             .map_err(AuthenticationServiceError::from_error)?;
+        self.session_cache.invalidate(session_id);
         Ok(())
     }
 
@@ -289,7 +610,142 @@ impl SessionService {
             .bind(("account_id", account_id.clone()))
             .await
             .map_err(AuthenticationServiceError::from_error)?;
+        self.session_cache.invalidate_account(account_id);
 
         Ok(())
     }
 }
+
+/// Retired hashes after rotating away from `retired_hash`, oldest first. Only the most recent
+/// ones are kept so long-lived sessions do not grow without bound.
+fn retire_refresh_hash(mut previous_hashes: Vec<String>, retired_hash: String) -> Vec<String> {
+    previous_hashes.push(retired_hash);
+    let excess = previous_hashes
+        .len()
+        .saturating_sub(MAX_PREVIOUS_REFRESH_HASHES);
+    previous_hashes.drain(..excess);
+    previous_hashes
+}
+
+/// A retired refresh token was either stolen or replayed, so the session it belonged to is
+/// revoked. Tokens that match no session only fail the refresh.
+fn refresh_token_reuse(
+    retired_session: Option<&SessionModel>,
+) -> (Option<&BaseId>, AuthenticationClientError) {
+    match retired_session {
+        Some(session) => (
+            Some(&session.id),
+            AuthenticationClientError::ReusedRefreshToken,
+        ),
+        None => (None, AuthenticationClientError::SessionNotFound),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(refresh_hash: &str, previous_refresh_hashes: Vec<String>) -> SessionModel {
+        SessionModel {
+            id: BaseId::from((SessionModel::table_name(), "test")),
+            account_id: BaseId::from((AccountModel::table_name(), "test")),
+            refresh_hash: refresh_hash.to_string(),
+            previous_refresh_hashes,
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: String::new(),
+            device_label: String::new(),
+            name: None,
+            last_used_at: None,
+            last_ip_address: None,
+            created_at: BaseDateTime::from(chrono::Utc::now()),
+            expires_at: BaseDateTime::from(chrono::Utc::now()),
+            is_active: true,
+            audience: FIRST_PARTY_AUDIENCE.to_string(),
+            scopes: Vec::new(),
+            impersonator_id: None,
+        }
+    }
+
+    /// Rotates a fresh session `rotations` times and returns it, hash `n` is the n-th issued one.
+    fn rotated_session(rotations: usize) -> SessionModel {
+        let mut session = session("hash-0", Vec::new());
+        for n in 1..=rotations {
+            session.previous_refresh_hashes = retire_refresh_hash(
+                session.previous_refresh_hashes.clone(),
+                session.refresh_hash.clone(),
+            );
+            session.refresh_hash = format!("hash-{}", n);
+        }
+        session
+    }
+
+    /// Mirrors the lookup of `get_session_by_previous_refresh_token_hash`.
+    fn retired_session<'a>(session: &'a SessionModel, hash: &str) -> Option<&'a SessionModel> {
+        session
+            .previous_refresh_hashes
+            .iter()
+            .any(|previous| previous == hash)
+            .then_some(session)
+    }
+
+    #[test]
+    fn keeps_every_hash_below_the_limit() {
+        let session = rotated_session(3);
+
+        assert_eq!(session.refresh_hash, "hash-3");
+        assert_eq!(
+            session.previous_refresh_hashes,
+            vec!["hash-0", "hash-1", "hash-2"]
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_most_recent_hashes() {
+        let session = rotated_session(MAX_PREVIOUS_REFRESH_HASHES + 5);
+
+        assert_eq!(
+            session.previous_refresh_hashes.len(),
+            MAX_PREVIOUS_REFRESH_HASHES
+        );
+        assert_eq!(session.previous_refresh_hashes[0], "hash-5");
+        assert_eq!(
+            session.previous_refresh_hashes.last().unwrap(),
+            &format!("hash-{}", MAX_PREVIOUS_REFRESH_HASHES + 4)
+        );
+        assert!(
+            !session
+                .previous_refresh_hashes
+                .contains(&session.refresh_hash)
+        );
+    }
+
+    #[test]
+    fn reused_recent_hash_revokes_the_session() {
+        let session = rotated_session(MAX_PREVIOUS_REFRESH_HASHES + 5);
+
+        for hash in ["hash-5", "hash-24"] {
+            let (revoked_session_id, client_error) =
+                refresh_token_reuse(retired_session(&session, hash));
+            assert_eq!(revoked_session_id, Some(&session.id));
+            assert!(matches!(
+                client_error,
+                AuthenticationClientError::ReusedRefreshToken
+            ));
+        }
+    }
+
+    #[test]
+    fn forgotten_or_unknown_hash_only_fails_the_refresh() {
+        let session = rotated_session(MAX_PREVIOUS_REFRESH_HASHES + 5);
+
+        for hash in ["hash-0", "hash-4", "never-issued"] {
+            let (revoked_session_id, client_error) =
+                refresh_token_reuse(retired_session(&session, hash));
+            assert_eq!(revoked_session_id, None);
+            assert!(matches!(
+                client_error,
+                AuthenticationClientError::SessionNotFound
+            ));
+        }
+    }
+}
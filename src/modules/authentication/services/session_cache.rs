@@ -0,0 +1,74 @@
+use crate::modules::{authentication::models::session::SessionModel, base::exports::BaseId};
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+static SESSION_CACHE: LazyLock<Mutex<HashMap<BaseId, CachedSession>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+struct CachedSession {
+    session: SessionModel,
+    cached_at: Instant,
+}
+
+pub struct SessionCache {
+    ttl: Duration,
+}
+
+impl SessionCache {
+    pub fn new(ttl_seconds: u64) -> Self {
+        Self {
+            ttl: Duration::from_secs(ttl_seconds),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    pub fn get(&self, session_id: &BaseId) -> Option<SessionModel> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let mut cache = SESSION_CACHE.lock().ok()?;
+        let cached = cache.get(session_id)?;
+        if cached.cached_at.elapsed() > self.ttl {
+            cache.remove(session_id);
+            return None;
+        }
+
+        Some(cached.session.clone())
+    }
+
+    pub fn insert(&self, session: &SessionModel) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if let Ok(mut cache) = SESSION_CACHE.lock() {
+            cache.retain(|_, cached| cached.cached_at.elapsed() <= self.ttl);
+            cache.insert(
+                session.id.clone(),
+                CachedSession {
+                    session: session.clone(),
+                    cached_at: Instant::now(),
+                },
+            );
+        }
+    }
+
+    pub fn invalidate(&self, session_id: &BaseId) {
+        if let Ok(mut cache) = SESSION_CACHE.lock() {
+            cache.remove(session_id);
+        }
+    }
+
+    pub fn invalidate_account(&self, account_id: &BaseId) {
+        if let Ok(mut cache) = SESSION_CACHE.lock() {
+            cache.retain(|_, cached| &cached.session.account_id != account_id);
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            config::authentication::{
+                AuthenticationConfiguration, JwtAlgorithm, SigningKeyConfiguration,
+            },
+            dtos::signing_key::{CreateSigningKeyOptions, CreateSigningKeyRequestDto},
+            errors::service::*,
+            models::signing_key::{SigningKeyModel, SigningKeyStatus},
+            services::{
+                encryption::EncryptionService,
+                keyring::{self, SigningKey},
+            },
+        },
+        base::exports::{BaseDateTime, DatabaseConnection},
+    },
+};
+use std::time::Duration;
+
+const SERVICE_NAME: &str = "SigningKeyService";
+
+pub struct SigningKeyService {
+    database_connection: DatabaseConnection,
+    signing_key_config: SigningKeyConfiguration,
+}
+
+impl SigningKeyService {
+    pub fn new(
+        signing_key_config: SigningKeyConfiguration,
+        database_connection: DatabaseConnection,
+    ) -> Self {
+        Self {
+            database_connection,
+            signing_key_config,
+        }
+    }
+
+    fn encryption_service(&self) -> Result<Option<EncryptionService>, AuthenticationServiceError> {
+        if self.signing_key_config.encryption_key.is_empty() {
+            return Ok(None);
+        }
+
+        EncryptionService::new(&self.signing_key_config.encryption_key).map(Some)
+    }
+
+    fn seal(&self, key_material: &str) -> Result<(String, bool), AuthenticationServiceError> {
+        match self.encryption_service()? {
+            Some(encryption_service) => Ok((encryption_service.encrypt(key_material)?, true)),
+            None => Ok((key_material.to_string(), false)),
+        }
+    }
+
+    fn unseal(&self, signing_key: &SigningKeyModel) -> Result<String, AuthenticationServiceError> {
+        if !signing_key.is_encrypted {
+            return Ok(signing_key.key_material.clone());
+        }
+
+        self.encryption_service()?
+            .ok_or_else(|| {
+                AuthenticationServiceError::ServerError(crate::log!(
+                    tracing::error,
+                    "{} Signing key '{}' is encrypted but no encryption key is configured",
+                    SERVICE_NAME,
+                    signing_key.kid
+                ))
+            })?
+            .decrypt(&signing_key.key_material)
+    }
+
+    pub async fn get_all_keys(&self) -> Result<Vec<SigningKeyModel>, AuthenticationServiceError> {
+        let signing_keys: Vec<SigningKeyModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) ORDER BY created_at DESC")
+            .bind(("table", SigningKeyModel::table_name()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(signing_keys)
+    }
+
+    pub async fn get_key_by_kid(
+        &self,
+        kid: &str,
+    ) -> Result<SigningKeyModel, AuthenticationServiceError> {
+        let signing_keys: Vec<SigningKeyModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE kid = $kid")
+            .bind(("table", SigningKeyModel::table_name()))
+            .bind(("kid", kid.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        signing_keys
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::SigningKeyNotFound,
+            ))
+    }
+
+    async fn insert_key(
+        &self,
+        algorithm: JwtAlgorithm,
+        key_material: &str,
+        status: SigningKeyStatus,
+    ) -> Result<SigningKeyModel, AuthenticationServiceError> {
+        let kid = keyring::generate_kid();
+        SigningKey::new(kid.clone(), algorithm, key_material).map_err(|e| {
+            AuthenticationServiceError::client(AuthenticationClientError::InvalidSigningKey(
+                e.to_string(),
+            ))
+        })?;
+
+        let (key_material, is_encrypted) = self.seal(key_material)?;
+        let created_keys: Vec<SigningKeyModel> = self
+            .database_connection
+            .insert(SigningKeyModel::table_name())
+            .content(CreateSigningKeyOptions {
+                kid,
+                algorithm,
+                key_material,
+                is_encrypted,
+                status,
+                activated_at: (status == SigningKeyStatus::Active)
+                    .then(|| BaseDateTime::from(chrono::Utc::now())),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        created_keys
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::ServerError(anyhow::anyhow!(
+                "Signing key creation failed without a specific error."
+            )))
+    }
+
+    /// Seeds an empty keyring with the key from `jwtAlgorithm` / `jwtPrivateKeyPath` and loads it.
+    pub async fn ensure_keyring(
+        &self,
+        authentication_config: &AuthenticationConfiguration,
+    ) -> Result<(), AuthenticationServiceError> {
+        if self.get_all_keys().await?.is_empty() {
+            let algorithm = authentication_config.jwt_algorithm;
+            let key_material = match algorithm {
+                JwtAlgorithm::HS256 => authentication_config.jwt_secret.clone(),
+                _ => {
+                    let path = authentication_config
+                        .jwt_private_key_path
+                        .as_deref()
+                        .ok_or_else(|| {
+                            AuthenticationServiceError::ServerError(crate::log!(
+                                tracing::error,
+                                "{} jwtPrivateKeyPath is required for {:?} signing",
+                                SERVICE_NAME,
+                                algorithm
+                            ))
+                        })?;
+
+                    std::fs::read_to_string(path).map_err(|e| {
+                        AuthenticationServiceError::ServerError(crate::log!(
+                            tracing::error,
+                            "{} Failed to read signing key '{}': {}",
+                            SERVICE_NAME,
+                            path,
+                            e
+                        ))
+                    })?
+                }
+            };
+
+            let signing_key = self
+                .insert_key(algorithm, &key_material, SigningKeyStatus::Active)
+                .await?;
+            tracing::info!(
+                "{} Seeded keyring with {:?} key '{}'",
+                SERVICE_NAME,
+                algorithm,
+                signing_key.kid
+            );
+        }
+
+        self.refresh().await
+    }
+
+    /// Reloads every key that may still verify tokens into the process wide keyring.
+    pub async fn refresh(&self) -> Result<(), AuthenticationServiceError> {
+        let signing_keys: Vec<SigningKeyModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET status = 'retired' WHERE status = 'inactive' AND retire_at <= time::now()")
+            .query("SELECT * FROM type::table($table) WHERE status != 'retired'")
+            .bind(("table", SigningKeyModel::table_name()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(1)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let mut active_kid = None;
+        let mut keys = Vec::with_capacity(signing_keys.len());
+        for signing_key in signing_keys {
+            // A broken key must not take the others down with it
+            let key = self.unseal(&signing_key).and_then(|key_material| {
+                SigningKey::new(
+                    signing_key.kid.clone(),
+                    signing_key.algorithm,
+                    &key_material,
+                )
+                .map_err(AuthenticationServiceError::from_error)
+            });
+
+            match key {
+                Ok(key) => {
+                    if signing_key.status == SigningKeyStatus::Active {
+                        active_kid = Some(signing_key.kid);
+                    }
+                    keys.push(key);
+                }
+                Err(e) => tracing::error!(
+                    "{} Skipping signing key '{}': {:?}",
+                    SERVICE_NAME,
+                    signing_key.kid,
+                    e
+                ),
+            }
+        }
+
+        keyring::replace(active_kid, keys);
+        Ok(())
+    }
+
+    /// Picks up keys rotated by other instances once the local copy is older than `refreshSeconds`.
+    pub async fn refresh_if_stale(&self) -> Result<(), AuthenticationServiceError> {
+        if keyring::claim_refresh(Duration::from_secs(self.signing_key_config.refresh_seconds)) {
+            self.refresh().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds a pending key, it is published right away so verifiers can pick it up before activation.
+    pub async fn add_key(
+        &self,
+        create_signing_key: CreateSigningKeyRequestDto,
+    ) -> Result<SigningKeyModel, AuthenticationServiceError> {
+        let algorithm = create_signing_key.algorithm;
+        let key_material = match create_signing_key.private_key {
+            Some(private_key) => private_key,
+            None => algorithm.generate_key_material().map_err(|e| {
+                AuthenticationServiceError::client(AuthenticationClientError::InvalidSigningKey(
+                    e.to_string(),
+                ))
+            })?,
+        };
+
+        let signing_key = self
+            .insert_key(algorithm, &key_material, SigningKeyStatus::Pending)
+            .await?;
+        self.refresh().await?;
+
+        Ok(signing_key)
+    }
+
+    /// Signs new tokens with the key, the previously active key keeps verifying for the overlap window.
+    pub async fn activate_key(
+        &self,
+        kid: &str,
+    ) -> Result<SigningKeyModel, AuthenticationServiceError> {
+        let signing_key = self.get_key_by_kid(kid).await?;
+        match signing_key.status {
+            SigningKeyStatus::Active => return Ok(signing_key),
+            SigningKeyStatus::Retired => {
+                return Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::SigningKeyRetired,
+                ));
+            }
+            SigningKeyStatus::Pending | SigningKeyStatus::Inactive => {}
+        }
+
+        let retire_at = BaseDateTime::from(
+            chrono::Utc::now()
+                + chrono::Duration::seconds(
+                    self.signing_key_config.rotation_overlap_seconds as i64,
+                ),
+        );
+
+        // Both updates run in one transaction so there is never more than one active key
+        self.database_connection
+            .query("BEGIN TRANSACTION")
+            .query("UPDATE type::table($table) SET status = 'inactive', retire_at = $retire_at WHERE status = 'active'")
+            .query("UPDATE type::table($table) SET status = 'active', activated_at = time::now(), retire_at = NONE WHERE kid = $kid")
+            .query("COMMIT TRANSACTION")
+            .bind(("table", SigningKeyModel::table_name()))
+            .bind(("retire_at", retire_at))
+            .bind(("kid", kid.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .check()
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let signing_key = self.get_key_by_kid(kid).await?;
+        self.refresh().await?;
+
+        Ok(signing_key)
+    }
+
+    /// Stops accepting tokens signed with the key immediately.
+    pub async fn retire_key(
+        &self,
+        kid: &str,
+    ) -> Result<SigningKeyModel, AuthenticationServiceError> {
+        let signing_key = self.get_key_by_kid(kid).await?;
+        if signing_key.status == SigningKeyStatus::Active {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::SigningKeyActive,
+            ));
+        }
+
+        let signing_keys: Vec<SigningKeyModel> = self
+            .database_connection
+            .query("UPDATE type::table($table) SET status = 'retired', retire_at = time::now() WHERE kid = $kid RETURN AFTER")
+            .bind(("table", SigningKeyModel::table_name()))
+            .bind(("kid", kid.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let signing_key =
+            signing_keys
+                .into_iter()
+                .next()
+                .ok_or(AuthenticationServiceError::client(
+                    AuthenticationClientError::SigningKeyNotFound,
+                ))?;
+        self.refresh().await?;
+
+        Ok(signing_key)
+    }
+}
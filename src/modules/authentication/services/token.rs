@@ -4,41 +4,149 @@ use crate::{
     modules::authentication::{
         config::authentication::AuthenticationConfiguration,
         errors::service::*,
-        models::{account::AccountModel, session::SessionModel},
+        models::{
+            account::AccountModel, service_account::ServiceAccountModel, session::SessionModel,
+        },
+        services::keyring,
     },
 };
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use chrono::{DateTime, Utc};
-use hmac::{Hmac, Mac};
-use jwt::{SignWithKey, VerifyWithKey};
+use jsonwebtoken::{Header, Validation, errors::ErrorKind, jwk::JwkSet};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use sha2::{Digest, Sha256};
-use std::collections::BTreeMap;
 use surrealdb::RecordId;
 
 const SERVICE_NAME: &str = "TokenService";
+const MFA_CHALLENGE_PURPOSE: &str = "mfa_challenge";
+const PASSWORD_CHANGE_PURPOSE: &str = "password_change";
+
+/// Audience of tokens issued to core's own sign-in flows, OAuth clients use their client id.
+/// Signed tokens carry the configured `jwt.audience` in its place.
+pub const FIRST_PARTY_AUDIENCE: &str = "core-auth";
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum TokenSubject {
+    Account {
+        account_id: String,
+        session_id: String,
+    },
+    ServiceAccount {
+        service_account_id: String,
+    },
+}
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TokenOpts {
-    pub account_id: String,
-    pub session_id: String,
-    pub service: String,
+    pub subject: TokenSubject,
+    pub audience: String,
+    pub scopes: Vec<String>,
+    /// Named account id of an admin impersonating the subject, issued as the `act` claim.
+    pub actor: Option<String>,
 }
 
 impl TokenOpts {
-    pub fn new(account_id: String, session_id: String, service: String) -> Self {
+    pub fn new(account_id: String, session_id: String, audience: String) -> Self {
         Self {
-            account_id,
-            session_id,
-            service,
+            subject: TokenSubject::Account {
+                account_id,
+                session_id,
+            },
+            audience,
+            scopes: Vec::new(),
+            actor: None,
         }
     }
 
-    pub fn into_map(self) -> BTreeMap<&'static str, String> {
-        let mut map = BTreeMap::new();
-        map.insert("account_id", self.account_id);
-        map.insert("session_id", self.session_id);
-        map.insert("service", self.service);
-        map
+    pub fn for_service_account(service_account_id: String, audience: String) -> Self {
+        Self {
+            subject: TokenSubject::ServiceAccount { service_account_id },
+            audience,
+            scopes: Vec::new(),
+            actor: None,
+        }
+    }
+
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
     }
+
+    pub fn with_actor(mut self, actor: Option<String>) -> Self {
+        self.actor = actor;
+        self
+    }
+}
+
+/// The `act` claim from RFC 8693 section 4.1, naming who acts on behalf of the subject.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ActorClaim {
+    sub: String,
+}
+
+/// Registered claims (RFC 7519 section 4.1) plus the private claims core relies on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JwtClaims {
+    sub: String,
+    iss: String,
+    aud: String,
+    exp: i64,
+    iat: i64,
+    nbf: i64,
+    jti: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    purpose: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    act: Option<ActorClaim>,
+}
+
+impl JwtClaims {
+    fn scopes(&self) -> Vec<String> {
+        self.scope
+            .as_deref()
+            .map(|scope| scope.split_whitespace().map(String::from).collect())
+            .unwrap_or_default()
+    }
+
+    fn timestamp(seconds: i64) -> Result<DateTime<Utc>, AuthenticationServiceError> {
+        DateTime::from_timestamp(seconds, 0).ok_or(AuthenticationServiceError::client(
+            AuthenticationClientError::InvalidAccessToken,
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccessTokenClaims {
+    pub account_id: RecordId,
+    pub session_id: RecordId,
+    pub audience: String,
+    pub scopes: Vec<String>,
+    pub token_id: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// The admin impersonating `account_id`, if any.
+    pub actor_id: Option<RecordId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServiceTokenClaims {
+    pub service_account_id: RecordId,
+    pub scopes: Vec<String>,
+    pub token_id: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Claims of a password change token, the token id is recorded once the token was used.
+#[derive(Debug, Clone)]
+pub struct PasswordChangeClaims {
+    pub account_id: RecordId,
+    pub token_id: String,
+    pub expires_at: DateTime<Utc>,
 }
 
 pub struct TokenService {
@@ -67,13 +175,123 @@ impl TokenService {
         Ok(Self::new(authentication_config))
     }
 
+    fn generate_token_id(&self) -> String {
+        use rand::Rng;
+        let token_id: [u8; 16] = rand::rng().random();
+        URL_SAFE_NO_PAD.encode(token_id)
+    }
+
+    fn issuer(&self) -> &str {
+        self.authentication_config
+            .jwt
+            .issuer
+            .as_deref()
+            .unwrap_or(&self.authentication_config.oidc.issuer)
+    }
+
+    /// Maps the internal first party audience to the configured `aud` value and back.
+    fn external_audience<'a>(&'a self, audience: &'a str) -> &'a str {
+        if audience == FIRST_PARTY_AUDIENCE {
+            &self.authentication_config.jwt.audience
+        } else {
+            audience
+        }
+    }
+
+    fn internal_audience(&self, audience: String) -> String {
+        if audience == self.authentication_config.jwt.audience {
+            FIRST_PARTY_AUDIENCE.to_string()
+        } else {
+            audience
+        }
+    }
+
+    fn claims(&self, subject: String, audience: &str, expires_at: DateTime<Utc>) -> JwtClaims {
+        let now = Utc::now().timestamp();
+
+        JwtClaims {
+            sub: subject,
+            iss: self.issuer().to_string(),
+            aud: self.external_audience(audience).to_string(),
+            exp: expires_at.timestamp(),
+            iat: now,
+            nbf: now,
+            jti: self.generate_token_id(),
+            session_id: None,
+            scope: None,
+            purpose: None,
+            act: None,
+        }
+    }
+
+    /// Algorithms of all keys in the keyring as used in the JOSE `alg` header.
+    pub fn signing_algorithms(&self) -> Vec<String> {
+        let mut algorithms: Vec<String> = keyring::algorithms()
+            .into_iter()
+            .map(|algorithm| format!("{:?}", algorithm))
+            .collect();
+        algorithms.sort();
+        algorithms
+    }
+
+    /// Public keys for offline verification, shared HS256 keys are never published.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: keyring::published_keys(),
+        }
+    }
+
+    fn sign<T: Serialize>(&self, claims: &T) -> Result<String, AuthenticationServiceError> {
+        let signing_key = keyring::active_key()?;
+
+        let mut header = Header::new(signing_key.algorithm);
+        header.kid = Some(signing_key.kid.clone());
+
+        jsonwebtoken::encode(&header, claims, &signing_key.encoding_key)
+            .map_err(AuthenticationServiceError::from_error)
+    }
+
+    /// Checks signature, issuer, expiry and not-before within the configured leeway.
+    fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T, AuthenticationServiceError> {
+        let invalid_token =
+            || AuthenticationServiceError::client(AuthenticationClientError::InvalidAccessToken);
+        let header = jsonwebtoken::decode_header(token).map_err(|_| invalid_token())?;
+
+        let mut expired = false;
+        // A key only verifies its own algorithm so keys cannot be used across algorithms
+        let claims = keyring::verification_keys(header.kid.as_deref())
+            .into_iter()
+            .filter(|signing_key| signing_key.algorithm == header.alg)
+            .find_map(|signing_key| {
+                let mut validation = Validation::new(signing_key.algorithm);
+                validation.leeway = self.authentication_config.jwt.leeway_seconds;
+                validation.validate_nbf = true;
+                validation.validate_aud = false;
+                validation.set_issuer(&[self.issuer()]);
+                validation.set_required_spec_claims(&["exp", "iat", "nbf", "iss", "sub", "aud"]);
+
+                match jsonwebtoken::decode::<T>(token, &signing_key.decoding_key, &validation) {
+                    Ok(data) => Some(data.claims),
+                    Err(e) => {
+                        expired |= matches!(e.kind(), ErrorKind::ExpiredSignature);
+                        None
+                    }
+                }
+            });
+
+        match claims {
+            Some(claims) => Ok(claims),
+            None if expired => Err(AuthenticationServiceError::client(
+                AuthenticationClientError::ExpiredAccessToken,
+            )),
+            None => Err(invalid_token()),
+        }
+    }
+
     pub fn generate_jwt(
         &self,
         opts: TokenOpts,
     ) -> Result<(String, DateTime<Utc>), AuthenticationServiceError> {
-        let key = Hmac::<Sha256>::new_from_slice(self.authentication_config.jwt_secret.as_bytes())
-            .map_err(AuthenticationServiceError::from_error)?;
-        let mut claims = opts.into_map();
         let exp = Utc::now()
             .checked_add_signed(chrono::Duration::seconds(
                 self.authentication_config.jwt_expiration_seconds as i64,
@@ -86,55 +304,211 @@ impl TokenService {
                 ))
             })?;
 
-        claims.insert("exp", exp.to_rfc3339());
-        claims
-            .sign_with_key(&key)
-            .map(|jwt| (jwt, exp))
-            .map_err(AuthenticationServiceError::from_error)
+        let mut claims = match opts.subject {
+            TokenSubject::Account {
+                account_id,
+                session_id,
+            } => {
+                let mut claims = self.claims(account_id, &opts.audience, exp);
+                claims.session_id = Some(session_id);
+                claims
+            }
+            TokenSubject::ServiceAccount { service_account_id } => {
+                self.claims(service_account_id, &opts.audience, exp)
+            }
+        };
+        if !opts.scopes.is_empty() {
+            claims.scope = Some(opts.scopes.join(" "));
+        }
+        claims.act = opts.actor.map(|sub| ActorClaim { sub });
+
+        self.sign(&claims).map(|jwt| (jwt, exp))
+    }
+
+    /// Signs an arbitrary claim set, used for id tokens which carry non-string claims.
+    pub fn sign_claims(
+        &self,
+        claims: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<String, AuthenticationServiceError> {
+        self.sign(claims)
+    }
+
+    pub fn verify_jwt(&self, token: &str) -> Result<AccessTokenClaims, AuthenticationServiceError> {
+        let claims = self.verify_access_token(token)?;
+
+        // Tokens issued to OAuth clients must not grant access to the first party API
+        if claims.audience != FIRST_PARTY_AUDIENCE {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidAccessToken,
+            ));
+        }
+
+        Ok(claims)
     }
 
-    pub fn verify_jwt(
+    pub fn verify_access_token(
         &self,
         token: &str,
-    ) -> Result<(RecordId, RecordId), AuthenticationServiceError> {
-        let key = Hmac::<Sha256>::new_from_slice(self.authentication_config.jwt_secret.as_bytes())
-            .map_err(AuthenticationServiceError::from_error)?;
+    ) -> Result<AccessTokenClaims, AuthenticationServiceError> {
+        let invalid_token =
+            || AuthenticationServiceError::client(AuthenticationClientError::InvalidAccessToken);
+        let claims: JwtClaims = self.verify(token)?;
 
-        let claims: BTreeMap<String, String> = token
-            .verify_with_key(&key)
-            .map_err(AuthenticationServiceError::from_error)?;
+        if claims.purpose.is_some() {
+            return Err(invalid_token());
+        }
 
-        let account_id = AccountModel::from_named_format(claims.get("account_id").ok_or(
-            AuthenticationServiceError::client(AuthenticationClientError::InvalidAccessToken),
-        )?)
-        .ok_or(AuthenticationServiceError::client(
-            AuthenticationClientError::InvalidAccountId,
-        ))?;
+        let account_id = AccountModel::from_named_format(&claims.sub).ok_or(
+            AuthenticationServiceError::client(AuthenticationClientError::InvalidAccountId),
+        )?;
 
-        let session_id = SessionModel::from_named_format(claims.get("session_id").ok_or(
-            AuthenticationServiceError::client(AuthenticationClientError::InvalidAccessToken),
-        )?)
+        let session_id = SessionModel::from_named_format(
+            claims.session_id.as_deref().ok_or_else(invalid_token)?,
+        )
         .ok_or(AuthenticationServiceError::client(
             AuthenticationClientError::InvalidSessionId,
         ))?;
 
-        let exp = claims
-            .get("exp")
-            .ok_or(AuthenticationServiceError::client(
-                AuthenticationClientError::InvalidAccessToken,
-            ))?
-            .parse::<DateTime<Utc>>()
-            .map_err(|_| {
-                AuthenticationServiceError::client(AuthenticationClientError::InvalidAccessToken)
-            })?;
+        let actor_id = match claims.act.as_ref() {
+            Some(actor) => {
+                Some(AccountModel::from_named_format(&actor.sub).ok_or_else(invalid_token)?)
+            }
+            None => None,
+        };
 
-        if chrono::Utc::now() > exp {
-            return Err(AuthenticationServiceError::client(
-                AuthenticationClientError::ExpiredAccessToken,
-            ));
+        Ok(AccessTokenClaims {
+            account_id,
+            session_id,
+            scopes: claims.scopes(),
+            issued_at: JwtClaims::timestamp(claims.iat)?,
+            expires_at: JwtClaims::timestamp(claims.exp)?,
+            audience: self.internal_audience(claims.aud),
+            token_id: claims.jti,
+            actor_id,
+        })
+    }
+
+    /// Verifies a first party token issued to a service account through the client credentials grant.
+    pub fn verify_service_token(
+        &self,
+        token: &str,
+    ) -> Result<ServiceTokenClaims, AuthenticationServiceError> {
+        let invalid_token =
+            || AuthenticationServiceError::client(AuthenticationClientError::InvalidAccessToken);
+        let claims: JwtClaims = self.verify(token)?;
+
+        if claims.purpose.is_some()
+            || claims.session_id.is_some()
+            || self.internal_audience(claims.aud.clone()) != FIRST_PARTY_AUDIENCE
+        {
+            return Err(invalid_token());
         }
 
-        Ok((account_id, session_id))
+        let service_account_id =
+            ServiceAccountModel::from_named_format(&claims.sub).ok_or_else(invalid_token)?;
+
+        Ok(ServiceTokenClaims {
+            service_account_id,
+            scopes: claims.scopes(),
+            issued_at: JwtClaims::timestamp(claims.iat)?,
+            expires_at: JwtClaims::timestamp(claims.exp)?,
+            token_id: claims.jti,
+        })
+    }
+
+    /// Purpose tokens are rejected as access tokens and only accepted by the matching verifier.
+    fn generate_purpose_token(
+        &self,
+        account_id: &RecordId,
+        purpose: &str,
+        expiration_seconds: u64,
+    ) -> Result<(String, DateTime<Utc>), AuthenticationServiceError> {
+        let exp = Utc::now() + chrono::Duration::seconds(expiration_seconds as i64);
+
+        let mut claims = self.claims(
+            AccountModel::to_named_format(account_id),
+            FIRST_PARTY_AUDIENCE,
+            exp,
+        );
+        claims.purpose = Some(purpose.to_string());
+
+        self.sign(&claims).map(|jwt| (jwt, exp))
+    }
+
+    fn verify_purpose_claims(
+        &self,
+        token: &str,
+        purpose: &str,
+        invalid_token: AuthenticationClientError,
+    ) -> Result<JwtClaims, AuthenticationServiceError> {
+        let claims: JwtClaims = match self.verify(token) {
+            Err(AuthenticationServiceError::ClientError(_)) => {
+                return Err(AuthenticationServiceError::client(invalid_token));
+            }
+            other => other?,
+        };
+
+        if claims.purpose.as_deref() != Some(purpose)
+            || self.internal_audience(claims.aud.clone()) != FIRST_PARTY_AUDIENCE
+        {
+            return Err(AuthenticationServiceError::client(invalid_token));
+        }
+
+        Ok(claims)
+    }
+
+    pub fn generate_mfa_challenge(
+        &self,
+        account_id: &RecordId,
+        expiration_seconds: u64,
+    ) -> Result<(String, DateTime<Utc>), AuthenticationServiceError> {
+        self.generate_purpose_token(account_id, MFA_CHALLENGE_PURPOSE, expiration_seconds)
+    }
+
+    pub fn verify_mfa_challenge(
+        &self,
+        token: &str,
+    ) -> Result<RecordId, AuthenticationServiceError> {
+        let claims = self.verify_purpose_claims(
+            token,
+            MFA_CHALLENGE_PURPOSE,
+            AuthenticationClientError::InvalidMfaChallenge,
+        )?;
+
+        AccountModel::from_named_format(&claims.sub).ok_or(AuthenticationServiceError::client(
+            AuthenticationClientError::InvalidMfaChallenge,
+        ))
+    }
+
+    /// Restricted token handed out at sign-in when the password has to be changed first.
+    pub fn generate_password_change_token(
+        &self,
+        account_id: &RecordId,
+        expiration_seconds: u64,
+    ) -> Result<(String, DateTime<Utc>), AuthenticationServiceError> {
+        self.generate_purpose_token(account_id, PASSWORD_CHANGE_PURPOSE, expiration_seconds)
+    }
+
+    pub fn verify_password_change_token(
+        &self,
+        token: &str,
+    ) -> Result<PasswordChangeClaims, AuthenticationServiceError> {
+        let invalid_token = || {
+            AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidPasswordChangeToken,
+            )
+        };
+        let claims = self.verify_purpose_claims(
+            token,
+            PASSWORD_CHANGE_PURPOSE,
+            AuthenticationClientError::InvalidPasswordChangeToken,
+        )?;
+
+        Ok(PasswordChangeClaims {
+            account_id: AccountModel::from_named_format(&claims.sub).ok_or_else(invalid_token)?,
+            expires_at: JwtClaims::timestamp(claims.exp).map_err(|_| invalid_token())?,
+            token_id: claims.jti,
+        })
     }
 
     pub fn generate_refresh_token(&self) -> String {
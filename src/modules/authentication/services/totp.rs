@@ -0,0 +1,125 @@
+use crate::modules::authentication::{
+    config::authentication::MfaConfiguration, errors::service::AuthenticationServiceError,
+};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+const TOTP_PERIOD_SECONDS: u64 = 30;
+const TOTP_DIGITS: u32 = 6;
+const SECRET_LENGTH: usize = 20;
+const RECOVERY_CODE_LENGTH: usize = 10;
+
+pub struct TotpService {
+    mfa_config: MfaConfiguration,
+}
+
+impl TotpService {
+    pub fn new(mfa_config: MfaConfiguration) -> Self {
+        Self { mfa_config }
+    }
+
+    pub fn generate_secret(&self) -> String {
+        use rand::Rng;
+        let secret: [u8; SECRET_LENGTH] = rand::rng().random();
+        BASE32_NOPAD.encode(&secret)
+    }
+
+    pub fn provisioning_uri(
+        &self,
+        secret: &str,
+        account_name: &str,
+    ) -> Result<String, AuthenticationServiceError> {
+        let label = format!("{}:{}", self.mfa_config.issuer, account_name);
+        let mut uri =
+            url::Url::parse("otpauth://totp/").map_err(AuthenticationServiceError::from_error)?;
+        uri.set_path(&label);
+        uri.query_pairs_mut()
+            .append_pair("secret", secret)
+            .append_pair("issuer", &self.mfa_config.issuer)
+            .append_pair("algorithm", "SHA1")
+            .append_pair("digits", &TOTP_DIGITS.to_string())
+            .append_pair("period", &TOTP_PERIOD_SECONDS.to_string());
+
+        Ok(uri.to_string())
+    }
+
+    pub fn current_step(&self) -> u64 {
+        chrono::Utc::now().timestamp().max(0) as u64 / TOTP_PERIOD_SECONDS
+    }
+
+    pub fn generate_code(
+        &self,
+        secret: &str,
+        step: u64,
+    ) -> Result<String, AuthenticationServiceError> {
+        let key = BASE32_NOPAD
+            .decode(secret.as_bytes())
+            .map_err(AuthenticationServiceError::from_error)?;
+        let mut mac =
+            Hmac::<Sha1>::new_from_slice(&key).map_err(AuthenticationServiceError::from_error)?;
+        mac.update(&step.to_be_bytes());
+        let digest = mac.finalize().into_bytes();
+
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            digest[offset] & 0x7f,
+            digest[offset + 1],
+            digest[offset + 2],
+            digest[offset + 3],
+        ]);
+
+        Ok(format!(
+            "{:0width$}",
+            binary % 10u32.pow(TOTP_DIGITS),
+            width = TOTP_DIGITS as usize
+        ))
+    }
+
+    pub fn verify_code(
+        &self,
+        secret: &str,
+        code: &str,
+        last_used_step: Option<u64>,
+    ) -> Result<Option<u64>, AuthenticationServiceError> {
+        let code = code.trim();
+        if code.len() != TOTP_DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(None);
+        }
+
+        let current_step = self.current_step();
+        let skew = self.mfa_config.allowed_skew_steps;
+        for step in current_step.saturating_sub(skew)..=current_step + skew {
+            if last_used_step.is_some_and(|last| step <= last) {
+                continue;
+            }
+
+            if self.generate_code(secret, step)? == code {
+                return Ok(Some(step));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn generate_recovery_codes(&self) -> Vec<String> {
+        use rand::Rng;
+        let mut rng = rand::rng();
+
+        (0..self.mfa_config.recovery_code_count)
+            .map(|_| {
+                let bytes: [u8; RECOVERY_CODE_LENGTH] = rng.random();
+                let code = BASE32_NOPAD.encode(&bytes).to_lowercase();
+                format!("{}-{}", &code[..8], &code[8..16])
+            })
+            .collect()
+    }
+
+    pub fn hash_recovery_code(&self, recovery_code: &str) -> String {
+        Sha256::digest(recovery_code.trim().to_lowercase().as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
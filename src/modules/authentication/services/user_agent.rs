@@ -0,0 +1,124 @@
+/// Browsers in the order they have to be checked, most engines also claim to be Chrome or Safari.
+const BROWSERS: &[(&str, &str)] = &[
+    ("Edg/", "Edge"),
+    ("EdgA/", "Edge"),
+    ("EdgiOS/", "Edge"),
+    ("OPR/", "Opera"),
+    ("SamsungBrowser/", "Samsung Internet"),
+    ("Vivaldi/", "Vivaldi"),
+    ("YaBrowser/", "Yandex Browser"),
+    ("FxiOS/", "Firefox"),
+    ("Firefox/", "Firefox"),
+    ("CriOS/", "Chrome"),
+    ("Chromium/", "Chromium"),
+    ("Chrome/", "Chrome"),
+    ("Safari/", "Safari"),
+    ("curl/", "curl"),
+    ("Wget/", "Wget"),
+    ("PostmanRuntime/", "Postman"),
+    ("okhttp/", "OkHttp"),
+];
+
+/// Operating systems in the order they have to be checked, Android also claims to be Linux and
+/// iOS to be like Mac OS X.
+const OPERATING_SYSTEMS: &[(&str, &str)] = &[
+    ("Windows", "Windows"),
+    ("iPhone", "iOS"),
+    ("iPad", "iPadOS"),
+    ("Android", "Android"),
+    ("CrOS", "ChromeOS"),
+    ("Mac OS X", "macOS"),
+    ("Macintosh", "macOS"),
+    ("Linux", "Linux"),
+];
+
+const UNKNOWN_DEVICE: &str = "Unknown device";
+
+/// Short label like "Firefox on Windows" to tell sessions apart, not meant for anything but display.
+pub fn device_label(user_agent: &str) -> String {
+    let browser = BROWSERS
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| *name);
+    let operating_system = OPERATING_SYSTEMS
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| *name);
+
+    match (browser, operating_system) {
+        (Some(browser), Some(operating_system)) => format!("{} on {}", browser, operating_system),
+        (Some(name), None) | (None, Some(name)) => name.to_string(),
+        (None, None) => UNKNOWN_DEVICE.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_desktop_browsers() {
+        assert_eq!(
+            device_label(
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.2210.91"
+            ),
+            "Edge on Windows"
+        );
+        assert_eq!(
+            device_label(
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"
+            ),
+            "Chrome on Windows"
+        );
+        assert_eq!(
+            device_label(
+                "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Safari/605.1.15"
+            ),
+            "Safari on macOS"
+        );
+        assert_eq!(
+            device_label("Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0"),
+            "Firefox on Linux"
+        );
+        assert_eq!(
+            device_label(
+                "Mozilla/5.0 (X11; CrOS x86_64 14541.0.0) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"
+            ),
+            "Chrome on ChromeOS"
+        );
+    }
+
+    #[test]
+    fn labels_mobile_browsers() {
+        assert_eq!(
+            device_label(
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Mobile/15E148 Safari/604.1"
+            ),
+            "Safari on iOS"
+        );
+        assert_eq!(
+            device_label(
+                "Mozilla/5.0 (iPad; CPU OS 17_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) CriOS/120.0.6099.119 Mobile/15E148 Safari/604.1"
+            ),
+            "Chrome on iPadOS"
+        );
+        assert_eq!(
+            device_label(
+                "Mozilla/5.0 (Linux; Android 14; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/23.0 Chrome/115.0.0.0 Mobile Safari/537.36"
+            ),
+            "Samsung Internet on Android"
+        );
+        assert_eq!(
+            device_label("Mozilla/5.0 (Android 14; Mobile; rv:121.0) Gecko/121.0 Firefox/121.0"),
+            "Firefox on Android"
+        );
+    }
+
+    #[test]
+    fn labels_clients_without_an_operating_system() {
+        assert_eq!(device_label("curl/8.4.0"), "curl");
+        assert_eq!(device_label("PostmanRuntime/7.36.0"), "Postman");
+        assert_eq!(device_label("unknown"), UNKNOWN_DEVICE);
+        assert_eq!(device_label(""), UNKNOWN_DEVICE);
+    }
+}
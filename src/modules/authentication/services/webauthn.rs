@@ -0,0 +1,1085 @@
+use crate::{
+    common::model::DatabaseModel,
+    modules::{
+        authentication::{
+            config::authentication::WebAuthnConfiguration,
+            dtos::webauthn::{
+                AuthenticationCredentialDto, AuthenticatorSelectionDto, CreateCredentialOptions,
+                CreateWebAuthnChallengeOptions, CredentialDescriptorDto, CredentialParameterDto,
+                PublicKeyCredentialCreationOptionsDto, PublicKeyCredentialRequestOptionsDto,
+                RegistrationCredentialDto, RelyingPartyDto, UserEntityDto,
+                WebAuthnAuthenticationOptionsDto, WebAuthnAuthenticationRequestDto,
+                WebAuthnAuthenticationStartRequestDto, WebAuthnRegistrationOptionsDto,
+                WebAuthnRegistrationRequestDto,
+            },
+            errors::service::*,
+            models::{
+                account::AccountModel, credential::CredentialModel,
+                webauthn_challenge::WebAuthnChallengeModel,
+            },
+            services::account::AccountService,
+        },
+        base::exports::{BaseDateTime, BaseId, DatabaseConnection},
+    },
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ciborium::Value as CborValue;
+use ring::signature::{
+    ECDSA_P256_SHA256_ASN1, ED25519, RSA_PKCS1_2048_8192_SHA256, RsaPublicKeyComponents,
+    UnparsedPublicKey,
+};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+
+const REGISTRATION_CEREMONY: &str = "registration";
+const AUTHENTICATION_CEREMONY: &str = "authentication";
+const PUBLIC_KEY_TYPE: &str = "public-key";
+
+const COSE_ALG_ES256: i64 = -7;
+const COSE_ALG_EDDSA: i64 = -8;
+const COSE_ALG_RS256: i64 = -257;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+const AUTHENTICATOR_DATA_MIN_LENGTH: usize = 37;
+const AAGUID_LENGTH: usize = 16;
+
+/// A registration response that passed every check, ready to be stored.
+struct VerifiedRegistration {
+    credential_id: Vec<u8>,
+    public_key: Vec<u8>,
+    algorithm: i64,
+    sign_count: u32,
+}
+
+struct AuthenticatorData {
+    rp_id_hash: Vec<u8>,
+    flags: u8,
+    sign_count: u32,
+    credential_id: Option<Vec<u8>>,
+    credential_public_key: Option<Vec<u8>>,
+}
+
+pub struct WebAuthnService {
+    database_connection: DatabaseConnection,
+    webauthn_config: WebAuthnConfiguration,
+}
+
+impl WebAuthnService {
+    pub fn new(
+        webauthn_config: WebAuthnConfiguration,
+        database_connection: DatabaseConnection,
+    ) -> Self {
+        Self {
+            database_connection,
+            webauthn_config,
+        }
+    }
+
+    pub async fn get_credentials_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<Vec<CredentialModel>, AuthenticationServiceError> {
+        let credentials: Vec<CredentialModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE account_id = $account_id")
+            .bind(("table", CredentialModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(credentials)
+    }
+
+    pub async fn get_credential_by_credential_id(
+        &self,
+        credential_id: &str,
+    ) -> Result<CredentialModel, AuthenticationServiceError> {
+        let credentials: Vec<CredentialModel> = self
+            .database_connection
+            .query("SELECT * FROM type::table($table) WHERE credential_id = $credential_id LIMIT 1")
+            .bind(("table", CredentialModel::table_name()))
+            .bind(("credential_id", credential_id.to_string()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        credentials
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::client(
+                AuthenticationClientError::CredentialNotFound,
+            ))
+    }
+
+    pub async fn delete_credential_for_account(
+        &self,
+        credential_id: &BaseId,
+        account_id: &BaseId,
+    ) -> Result<bool, AuthenticationServiceError> {
+        let credentials: Vec<CredentialModel> = self
+            .database_connection
+            .query("DELETE FROM type::table($table) WHERE id = $id AND account_id = $account_id RETURN BEFORE")
+            .bind(("table", CredentialModel::table_name()))
+            .bind(("id", credential_id.clone()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?
+            .take(0)
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(!credentials.is_empty())
+    }
+
+    pub async fn delete_all_credentials_for_account(
+        &self,
+        account_id: &BaseId,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE account_id = $account_id")
+            .bind(("table", CredentialModel::table_name()))
+            .bind(("account_id", account_id.clone()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        Ok(())
+    }
+
+    async fn create_challenge(
+        &self,
+        ceremony: &str,
+        account_id: Option<BaseId>,
+    ) -> Result<WebAuthnChallengeModel, AuthenticationServiceError> {
+        use rand::Rng;
+        let challenge_bytes: [u8; 32] = rand::rng().random();
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::seconds(self.webauthn_config.challenge_expiration_seconds as i64);
+
+        self.database_connection
+            .query("DELETE FROM type::table($table) WHERE expires_at < time::now()")
+            .bind(("table", WebAuthnChallengeModel::table_name()))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let challenges: Vec<WebAuthnChallengeModel> = self
+            .database_connection
+            .insert(WebAuthnChallengeModel::table_name())
+            .content(CreateWebAuthnChallengeOptions {
+                challenge: URL_SAFE_NO_PAD.encode(challenge_bytes),
+                ceremony: ceremony.to_string(),
+                account_id,
+                expires_at: BaseDateTime::from(expires_at),
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        challenges
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::ServerError(anyhow::anyhow!(
+                "WebAuthn challenge creation failed without a specific error."
+            )))
+    }
+
+    async fn consume_challenge(
+        &self,
+        challenge_id: &str,
+        ceremony: &str,
+    ) -> Result<WebAuthnChallengeModel, AuthenticationServiceError> {
+        let invalid_challenge = || {
+            AuthenticationServiceError::client(AuthenticationClientError::InvalidWebAuthnChallenge)
+        };
+        let challenge_id = WebAuthnChallengeModel::from_named_format(challenge_id)
+            .ok_or_else(invalid_challenge)?;
+
+        let challenge: Option<WebAuthnChallengeModel> = self
+            .database_connection
+            .delete(&challenge_id)
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+        let challenge = challenge.ok_or_else(invalid_challenge)?;
+
+        if challenge.ceremony != ceremony
+            || challenge.expires_at <= BaseDateTime::from(chrono::Utc::now())
+        {
+            return Err(invalid_challenge());
+        }
+
+        Ok(challenge)
+    }
+
+    fn user_verification(&self) -> String {
+        if self.webauthn_config.require_user_verification {
+            "required".to_string()
+        } else {
+            "preferred".to_string()
+        }
+    }
+
+    fn credential_descriptors(credentials: &[CredentialModel]) -> Vec<CredentialDescriptorDto> {
+        credentials
+            .iter()
+            .map(|credential| CredentialDescriptorDto {
+                type_: PUBLIC_KEY_TYPE.to_string(),
+                id: credential.credential_id.clone(),
+                transports: credential.transports.clone(),
+            })
+            .collect()
+    }
+
+    pub async fn start_registration(
+        &self,
+        account: &AccountModel,
+    ) -> Result<WebAuthnRegistrationOptionsDto, AuthenticationServiceError> {
+        let existing_credentials = self.get_credentials_for_account(&account.id).await?;
+        let challenge = self
+            .create_challenge(REGISTRATION_CEREMONY, Some(account.id.clone()))
+            .await?;
+
+        Ok(WebAuthnRegistrationOptionsDto {
+            challenge_id: WebAuthnChallengeModel::to_named_format(&challenge.id),
+            public_key: PublicKeyCredentialCreationOptionsDto {
+                rp: RelyingPartyDto {
+                    id: self.webauthn_config.rp_id.clone(),
+                    name: self.webauthn_config.rp_name.clone(),
+                },
+                user: UserEntityDto {
+                    id: URL_SAFE_NO_PAD.encode(AccountModel::to_named_format(&account.id)),
+                    name: account.username.clone(),
+                    display_name: account.username.clone(),
+                },
+                challenge: challenge.challenge,
+                pub_key_cred_params: [COSE_ALG_ES256, COSE_ALG_EDDSA, COSE_ALG_RS256]
+                    .into_iter()
+                    .map(|alg| CredentialParameterDto {
+                        type_: PUBLIC_KEY_TYPE.to_string(),
+                        alg,
+                    })
+                    .collect(),
+                timeout: self.webauthn_config.challenge_expiration_seconds * 1000,
+                exclude_credentials: Self::credential_descriptors(&existing_credentials),
+                authenticator_selection: AuthenticatorSelectionDto {
+                    resident_key: "preferred".to_string(),
+                    user_verification: self.user_verification(),
+                },
+                attestation: "none".to_string(),
+            },
+        })
+    }
+
+    pub async fn finish_registration(
+        &self,
+        account: &AccountModel,
+        registration: WebAuthnRegistrationRequestDto,
+    ) -> Result<CredentialModel, AuthenticationServiceError> {
+        let challenge = self
+            .consume_challenge(&registration.challenge_id, REGISTRATION_CEREMONY)
+            .await?;
+        if challenge.account_id.as_ref() != Some(&account.id) {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::InvalidWebAuthnChallenge,
+            ));
+        }
+
+        let credential = registration.credential;
+        let verified = self.verify_registration(&credential, &challenge.challenge)?;
+        let credential_id = URL_SAFE_NO_PAD.encode(&verified.credential_id);
+
+        match self.get_credential_by_credential_id(&credential_id).await {
+            Ok(_) => {
+                return Err(AuthenticationServiceError::client(
+                    AuthenticationClientError::CredentialAlreadyRegistered,
+                ));
+            }
+            Err(AuthenticationServiceError::ClientError(
+                AuthenticationClientError::CredentialNotFound,
+            )) => {}
+            Err(e) => return Err(e),
+        }
+
+        let created_credentials: Vec<CredentialModel> = self
+            .database_connection
+            .insert(CredentialModel::table_name())
+            .content(CreateCredentialOptions {
+                account_id: account.id.clone(),
+                credential_id,
+                public_key: URL_SAFE_NO_PAD.encode(&verified.public_key),
+                algorithm: verified.algorithm,
+                sign_count: verified.sign_count,
+                name: registration.name.unwrap_or_else(|| "Passkey".to_string()),
+                transports: credential.response.transports,
+            })
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        created_credentials
+            .into_iter()
+            .next()
+            .ok_or(AuthenticationServiceError::ServerError(anyhow::anyhow!(
+                "Credential creation failed without a specific error."
+            )))
+    }
+
+    pub async fn start_authentication(
+        &self,
+        account_service: &AccountService,
+        start: WebAuthnAuthenticationStartRequestDto,
+    ) -> Result<WebAuthnAuthenticationOptionsDto, AuthenticationServiceError> {
+        let mut allow_credentials = Vec::new();
+        let mut account_id = None;
+
+        if let Some(username) = start.username {
+            match account_service.get_account_by_username(&username).await {
+                Ok(account) => {
+                    let credentials = self.get_credentials_for_account(&account.id).await?;
+                    allow_credentials = Self::credential_descriptors(&credentials);
+                    account_id = Some(account.id);
+                }
+                Err(AuthenticationServiceError::ClientError(
+                    AuthenticationClientError::AccountNotFound,
+                )) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let challenge = self
+            .create_challenge(AUTHENTICATION_CEREMONY, account_id)
+            .await?;
+
+        Ok(WebAuthnAuthenticationOptionsDto {
+            challenge_id: WebAuthnChallengeModel::to_named_format(&challenge.id),
+            public_key: PublicKeyCredentialRequestOptionsDto {
+                challenge: challenge.challenge,
+                rp_id: self.webauthn_config.rp_id.clone(),
+                timeout: self.webauthn_config.challenge_expiration_seconds * 1000,
+                allow_credentials,
+                user_verification: self.user_verification(),
+            },
+        })
+    }
+
+    /// Verifies a passkey assertion and returns the signed in account. A passkey already proves
+    /// possession and, with user verification, knowledge, so no TOTP challenge follows.
+    pub async fn finish_authentication(
+        &self,
+        account_service: &AccountService,
+        authentication: WebAuthnAuthenticationRequestDto,
+    ) -> Result<AccountModel, AuthenticationServiceError> {
+        let challenge = self
+            .consume_challenge(&authentication.challenge_id, AUTHENTICATION_CEREMONY)
+            .await?;
+
+        let credential = authentication.credential;
+        if credential.type_ != PUBLIC_KEY_TYPE {
+            return Err(invalid_response("unsupported credential type"));
+        }
+
+        let stored_credential = self
+            .get_credential_by_credential_id(&credential.raw_id)
+            .await?;
+
+        if challenge
+            .account_id
+            .as_ref()
+            .is_some_and(|account_id| account_id != &stored_credential.account_id)
+        {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::CredentialNotFound,
+            ));
+        }
+
+        let sign_count =
+            self.verify_assertion(&credential, &challenge.challenge, &stored_credential)?;
+
+        self.database_connection
+            .query("UPDATE type::table($table) SET sign_count = $sign_count, last_used_at = time::now() WHERE id = $id")
+            .bind(("table", CredentialModel::table_name()))
+            .bind(("id", stored_credential.id.clone()))
+            .bind(("sign_count", sign_count))
+            .await
+            .map_err(AuthenticationServiceError::from_error)?;
+
+        let account = account_service
+            .get_account_by_id(&stored_credential.account_id)
+            .await?;
+        if account.is_locked() {
+            return Err(AuthenticationServiceError::client(
+                AuthenticationClientError::AccountLocked,
+            ));
+        }
+        account_service.ensure_email_verified(&account)?;
+
+        Ok(account)
+    }
+
+    fn verify_registration(
+        &self,
+        credential: &RegistrationCredentialDto,
+        expected_challenge: &str,
+    ) -> Result<VerifiedRegistration, AuthenticationServiceError> {
+        if credential.type_ != PUBLIC_KEY_TYPE {
+            return Err(invalid_response("unsupported credential type"));
+        }
+
+        self.verify_client_data(
+            &credential.response.client_data_json,
+            "webauthn.create",
+            expected_challenge,
+        )?;
+
+        let attestation_object = decode_base64url(&credential.response.attestation_object)?;
+        let auth_data_bytes = parse_attestation_object(&attestation_object)?;
+        let auth_data = parse_authenticator_data(&auth_data_bytes)?;
+        self.verify_authenticator_data(&auth_data)?;
+
+        let credential_id = auth_data
+            .credential_id
+            .ok_or_else(|| invalid_response("missing attested credential data"))?;
+        let public_key = auth_data
+            .credential_public_key
+            .ok_or_else(|| invalid_response("missing credential public key"))?;
+
+        if decode_base64url(&credential.raw_id)? != credential_id {
+            return Err(invalid_response("credential id mismatch"));
+        }
+
+        Ok(VerifiedRegistration {
+            algorithm: cose_key_algorithm(&public_key)?,
+            credential_id,
+            public_key,
+            sign_count: auth_data.sign_count,
+        })
+    }
+
+    /// Checks an assertion against the stored credential and returns the new signature counter.
+    fn verify_assertion(
+        &self,
+        credential: &AuthenticationCredentialDto,
+        expected_challenge: &str,
+        stored_credential: &CredentialModel,
+    ) -> Result<u32, AuthenticationServiceError> {
+        if let Some(user_handle) = credential.response.user_handle.as_deref() {
+            let user_handle = decode_base64url(user_handle)?;
+            if user_handle
+                != AccountModel::to_named_format(&stored_credential.account_id).as_bytes()
+            {
+                return Err(invalid_response("user handle mismatch"));
+            }
+        }
+
+        let client_data = self.verify_client_data(
+            &credential.response.client_data_json,
+            "webauthn.get",
+            expected_challenge,
+        )?;
+
+        let auth_data_bytes = decode_base64url(&credential.response.authenticator_data)?;
+        let auth_data = parse_authenticator_data(&auth_data_bytes)?;
+        self.verify_authenticator_data(&auth_data)?;
+
+        let mut signed_data = auth_data_bytes;
+        signed_data.extend_from_slice(&Sha256::digest(&client_data));
+
+        let public_key = decode_base64url(&stored_credential.public_key)?;
+        let signature = decode_base64url(&credential.response.signature)?;
+        verify_signature(
+            &public_key,
+            stored_credential.algorithm,
+            &signed_data,
+            &signature,
+        )?;
+
+        if (auth_data.sign_count != 0 || stored_credential.sign_count != 0)
+            && auth_data.sign_count <= stored_credential.sign_count
+        {
+            tracing::warn!(
+                "Signature counter did not increase for credential {}, possible cloned authenticator",
+                CredentialModel::to_named_format(&stored_credential.id)
+            );
+            return Err(invalid_response("signature counter did not increase"));
+        }
+
+        Ok(auth_data.sign_count)
+    }
+
+    fn verify_client_data(
+        &self,
+        client_data_json: &str,
+        expected_type: &str,
+        expected_challenge: &str,
+    ) -> Result<Vec<u8>, AuthenticationServiceError> {
+        let client_data = decode_base64url(client_data_json)?;
+        let parsed: serde_json::Value = serde_json::from_slice(&client_data)
+            .map_err(|_| invalid_response("malformed client data"))?;
+
+        if parsed.get("type").and_then(|v| v.as_str()) != Some(expected_type) {
+            return Err(invalid_response("unexpected client data type"));
+        }
+
+        if parsed.get("challenge").and_then(|v| v.as_str()) != Some(expected_challenge) {
+            return Err(invalid_response("challenge mismatch"));
+        }
+
+        let origin = parsed
+            .get("origin")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| invalid_response("missing origin"))?;
+        if !self
+            .webauthn_config
+            .origins
+            .iter()
+            .any(|allowed| allowed == origin)
+        {
+            return Err(invalid_response("origin not allowed"));
+        }
+
+        if parsed.get("crossOrigin").and_then(|v| v.as_bool()) == Some(true) {
+            return Err(invalid_response("cross origin requests are not allowed"));
+        }
+
+        Ok(client_data)
+    }
+
+    fn verify_authenticator_data(
+        &self,
+        auth_data: &AuthenticatorData,
+    ) -> Result<(), AuthenticationServiceError> {
+        let expected_rp_id_hash = Sha256::digest(self.webauthn_config.rp_id.as_bytes());
+        if auth_data.rp_id_hash != expected_rp_id_hash.as_slice() {
+            return Err(invalid_response("relying party id mismatch"));
+        }
+
+        if auth_data.flags & FLAG_USER_PRESENT == 0 {
+            return Err(invalid_response("user not present"));
+        }
+
+        if self.webauthn_config.require_user_verification
+            && auth_data.flags & FLAG_USER_VERIFIED == 0
+        {
+            return Err(invalid_response("user not verified"));
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_response(reason: &str) -> AuthenticationServiceError {
+    AuthenticationServiceError::client(AuthenticationClientError::InvalidWebAuthnResponse(
+        reason.to_string(),
+    ))
+}
+
+fn decode_base64url(value: &str) -> Result<Vec<u8>, AuthenticationServiceError> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| invalid_response("malformed base64url value"))
+}
+
+// Attestation statements are not verified since registrations request "none" attestation.
+fn parse_attestation_object(
+    attestation_object: &[u8],
+) -> Result<Vec<u8>, AuthenticationServiceError> {
+    let value: CborValue = ciborium::de::from_reader(attestation_object)
+        .map_err(|_| invalid_response("malformed attestation object"))?;
+
+    value
+        .as_map()
+        .and_then(|entries| {
+            entries
+                .iter()
+                .find(|(key, _)| key.as_text() == Some("authData"))
+        })
+        .and_then(|(_, value)| value.as_bytes())
+        .cloned()
+        .ok_or_else(|| invalid_response("missing authenticator data"))
+}
+
+fn parse_authenticator_data(data: &[u8]) -> Result<AuthenticatorData, AuthenticationServiceError> {
+    if data.len() < AUTHENTICATOR_DATA_MIN_LENGTH {
+        return Err(invalid_response("authenticator data too short"));
+    }
+
+    let rp_id_hash = data[..32].to_vec();
+    let flags = data[32];
+    let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+    let mut credential_id = None;
+    let mut credential_public_key = None;
+
+    if flags & FLAG_ATTESTED_CREDENTIAL_DATA != 0 {
+        let rest = &data[AUTHENTICATOR_DATA_MIN_LENGTH..];
+        if rest.len() < AAGUID_LENGTH + 2 {
+            return Err(invalid_response("attested credential data too short"));
+        }
+
+        let id_length = u16::from_be_bytes([rest[AAGUID_LENGTH], rest[AAGUID_LENGTH + 1]]) as usize;
+        let id_start = AAGUID_LENGTH + 2;
+        if rest.len() < id_start + id_length {
+            return Err(invalid_response("credential id too short"));
+        }
+
+        let key_bytes = &rest[id_start + id_length..];
+        let mut cursor = Cursor::new(key_bytes);
+        let _: CborValue = ciborium::de::from_reader(&mut cursor)
+            .map_err(|_| invalid_response("malformed credential public key"))?;
+
+        credential_id = Some(rest[id_start..id_start + id_length].to_vec());
+        credential_public_key = Some(key_bytes[..cursor.position() as usize].to_vec());
+    }
+
+    Ok(AuthenticatorData {
+        rp_id_hash,
+        flags,
+        sign_count,
+        credential_id,
+        credential_public_key,
+    })
+}
+
+fn parse_cose_key(
+    public_key: &[u8],
+) -> Result<Vec<(CborValue, CborValue)>, AuthenticationServiceError> {
+    let value: CborValue = ciborium::de::from_reader(public_key)
+        .map_err(|_| invalid_response("malformed credential public key"))?;
+
+    value
+        .into_map()
+        .map_err(|_| invalid_response("credential public key is not a map"))
+}
+
+fn cose_value(key: &[(CborValue, CborValue)], label: i64) -> Option<&CborValue> {
+    key.iter()
+        .find(|(k, _)| {
+            k.as_integer()
+                .is_some_and(|integer| i128::from(integer) == label as i128)
+        })
+        .map(|(_, v)| v)
+}
+
+fn cose_bytes(
+    key: &[(CborValue, CborValue)],
+    label: i64,
+) -> Result<&[u8], AuthenticationServiceError> {
+    cose_value(key, label)
+        .and_then(|value| value.as_bytes())
+        .map(Vec::as_slice)
+        .ok_or_else(|| invalid_response("incomplete credential public key"))
+}
+
+fn cose_key_algorithm(public_key: &[u8]) -> Result<i64, AuthenticationServiceError> {
+    let key = parse_cose_key(public_key)?;
+    let algorithm = cose_value(&key, 3)
+        .and_then(|value| value.as_integer())
+        .and_then(|integer| i64::try_from(integer).ok())
+        .ok_or_else(|| invalid_response("missing credential algorithm"))?;
+
+    match algorithm {
+        COSE_ALG_ES256 => {
+            cose_bytes(&key, -2)?;
+            cose_bytes(&key, -3)?;
+        }
+        COSE_ALG_EDDSA => {
+            cose_bytes(&key, -2)?;
+        }
+        COSE_ALG_RS256 => {
+            cose_bytes(&key, -1)?;
+            cose_bytes(&key, -2)?;
+        }
+        _ => return Err(invalid_response("unsupported credential algorithm")),
+    }
+
+    Ok(algorithm)
+}
+
+fn verify_signature(
+    public_key: &[u8],
+    algorithm: i64,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), AuthenticationServiceError> {
+    let key = parse_cose_key(public_key)?;
+
+    let verified = match algorithm {
+        COSE_ALG_ES256 => {
+            let mut point = vec![0x04];
+            point.extend_from_slice(cose_bytes(&key, -2)?);
+            point.extend_from_slice(cose_bytes(&key, -3)?);
+            UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, point)
+                .verify(message, signature)
+                .is_ok()
+        }
+        COSE_ALG_EDDSA => UnparsedPublicKey::new(&ED25519, cose_bytes(&key, -2)?)
+            .verify(message, signature)
+            .is_ok(),
+        COSE_ALG_RS256 => RsaPublicKeyComponents {
+            n: cose_bytes(&key, -1)?,
+            e: cose_bytes(&key, -2)?,
+        }
+        .verify(&RSA_PKCS1_2048_8192_SHA256, message, signature)
+        .is_ok(),
+        _ => false,
+    };
+
+    if !verified {
+        return Err(invalid_response("signature verification failed"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::authentication::dtos::webauthn::{
+        AssertionResponseDto, AttestationResponseDto,
+    };
+    use ring::{
+        rand::SystemRandom,
+        signature::{ECDSA_P256_SHA256_ASN1_SIGNING, EcdsaKeyPair, Ed25519KeyPair, KeyPair},
+    };
+
+    const ORIGIN: &str = "http://localhost:3000";
+    const CHALLENGE: &str = "dGVzdC1jaGFsbGVuZ2U";
+
+    enum SigningKey {
+        Es256(EcdsaKeyPair),
+        EdDsa(Ed25519KeyPair),
+    }
+
+    /// Software authenticator producing "none" attestations and assertions like a browser would.
+    struct SoftwareAuthenticator {
+        key: SigningKey,
+        credential_id: Vec<u8>,
+        rp_id: String,
+        sign_count: u32,
+    }
+
+    impl SoftwareAuthenticator {
+        fn es256() -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 =
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+            let key =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng)
+                    .unwrap();
+            Self::with_key(SigningKey::Es256(key))
+        }
+
+        fn eddsa() -> Self {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+            let key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+            Self::with_key(SigningKey::EdDsa(key))
+        }
+
+        fn with_key(key: SigningKey) -> Self {
+            Self {
+                key,
+                credential_id: vec![7; 16],
+                rp_id: "localhost".to_string(),
+                sign_count: 0,
+            }
+        }
+
+        fn cose_key(&self) -> Vec<u8> {
+            let entry = |label: i64, value: CborValue| (CborValue::Integer(label.into()), value);
+            let integer = |value: i64| CborValue::Integer(value.into());
+
+            let entries = match &self.key {
+                SigningKey::Es256(key) => {
+                    let point = key.public_key().as_ref();
+                    vec![
+                        entry(1, integer(2)),
+                        entry(3, integer(COSE_ALG_ES256)),
+                        entry(-1, integer(1)),
+                        entry(-2, CborValue::Bytes(point[1..33].to_vec())),
+                        entry(-3, CborValue::Bytes(point[33..65].to_vec())),
+                    ]
+                }
+                SigningKey::EdDsa(key) => vec![
+                    entry(1, integer(1)),
+                    entry(3, integer(COSE_ALG_EDDSA)),
+                    entry(-1, integer(6)),
+                    entry(-2, CborValue::Bytes(key.public_key().as_ref().to_vec())),
+                ],
+            };
+
+            let mut encoded = Vec::new();
+            ciborium::ser::into_writer(&CborValue::Map(entries), &mut encoded).unwrap();
+            encoded
+        }
+
+        fn authenticator_data(&self, attested: bool) -> Vec<u8> {
+            let mut flags = FLAG_USER_PRESENT | FLAG_USER_VERIFIED;
+            if attested {
+                flags |= FLAG_ATTESTED_CREDENTIAL_DATA;
+            }
+
+            let mut data = Sha256::digest(self.rp_id.as_bytes()).to_vec();
+            data.push(flags);
+            data.extend_from_slice(&self.sign_count.to_be_bytes());
+            if attested {
+                data.extend_from_slice(&[0; AAGUID_LENGTH]);
+                data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+                data.extend_from_slice(&self.credential_id);
+                data.extend_from_slice(&self.cose_key());
+            }
+            data
+        }
+
+        fn register(&self, origin: &str, challenge: &str) -> RegistrationCredentialDto {
+            let attestation_object = CborValue::Map(vec![
+                (
+                    CborValue::Text("fmt".to_string()),
+                    CborValue::Text("none".to_string()),
+                ),
+                (
+                    CborValue::Text("attStmt".to_string()),
+                    CborValue::Map(Vec::new()),
+                ),
+                (
+                    CborValue::Text("authData".to_string()),
+                    CborValue::Bytes(self.authenticator_data(true)),
+                ),
+            ]);
+            let mut encoded = Vec::new();
+            ciborium::ser::into_writer(&attestation_object, &mut encoded).unwrap();
+
+            RegistrationCredentialDto {
+                id: URL_SAFE_NO_PAD.encode(&self.credential_id),
+                raw_id: URL_SAFE_NO_PAD.encode(&self.credential_id),
+                type_: PUBLIC_KEY_TYPE.to_string(),
+                response: AttestationResponseDto {
+                    client_data_json: client_data("webauthn.create", origin, challenge),
+                    attestation_object: URL_SAFE_NO_PAD.encode(encoded),
+                    transports: vec!["internal".to_string()],
+                },
+            }
+        }
+
+        fn assert(&mut self, origin: &str, challenge: &str) -> AuthenticationCredentialDto {
+            self.sign_count += 1;
+            let client_data_json = client_data("webauthn.get", origin, challenge);
+            let authenticator_data = self.authenticator_data(false);
+
+            let mut signed_data = authenticator_data.clone();
+            signed_data.extend_from_slice(&Sha256::digest(
+                URL_SAFE_NO_PAD.decode(&client_data_json).unwrap(),
+            ));
+            let signature = match &self.key {
+                SigningKey::Es256(key) => key
+                    .sign(&SystemRandom::new(), &signed_data)
+                    .unwrap()
+                    .as_ref()
+                    .to_vec(),
+                SigningKey::EdDsa(key) => key.sign(&signed_data).as_ref().to_vec(),
+            };
+
+            AuthenticationCredentialDto {
+                id: URL_SAFE_NO_PAD.encode(&self.credential_id),
+                raw_id: URL_SAFE_NO_PAD.encode(&self.credential_id),
+                type_: PUBLIC_KEY_TYPE.to_string(),
+                response: AssertionResponseDto {
+                    client_data_json,
+                    authenticator_data: URL_SAFE_NO_PAD.encode(authenticator_data),
+                    signature: URL_SAFE_NO_PAD.encode(signature),
+                    user_handle: None,
+                },
+            }
+        }
+    }
+
+    fn client_data(type_: &str, origin: &str, challenge: &str) -> String {
+        let client_data = serde_json::json!({
+            "type": type_,
+            "challenge": challenge,
+            "origin": origin,
+            "crossOrigin": false,
+        });
+        URL_SAFE_NO_PAD.encode(client_data.to_string())
+    }
+
+    fn service() -> WebAuthnService {
+        WebAuthnService::new(WebAuthnConfiguration::default(), DatabaseConnection::init())
+    }
+
+    fn stored_credential(registration: &VerifiedRegistration) -> CredentialModel {
+        CredentialModel {
+            id: surrealdb::RecordId::from((CredentialModel::table_name(), "test")),
+            account_id: surrealdb::RecordId::from((AccountModel::table_name(), "test")),
+            credential_id: URL_SAFE_NO_PAD.encode(&registration.credential_id),
+            public_key: URL_SAFE_NO_PAD.encode(&registration.public_key),
+            algorithm: registration.algorithm,
+            sign_count: registration.sign_count,
+            name: "Passkey".to_string(),
+            transports: Vec::new(),
+            created_at: chrono::Utc::now().into(),
+            last_used_at: None,
+        }
+    }
+
+    fn rejection_reason<T>(result: Result<T, AuthenticationServiceError>) -> String {
+        match result {
+            Err(AuthenticationServiceError::ClientError(
+                AuthenticationClientError::InvalidWebAuthnResponse(reason),
+            )) => reason,
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("response was accepted"),
+        }
+    }
+
+    fn registers_and_signs_in(mut authenticator: SoftwareAuthenticator, algorithm: i64) {
+        let service = service();
+        let registration = service
+            .verify_registration(&authenticator.register(ORIGIN, CHALLENGE), CHALLENGE)
+            .unwrap();
+        assert_eq!(registration.algorithm, algorithm);
+        assert_eq!(registration.credential_id, authenticator.credential_id);
+
+        let mut credential = stored_credential(&registration);
+        for expected_sign_count in 1..=2 {
+            let assertion = authenticator.assert(ORIGIN, CHALLENGE);
+            let sign_count = service
+                .verify_assertion(&assertion, CHALLENGE, &credential)
+                .unwrap();
+            assert_eq!(sign_count, expected_sign_count);
+            credential.sign_count = sign_count;
+        }
+    }
+
+    #[test]
+    fn registers_and_signs_in_with_es256() {
+        registers_and_signs_in(SoftwareAuthenticator::es256(), COSE_ALG_ES256);
+    }
+
+    #[test]
+    fn registers_and_signs_in_with_eddsa() {
+        registers_and_signs_in(SoftwareAuthenticator::eddsa(), COSE_ALG_EDDSA);
+    }
+
+    #[test]
+    fn rejects_wrong_origin() {
+        let service = service();
+        let mut authenticator = SoftwareAuthenticator::es256();
+        let origin = "https://attacker.example";
+
+        assert_eq!(
+            rejection_reason(
+                service.verify_registration(&authenticator.register(origin, CHALLENGE), CHALLENGE)
+            ),
+            "origin not allowed"
+        );
+
+        let registration = service
+            .verify_registration(&authenticator.register(ORIGIN, CHALLENGE), CHALLENGE)
+            .unwrap();
+        let credential = stored_credential(&registration);
+        assert_eq!(
+            rejection_reason(service.verify_assertion(
+                &authenticator.assert(origin, CHALLENGE),
+                CHALLENGE,
+                &credential
+            )),
+            "origin not allowed"
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_challenge() {
+        let service = service();
+        let mut authenticator = SoftwareAuthenticator::eddsa();
+
+        assert_eq!(
+            rejection_reason(
+                service.verify_registration(&authenticator.register(ORIGIN, "other"), CHALLENGE)
+            ),
+            "challenge mismatch"
+        );
+
+        let registration = service
+            .verify_registration(&authenticator.register(ORIGIN, CHALLENGE), CHALLENGE)
+            .unwrap();
+        let credential = stored_credential(&registration);
+        assert_eq!(
+            rejection_reason(service.verify_assertion(
+                &authenticator.assert(ORIGIN, "other"),
+                CHALLENGE,
+                &credential
+            )),
+            "challenge mismatch"
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_rp_id() {
+        let service = service();
+        let mut authenticator = SoftwareAuthenticator::es256();
+        let registration = service
+            .verify_registration(&authenticator.register(ORIGIN, CHALLENGE), CHALLENGE)
+            .unwrap();
+        let credential = stored_credential(&registration);
+
+        authenticator.rp_id = "attacker.example".to_string();
+        assert_eq!(
+            rejection_reason(
+                service.verify_registration(&authenticator.register(ORIGIN, CHALLENGE), CHALLENGE)
+            ),
+            "relying party id mismatch"
+        );
+        assert_eq!(
+            rejection_reason(service.verify_assertion(
+                &authenticator.assert(ORIGIN, CHALLENGE),
+                CHALLENGE,
+                &credential
+            )),
+            "relying party id mismatch"
+        );
+    }
+
+    #[test]
+    fn rejects_sign_count_rollback() {
+        let service = service();
+        let mut authenticator = SoftwareAuthenticator::es256();
+        let registration = service
+            .verify_registration(&authenticator.register(ORIGIN, CHALLENGE), CHALLENGE)
+            .unwrap();
+        let mut credential = stored_credential(&registration);
+        credential.sign_count = 5;
+
+        // A cloned authenticator replays a counter at or below the stored one
+        authenticator.sign_count = 4;
+        assert_eq!(
+            rejection_reason(service.verify_assertion(
+                &authenticator.assert(ORIGIN, CHALLENGE),
+                CHALLENGE,
+                &credential
+            )),
+            "signature counter did not increase"
+        );
+        authenticator.sign_count = 2;
+        assert_eq!(
+            rejection_reason(service.verify_assertion(
+                &authenticator.assert(ORIGIN, CHALLENGE),
+                CHALLENGE,
+                &credential
+            )),
+            "signature counter did not increase"
+        );
+
+        authenticator.sign_count = 5;
+        assert_eq!(
+            service
+                .verify_assertion(
+                    &authenticator.assert(ORIGIN, CHALLENGE),
+                    CHALLENGE,
+                    &credential
+                )
+                .unwrap(),
+            6
+        );
+    }
+}